@@ -0,0 +1,101 @@
+use card_game_4::engine::CardLibrary;
+use card_game_4::game::events::GameSetup;
+use card_game_4::game::modes::GameMode;
+use card_game_4::game::replay::LATEST_REPLAY;
+use card_game_4::network::protocol::DEFAULT_PORT;
+use card_game_4::network::server::Server;
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::time::Duration;
+
+//the game is given up if the players did not all join by then
+const WAIT_FOR_PLAYERS: Duration = Duration::from_secs(10 * 60);
+
+//headless authoritative server, the players connect with ServerClient
+//usage: server [--address <address>] [--set <id|name>] [--players <count>] [--seed <seed>]
+//       [--starting-player <player>] [--mode <free-for-all|hunter|team-emperor|raid>]
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let mut address = format!("0.0.0.0:{DEFAULT_PORT}");
+    let mut set = "default".to_string();
+    let mut setup = GameSetup {
+        seed: rand::random(),
+        ..Default::default()
+    };
+    while let Some(arg) = args.next() {
+        let value = args.next().unwrap_or_default();
+        let parsed = match arg.as_str() {
+            "--address" => {
+                address = value;
+                Ok(())
+            }
+            "--set" => {
+                set = value;
+                Ok(())
+            }
+            "--players" => value.parse().map(|v| setup.players = v),
+            "--seed" => value.parse().map(|v| setup.seed = v),
+            "--starting-player" => value.parse().map(|v| setup.starting_player = v),
+            "--mode" => {
+                setup.mode = match value.as_str() {
+                    "free-for-all" => GameMode::FreeForAll,
+                    "hunter" => GameMode::Hunter,
+                    "team-emperor" => GameMode::TeamEmperor,
+                    "raid" => GameMode::Raid,
+                    _ => {
+                        eprintln!("unknown mode: {value}");
+                        return;
+                    }
+                };
+                Ok(())
+            }
+            _ => {
+                eprintln!("unknown argument: {arg}");
+                return;
+            }
+        };
+        if let Err(err) = parsed {
+            eprintln!("invalid value for {arg}: {err}");
+            return;
+        }
+    }
+    if !setup.mode.valid_player_count(setup.players) {
+        eprintln!(
+            "{:?} can not be played by {} players",
+            setup.mode, setup.players
+        );
+        return;
+    }
+    let library = match CardLibrary::load("assets", &set) {
+        Ok(library) => Arc::new(library),
+        Err(err) => {
+            eprintln!("could not load the set {set}: {err}");
+            return;
+        }
+    };
+    setup.set = library.id;
+    let listener = match TcpListener::bind(&address) {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("could not listen on {address}: {err}");
+            return;
+        }
+    };
+    println!(
+        "waiting for {} players on {address} (seed {})",
+        setup.players, setup.seed
+    );
+    let mut server = match Server::start(&listener, library, setup.event(), WAIT_FOR_PLAYERS) {
+        Ok(server) => server,
+        Err(err) => {
+            eprintln!("could not start the game: {err}");
+            return;
+        }
+    };
+    println!("game started");
+    server.run();
+    println!("game over: {:?}", server.state.turn());
+    if let Err(err) = server.replay.write(LATEST_REPLAY) {
+        eprintln!("could not save the replay: {err}");
+    }
+}
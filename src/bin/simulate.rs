@@ -0,0 +1,139 @@
+use card_game_4::bots::simulation::{play_game, SimulationStats};
+use card_game_4::bots::BotKind;
+use card_game_4::engine::CardLibrary;
+use card_game_4::game::events::GameSetup;
+use card_game_4::game::modes::GameMode;
+use std::sync::Arc;
+
+//headless games between bots, writes the statistics of each card of the set
+//usage: simulate [--games <count>] [--set <id|name>] [--players <count>] [--mode <mode>]
+//       [--bots <bot>,<bot>...] [--seed <seed>] [--output <path>]
+//bots are given by seat, the last one fills the remaining seats (see BotKind::parse)
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let mut games = 100u32;
+    let mut set = "default".to_string();
+    let mut bots = vec![BotKind::Heuristic];
+    let mut output = "simulations/stats.ron".to_string();
+    let mut setup = GameSetup {
+        seed: rand::random(),
+        ..Default::default()
+    };
+    while let Some(arg) = args.next() {
+        let value = args.next().unwrap_or_default();
+        let parsed = match arg.as_str() {
+            "--games" => value.parse().map(|v| games = v).map_err(|e| e.to_string()),
+            "--set" => {
+                set = value;
+                Ok(())
+            }
+            "--players" => value
+                .parse()
+                .map(|v| setup.players = v)
+                .map_err(|e| e.to_string()),
+            "--mode" => ron::from_str::<GameMode>(&value)
+                .map(|v| setup.mode = v)
+                .map_err(|e| e.to_string()),
+            "--seed" => value
+                .parse()
+                .map(|v| setup.seed = v)
+                .map_err(|e| e.to_string()),
+            "--bots" => value
+                .split(',')
+                .map(|bot| BotKind::parse(bot.trim()).ok_or(format!("unknown bot {bot}")))
+                .collect::<Result<Vec<_>, _>>()
+                .map(|v| bots = v),
+            "--output" => {
+                output = value;
+                Ok(())
+            }
+            _ => {
+                eprintln!("unknown argument: {arg}");
+                return;
+            }
+        };
+        if let Err(err) = parsed {
+            eprintln!("invalid value for {arg}: {err}");
+            return;
+        }
+    }
+    if bots.is_empty() {
+        eprintln!("no bot given");
+        return;
+    }
+    let library = match CardLibrary::load("assets", &set) {
+        Ok(library) => Arc::new(library),
+        Err(err) => {
+            eprintln!("could not load the set {set}: {err}");
+            return;
+        }
+    };
+    setup.set = library.id;
+    //the games are spread over the cores, each with its own bots
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get() as u32);
+    let mut stats = SimulationStats::default();
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.min(games.max(1)))
+            .map(|thread| {
+                let (library, bots) = (library.clone(), &bots);
+                scope.spawn(move || {
+                    let mut stats = SimulationStats::default();
+                    for game in (thread..games).step_by(threads as usize) {
+                        let seed = setup.seed.wrapping_add(game as u64);
+                        //the first player changes each game, so no seat is favored
+                        let setup = GameSetup {
+                            seed,
+                            starting_player: (game % setup.players as u32) as u8,
+                            ..setup
+                        };
+                        let mut seats: Vec<_> = (0..setup.players as usize)
+                            .map(|seat| bots[seat.min(bots.len() - 1)].build(seed))
+                            .collect();
+                        match play_game(library.clone(), setup.event(), &mut seats) {
+                            Ok(record) => stats.add(&record),
+                            Err(err) => eprintln!("game {game} (seed {seed}) failed: {err}"),
+                        }
+                    }
+                    stats
+                })
+            })
+            .collect();
+        for worker in workers {
+            if let Ok(worker) = worker.join() {
+                stats.merge(worker);
+            }
+        }
+    });
+    stats.finish();
+    println!(
+        "{} games ({} unfinished), {:.1} turns on average, wins by seat {:?}",
+        stats.games, stats.unfinished, stats.average_turns, stats.wins_by_seat
+    );
+    let mut cards: Vec<_> = stats.cards.iter().collect();
+    cards.sort_by(|a, b| b.1.buy_rate.total_cmp(&a.1.buy_rate));
+    println!("card                      bought  buy rate  win rate  avg turn");
+    for (name, card) in cards {
+        //the explorers are never offered in the market row
+        let buy_rate = match card.offered {
+            0 => "-".to_string(),
+            _ => format!("{:.0}%", 100. * card.buy_rate),
+        };
+        println!(
+            "{name:<25} {:>6}  {buy_rate:>8}  {:>7.0}%  {:>8.1}",
+            card.bought,
+            100. * card.win_rate_when_owned,
+            card.average_turn_bought
+        );
+    }
+    for (name, faction) in &stats.factions {
+        println!(
+            "{name}: {:.0}% of {} players",
+            100. * faction.win_rate,
+            faction.players
+        );
+    }
+    match stats.write(&output) {
+        Ok(_) => println!("statistics written to {output}"),
+        Err(err) => eprintln!("{err}"),
+    }
+}
@@ -0,0 +1,256 @@
+use crate::bots::Bot;
+use crate::cards::actions::{Action, ActionCondition, ActionSet};
+use crate::engine::replay::ReplayInput;
+use crate::engine::{CardKey, Decision, GameState};
+use crate::game::events::{BuyFrom, GameEvents};
+use crate::prelude::*;
+
+//simple rule based player: plays its whole hand, uses every ability that does not cost a card,
+//buys the most expensive card it can afford (favoring the factions it already owns), scraps its
+//cheap cards for their bonus, then attacks the outposts in the way before the weakest opponent
+#[derive(Debug, Default, Clone)]
+pub struct HeuristicBot;
+
+//rough worth of an action for the player resolving it
+fn action_value(action: &Action, life: i32) -> f32 {
+    match *action {
+        Action::Eco(n) | Action::Atk(n) => n as f32,
+        Action::Life(n) if life < 20 => n as f32,
+        Action::Life(n) => n as f32 / 2.,
+        Action::Draw(n) => 1.5 * n as f32,
+        Action::DrawPer(_) => 1.5,
+        Action::Discard | Action::ScrapSelf => -1.,
+        Action::ScrapHand | Action::ScrapDiscard | Action::ScrapHandOrDiscard => 0.5,
+        Action::ScrapMarket => 0.2,
+        Action::DestroyBase => 1.5,
+        Action::EnemyDiscard | Action::PlayerDiscard(_) => 1.,
+        Action::FreeBuy { max_cost, .. } => max_cost as f32,
+        Action::NextBuyOnDeck(_) => 0.5,
+        Action::Unique(_) => 1.,
+    }
+}
+
+fn set_value(set: &ActionSet, life: i32) -> f32 {
+    match set {
+        ActionSet::None => 0.,
+        ActionSet::One(action) => action_value(action, life),
+        ActionSet::All(sets) => sets.iter().map(|s| set_value(s, life)).sum(),
+        ActionSet::Optional(sets) => sets.iter().map(|s| set_value(s, life)).sum::<f32>().max(0.),
+        ActionSet::Choose { max, of, .. } => {
+            let mut values: Vec<f32> = of.iter().map(|s| set_value(s, life)).collect();
+            values.sort_by(|a, b| b.total_cmp(a));
+            values
+                .into_iter()
+                .take(*max as usize)
+                .filter(|v| *v > 0.)
+                .sum()
+        }
+        ActionSet::If { then, .. } => then.iter().map(|s| set_value(s, life)).sum::<f32>() / 2.,
+    }
+}
+
+impl HeuristicBot {
+    //cost of the card, plus a bonus per card of the same faction already owned
+    fn buy_score(state: &GameState, player: u8, card: CardKey) -> f32 {
+        let bought = &state.cards()[card];
+        let owned = state
+            .cards()
+            .iter()
+            .filter(|c| {
+                c.owner == CardOwners::Player(player)
+                    && c.factions.0.iter().any(|f| bought.factions.0.contains(f))
+            })
+            .count();
+        bought.cost as f32 + 0.5 * owned.min(6) as f32
+    }
+
+    //affordable cards, best first
+    fn buys(state: &GameState, player: u8) -> Vec<GameEvents> {
+        let Some(economy) = state.player(player).map(|p| p.economy) else {
+            return Vec::new();
+        };
+        let market = state
+            .stack(CardOwners::Market, Stacks::MarketRow)
+            .into_iter()
+            .map(|c| (c, BuyFrom::Market(state.cards()[c].index as u8)));
+        let joker = state
+            .card_at(CardOwners::Market, Stacks::JokerDeck, 0)
+            .map(|c| (c, BuyFrom::Joker));
+        let mut buys: Vec<(f32, GameEvents)> = market
+            .chain(joker)
+            .filter(|(card, _)| state.cards()[*card].cost <= economy)
+            .map(|(card, from)| {
+                //the joker (explorers) is only bought when nothing else is affordable
+                let score = match from {
+                    BuyFrom::Joker => 0.,
+                    BuyFrom::Market(_) => Self::buy_score(state, player, card),
+                };
+                (score, GameEvents::BuyCard(from))
+            })
+            .collect();
+        buys.sort_by(|a, b| b.0.total_cmp(&a.0));
+        buys.into_iter().map(|(_, event)| event).collect()
+    }
+
+    //abilities of the cards in play, scrap abilities only if scrap is true
+    pub fn activations(state: &GameState, player: u8, scrap: bool) -> Vec<GameEvents> {
+        let owner = CardOwners::Player(player);
+        let mut out = Vec::new();
+        for (stack, base) in [(Stacks::UsedCards, false), (Stacks::Bases, true)] {
+            for card in state.stack(owner, stack) {
+                let card = &state.cards()[card];
+                //only the cheap cards are worth scrapping for their bonus
+                if scrap && card.cost > 2 {
+                    continue;
+                }
+                for action in 0..card.actions.len() {
+                    let is_scrap = matches!(
+                        card.actions.condition(action),
+                        Some(ActionCondition::Scrap(_))
+                    );
+                    if is_scrap == scrap {
+                        out.push(GameEvents::ActivateCard {
+                            base,
+                            index: card.index as u32,
+                            action,
+                        });
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    //the weakest opponent that can be hit: directly, or through the weakest of their outposts that
+    //can be destroyed this turn (damage on a base is healed at the end of the turn)
+    fn attack(state: &GameState, player: u8) -> Option<GameEvents> {
+        let attack = state.player(player)?.attack;
+        if attack <= 0 {
+            return None;
+        }
+        let mut targets = state.targets(player);
+        targets.sort_by_key(|&p| state.player(p).map_or(i32::MAX, |p| p.life));
+        targets.into_iter().find_map(|target| {
+            let outposts: Vec<CardKey> = state
+                .stack(CardOwners::Player(target), Stacks::Bases)
+                .into_iter()
+                .filter(|&c| matches!(state.cards()[c].kind, CardKinds::Outpost(_)))
+                .collect();
+            if outposts.is_empty() {
+                return Some(GameEvents::Attack {
+                    player: target,
+                    as_much_as_possible: true,
+                    base_index: None,
+                });
+            }
+            let outpost = outposts
+                .into_iter()
+                .map(|c| &state.cards()[c])
+                .filter(|c| c.life.0 <= attack)
+                .min_by_key(|c| c.life.0)?;
+            Some(GameEvents::Attack {
+                player: target,
+                as_much_as_possible: true,
+                base_index: Some(outpost.index as u32),
+            })
+        })
+    }
+
+    //every event worth trying this turn, in order of preference: the first one accepted by the
+    //rules is played, PassTurn always is
+    pub fn ranked(state: &GameState) -> Vec<GameEvents> {
+        let Some(player) = state.current_player() else {
+            return Vec::new();
+        };
+        let mut out: Vec<GameEvents> = state
+            .stack(CardOwners::Player(player), Stacks::Hand)
+            .first()
+            .map(|&card| GameEvents::PlayCard(state.cards()[card].index as u8))
+            .into_iter()
+            .collect();
+        out.extend(Self::activations(state, player, false));
+        out.extend(Self::buys(state, player));
+        out.extend(Self::activations(state, player, true));
+        out.extend(Self::attack(state, player));
+        out.push(GameEvents::PassTurn);
+        out
+    }
+
+    //the cards least worth keeping (starting cards first) for discards and scraps of our own
+    //cards, the most valuable ones when they belong to an opponent (destroying a base)
+    fn select(
+        state: &GameState,
+        player: u8,
+        min: usize,
+        max: usize,
+        cards: &[CardKey],
+    ) -> Vec<CardKey> {
+        let mut cards = cards.to_vec();
+        let ours = cards
+            .iter()
+            .all(|&c| state.cards()[c].owner == CardOwners::Player(player));
+        let market = cards
+            .iter()
+            .all(|&c| state.cards()[c].owner == CardOwners::Market);
+        cards.sort_by_key(|&c| state.cards()[c].cost);
+        let count = if ours {
+            //optional picks are only used on the starting cards
+            let starting = cards
+                .iter()
+                .filter(|&&c| state.cards()[c].cost == 0)
+                .count();
+            starting.max(min)
+        } else if market {
+            min
+        } else {
+            cards.reverse();
+            min.max(1)
+        };
+        cards.truncate(count.min(max));
+        cards
+    }
+
+    fn choose(state: &GameState, player: u8, set: &ActionSet) -> Vec<u8> {
+        let life = state.player(player).map_or(50, |p| p.life);
+        let (min, max) = set.choice_bounds();
+        let mut options: Vec<(u8, f32)> = set
+            .options()
+            .iter()
+            .enumerate()
+            .map(|(index, option)| (index as u8, set_value(option, life)))
+            .collect();
+        options.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let positive = options.iter().filter(|(_, v)| *v > 0.).count() as u8;
+        let mut choices: Vec<u8> = options
+            .into_iter()
+            .take(positive.max(min).min(max) as usize)
+            .map(|(index, _)| index)
+            .collect();
+        choices.sort();
+        choices
+    }
+}
+
+impl Bot for HeuristicBot {
+    fn play(&mut self, state: &GameState) -> GameEvents {
+        let legal = state.legal_events();
+        Self::ranked(state)
+            .into_iter()
+            .find(|event| legal.contains(event))
+            .unwrap_or(GameEvents::PassTurn)
+    }
+
+    fn answer(&mut self, state: &GameState, decision: &Decision) -> ReplayInput {
+        match decision {
+            Decision::Selection {
+                player,
+                min,
+                max,
+                cards,
+            } => ReplayInput::Select(Self::select(state, *player, *min, *max, cards)),
+            Decision::Choice { player, set } => {
+                ReplayInput::Choose(Self::choose(state, *player, set))
+            }
+        }
+    }
+}
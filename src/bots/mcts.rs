@@ -0,0 +1,288 @@
+use crate::bots::heuristic::HeuristicBot;
+use crate::bots::Bot;
+use crate::engine::replay::ReplayInput;
+use crate::engine::{Decision, GameState};
+use crate::game::events::GameEvents;
+use crate::prelude::*;
+use crate::states::turn::TurnStates;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::{Duration, Instant};
+
+//how long the bot thinks before each input, the difficulty scales with it
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Budget {
+    Iterations(u32),
+    Time(Duration),
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Self::Iterations(200)
+    }
+}
+
+//answers tried for a selection or a choice, past this the other subsets are ignored
+const MAX_ANSWERS: usize = 16;
+
+//determinized monte carlo tree search: each iteration samples a board consistent with what the
+//bot can see (GameState::determinize), walks down the tree with UCB among the inputs legal on that
+//board (single observer ISMCTS), then finishes with a heuristic rollout
+#[derive(Debug, Clone)]
+pub struct MctsBot {
+    pub budget: Budget,
+    pub exploration: f32,
+    pub depth: usize, //inputs played by a rollout before the board is scored
+    rng: StdRng,
+    rollout: HeuristicBot,
+}
+
+#[derive(Default)]
+struct Node {
+    children: Vec<Child>,
+}
+
+struct Child {
+    input: ReplayInput,
+    visits: u32,
+    available: u32, //iterations where this input was legal
+    reward: f32,    //sum of the rewards of the player giving this input
+    node: Node,
+}
+
+impl MctsBot {
+    pub fn new(budget: Budget, seed: u64) -> Self {
+        Self {
+            budget,
+            exploration: 0.7,
+            depth: 40,
+            rng: StdRng::seed_from_u64(seed),
+            rollout: HeuristicBot,
+        }
+    }
+
+    //the input of the root with the most visits
+    fn search(&mut self, state: &GameState) -> Option<ReplayInput> {
+        let player = state
+            .pending()
+            .map(Decision::player)
+            .or(state.current_player())?;
+        let mut root = Node::default();
+        let start = Instant::now();
+        let mut iterations = 0;
+        while match self.budget {
+            Budget::Iterations(max) => iterations < max,
+            Budget::Time(time) => iterations == 0 || start.elapsed() < time,
+        } {
+            let mut sample = state.clone();
+            sample.determinize(player, self.rng.gen());
+            self.iterate(&mut root, &mut sample);
+            iterations += 1;
+        }
+        root.children
+            .into_iter()
+            .max_by_key(|c| c.visits)
+            .map(|c| c.input)
+    }
+
+    //one descent of the tree, returns the rewards of each player
+    fn iterate(&mut self, node: &mut Node, state: &mut GameState) -> Vec<f32> {
+        let Some(player) = state
+            .pending()
+            .map(Decision::player)
+            .or(state.current_player())
+        else {
+            return rewards(state);
+        };
+        let inputs = inputs(state);
+        for child in node.children.iter_mut() {
+            if inputs.contains(&child.input) {
+                child.available += 1;
+            }
+        }
+        let unexplored: Vec<&ReplayInput> = inputs
+            .iter()
+            .filter(|i| node.children.iter().all(|c| c.input != **i))
+            .collect();
+        let (index, expanded) = if !unexplored.is_empty() {
+            let input = unexplored[self.rng.gen_range(0..unexplored.len())].clone();
+            node.children.push(Child {
+                input,
+                visits: 0,
+                available: 1,
+                reward: 0.,
+                node: Node::default(),
+            });
+            (node.children.len() - 1, true)
+        } else {
+            let exploration = self.exploration;
+            let ucb = |c: &Child| {
+                c.reward / c.visits as f32
+                    + exploration * ((c.available as f32).ln() / c.visits as f32).sqrt()
+            };
+            let Some(index) = (0..node.children.len())
+                .filter(|&i| inputs.contains(&node.children[i].input))
+                .max_by(|&a, &b| ucb(&node.children[a]).total_cmp(&ucb(&node.children[b])))
+            else {
+                return rewards(state);
+            };
+            (index, false)
+        };
+        let child = &mut node.children[index];
+        if state.input(child.input.clone()).is_err() {
+            return rewards(state);
+        }
+        let result = if expanded {
+            self.simulate(state)
+        } else {
+            self.iterate(&mut child.node, state)
+        };
+        child.visits += 1;
+        child.reward += result.get(player as usize).copied().unwrap_or(0.);
+        result
+    }
+
+    //heuristic playout, events are tried in place: a refused event leaves the state untouched,
+    //which saves a copy of the board for each candidate
+    fn simulate(&mut self, state: &mut GameState) -> Vec<f32> {
+        for _ in 0..self.depth {
+            let played = match state.pending() {
+                Some(decision) => {
+                    let answer = self.rollout.answer(state, decision);
+                    state.input(answer).is_ok()
+                }
+                None => HeuristicBot::ranked(state)
+                    .into_iter()
+                    .any(|event| state.apply(event).is_ok()),
+            };
+            if !played {
+                break;
+            }
+        }
+        rewards(state)
+    }
+}
+
+impl Default for MctsBot {
+    fn default() -> Self {
+        Self::new(Budget::default(), 0)
+    }
+}
+
+//1 for every player of the winning team, otherwise the chances of each player against their
+//strongest opponent, from their life and the cost of the cards they own
+fn rewards(state: &GameState) -> Vec<f32> {
+    let players = state.players().len();
+    let mode = state.mode();
+    if let TurnStates::GameOver { winner, .. } = state.turn() {
+        return (0..players as u8)
+            .map(|p| {
+                if mode.won(p, winner, players as u8) {
+                    1.
+                } else {
+                    0.
+                }
+            })
+            .collect();
+    }
+    let mut strength: Vec<f32> = state
+        .players()
+        .iter()
+        .map(|p| p.life.max(0) as f32)
+        .collect();
+    for card in state.cards() {
+        if let CardOwners::Player(p) = card.owner {
+            strength[p as usize] += card.cost as f32;
+        }
+    }
+    //teammates are not opponents, whatever their strength
+    let team = |p: usize| mode.team(p as u8, players as u8);
+    (0..players)
+        .map(|p| {
+            let opponent = (0..players)
+                .filter(|&o| team(o) != team(p) && state.players()[o].life > 0)
+                .map(|o| strength[o])
+                .fold(0., f32::max);
+            1. / (1. + ((opponent - strength[p]) / 10.).exp())
+        })
+        .collect()
+}
+
+fn valid(state: &GameState, input: &ReplayInput) -> bool {
+    state.clone().input(input.clone()).is_ok()
+}
+
+//inputs worth searching in this state, the answers to a decision are checked by the engine
+fn inputs(state: &GameState) -> Vec<ReplayInput> {
+    let Some(decision) = state.pending() else {
+        return events(state).into_iter().map(ReplayInput::Event).collect();
+    };
+    //the heuristic answer is always tried, whatever the number of subsets
+    let mut out = vec![HeuristicBot.answer(state, decision)];
+    out.extend(state.legal_answers(MAX_ANSWERS));
+    let mut seen = Vec::new();
+    out.retain(|input| {
+        let new = !seen.contains(input) && valid(state, input);
+        seen.push(input.clone());
+        new
+    });
+    out
+}
+
+fn events(state: &GameState) -> Vec<GameEvents> {
+    let Some(player) = state.current_player() else {
+        return Vec::new();
+    };
+    //conceding is never searched, and attacking one point at a time is the same as all at once
+    let legal: Vec<GameEvents> = state
+        .legal_events()
+        .into_iter()
+        .filter(|e| {
+            !matches!(
+                e,
+                GameEvents::Concede(_)
+                    | GameEvents::Attack {
+                        as_much_as_possible: false,
+                        ..
+                    }
+            )
+        })
+        .collect();
+    //playing a card never hurts, the order of the hand does not matter
+    if let Some(&event) = legal.iter().find(|e| matches!(e, GameEvents::PlayCard(_))) {
+        return vec![event];
+    }
+    //neither does an ability that costs nothing, the search is kept for the real decisions
+    if let Some(event) = HeuristicBot::activations(state, player, false)
+        .into_iter()
+        .find(|e| legal.contains(e))
+    {
+        return vec![event];
+    }
+    //damage is never worth keeping and does not depend on the rest of the turn
+    let attacks: Vec<GameEvents> = legal
+        .iter()
+        .copied()
+        .filter(|e| matches!(e, GameEvents::Attack { .. }))
+        .collect();
+    if !attacks.is_empty() {
+        return attacks;
+    }
+    legal
+}
+
+impl Bot for MctsBot {
+    fn play(&mut self, state: &GameState) -> GameEvents {
+        match self.search(state) {
+            Some(ReplayInput::Event(event)) => event,
+            _ => self.rollout.play(state),
+        }
+    }
+
+    fn answer(&mut self, state: &GameState, decision: &Decision) -> ReplayInput {
+        match self.search(state) {
+            Some(input @ (ReplayInput::Select(_) | ReplayInput::Choose(_))) => input,
+            _ => self.rollout.answer(state, decision),
+        }
+    }
+}
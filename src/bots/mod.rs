@@ -0,0 +1,285 @@
+pub mod heuristic;
+pub mod mcts;
+pub mod simulation;
+
+use crate::cards::transition::PlayBackSpeed;
+use crate::engine::replay::ReplayInput;
+use crate::engine::{Decision, GameState};
+use crate::game::event_handlers::EngineBoard;
+use crate::game::events::{GameEvent, GameEvents};
+use crate::game::history::board_at_rest;
+use crate::game::routines::card_action::{Selectable, Selection};
+use crate::game::routines::selection::{answer_selection, selection_open};
+use crate::game::GameStates;
+use crate::prelude::*;
+use crate::states::turn::TurnStates;
+use crate::ui::choice_ui::{answer_choice, ChoiceRoot};
+use bevy::tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task};
+use heuristic::HeuristicBot;
+use mcts::{Budget, MctsBot};
+use std::time::Duration;
+
+//a player driven by the program, it sees the board through the rules engine and answers with the
+//same vocabulary as a human: events on its turn, selections and choices when prompted
+pub trait Bot: Send + Sync {
+    fn play(&mut self, state: &GameState) -> GameEvents;
+    fn answer(&mut self, state: &GameState, decision: &Decision) -> ReplayInput;
+}
+
+//next input of a bot on a headless game, None once the game is over
+pub fn decide(bot: &mut dyn Bot, state: &GameState) -> Option<ReplayInput> {
+    match state.pending() {
+        Some(decision) => Some(bot.answer(state, decision)),
+        None => state
+            .current_player()
+            .map(|_| ReplayInput::Event(bot.play(state))),
+    }
+}
+
+//the bots that can take a seat, written "heuristic", "mcts", "mcts:<iterations>" or
+//"mcts:<milliseconds>ms" on the command line
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BotKind {
+    Heuristic,
+    Mcts(Budget),
+}
+
+impl BotKind {
+    pub fn parse(text: &str) -> Option<Self> {
+        let (name, budget) = text.split_once(':').unwrap_or((text, ""));
+        match name {
+            "heuristic" if budget.is_empty() => Some(Self::Heuristic),
+            "mcts" if budget.is_empty() => Some(Self::Mcts(Budget::default())),
+            "mcts" => match budget.strip_suffix("ms") {
+                Some(time) => time
+                    .parse()
+                    .ok()
+                    .map(|t| Self::Mcts(Budget::Time(Duration::from_millis(t)))),
+                None => budget
+                    .parse()
+                    .ok()
+                    .map(|i| Self::Mcts(Budget::Iterations(i))),
+            },
+            _ => None,
+        }
+    }
+
+    pub fn build(&self, seed: u64) -> Box<dyn Bot> {
+        match *self {
+            Self::Heuristic => Box::new(HeuristicBot),
+            Self::Mcts(budget) => Box::new(MctsBot::new(budget, seed)),
+        }
+    }
+}
+
+//a bot passes its turn after this many events, in case the app keeps refusing what it plays
+const MAX_ACTIONS_PER_TURN: usize = 60;
+
+//what the app asks a bot: an event on its turn, or the answer to a decision
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Question {
+    Play(u8),
+    Answer(Decision),
+}
+
+impl Question {
+    fn player(&self) -> u8 {
+        match self {
+            Question::Play(player) => *player,
+            Question::Answer(decision) => decision.player(),
+        }
+    }
+}
+
+//a search running on the async compute pool, it gives the bot back with its input
+struct Thinking {
+    question: Question,
+    task: Task<(Box<dyn Bot>, ReplayInput)>,
+}
+
+//seats played by a bot in the bevy app
+#[derive(Resource)]
+pub struct Bots {
+    pub seats: Vec<u8>,
+    pub delay: Duration, //wait between two inputs so the moves can be followed
+    bot: Box<dyn Bot>,
+    board: EngineBoard,
+    elapsed: Duration,
+    actions: (TurnStates, usize),
+    thinking: Option<Thinking>,
+}
+
+impl Bots {
+    pub fn new(seats: Vec<u8>, bot: impl Bot + 'static) -> Self {
+        Self {
+            seats,
+            delay: Duration::from_millis(600),
+            bot: Box::new(bot),
+            board: EngineBoard::default(),
+            elapsed: Duration::ZERO,
+            actions: (TurnStates::Setup, 0),
+            thinking: None,
+        }
+    }
+
+    pub fn plays(&self, player: u8) -> bool {
+        self.seats.contains(&player)
+    }
+
+    //the search of the previous bot is dropped
+    pub fn set_bot(&mut self, bot: Box<dyn Bot>) {
+        self.bot = bot;
+        self.thinking = None;
+    }
+
+    pub fn thinking(&self) -> bool {
+        self.thinking.is_some()
+    }
+
+    //true once the delay since the last input elapsed
+    fn ready(&mut self, world: &World) -> bool {
+        let speed = world
+            .get_resource::<PlayBackSpeed>()
+            .map_or(1., |s| s.0.max(0.));
+        self.elapsed += world.resource::<Time>().delta();
+        if self.elapsed < self.delay.mul_f32(speed) {
+            return false;
+        }
+        self.elapsed = Duration::ZERO;
+        true
+    }
+
+    //the bot is lent to the task for the time of the search, a search with a time budget would
+    //freeze the window if it ran in the frame
+    fn think(&mut self, state: GameState, question: Question) {
+        let mut bot = std::mem::replace(&mut self.bot, Box::new(HeuristicBot));
+        let asked = question.clone();
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            let input = match &asked {
+                Question::Play(_) => ReplayInput::Event(bot.play(&state)),
+                Question::Answer(decision) => bot.answer(&state, decision),
+            };
+            (bot, input)
+        });
+        self.thinking = Some(Thinking { question, task });
+    }
+
+    //the input of the search once it is over, with the question it answers
+    fn thought(&mut self) -> Option<(Question, ReplayInput)> {
+        let thinking = self.thinking.as_mut()?;
+        let (bot, input) = block_on(future::poll_once(&mut thinking.task))?;
+        let thinking = self.thinking.take()?;
+        self.bot = bot;
+        Some((thinking.question, input))
+    }
+}
+
+impl Default for Bots {
+    fn default() -> Self {
+        Self::new(vec![1], HeuristicBot)
+    }
+}
+
+//what the app waits for from the player who has to act, if anything
+fn question(world: &mut World) -> Option<Question> {
+    match world.resource::<State<GameStates>>().get() {
+        GameStates::MainLoop => {
+            let TurnStates::PlayerTurn(player) = *world.resource::<State<TurnStates>>().get()
+            else {
+                return None;
+            };
+            board_at_rest(world).then_some(Question::Play(player))
+        }
+        GameStates::SelectionInput => {
+            if !selection_open(world) {
+                return None;
+            }
+            let selection = world.resource::<Selection>();
+            let (player, min, max) = (selection.player, selection.min, selection.max);
+            let mut cards: Vec<usize> = world
+                .query_filtered::<&CardId, With<Selectable>>()
+                .iter(world)
+                .map(|id| id.0)
+                .collect();
+            cards.sort();
+            Some(Question::Answer(Decision::Selection {
+                player,
+                min,
+                max,
+                cards,
+            }))
+        }
+        GameStates::ChoiceInput => {
+            let root = world.query::<&ChoiceRoot>().get_single(world).ok()?;
+            if root.finished {
+                return None;
+            }
+            Some(Question::Answer(Decision::Choice {
+                player: root.player,
+                set: root.set.clone(),
+            }))
+        }
+    }
+}
+
+//on the turn of a bot, play one event each time the board is at rest, and answer the selections
+//and choices asked to it. the search runs in the background, its input is played on the frame it
+//finishes
+pub fn bot_input(world: &mut World) {
+    let question = question(world);
+    world.resource_scope(|world, mut bots: Mut<Bots>| {
+        if bots.thinking() {
+            let Some((asked, input)) = bots.thought() else {
+                return;
+            };
+            //the board may have moved during the search, when a player conceded
+            if question.as_ref() == Some(&asked) {
+                apply(world, input);
+            }
+            return;
+        }
+        let Some(question) = question else {
+            return;
+        };
+        if !bots.plays(question.player()) || !bots.ready(world) {
+            return;
+        }
+        if let Question::Play(player) = question {
+            let turn = TurnStates::PlayerTurn(player);
+            if bots.actions.0 != turn {
+                bots.actions = (turn, 0);
+            }
+            bots.actions.1 += 1;
+            if bots.actions.1 > MAX_ACTIONS_PER_TURN {
+                world.resource_mut::<GameEvent>().push(GameEvents::PassTurn);
+                return;
+            }
+        }
+        match bots.board.state(world) {
+            Some(state) => bots.think(state, question),
+            None if matches!(question, Question::Play(_)) => {
+                world.resource_mut::<GameEvent>().push(GameEvents::PassTurn);
+            }
+            None => {}
+        }
+    });
+}
+
+fn apply(world: &mut World, input: ReplayInput) {
+    match input {
+        ReplayInput::Select(cards) => answer_selection(world, &cards),
+        ReplayInput::Choose(choices) => answer_choice(world, &choices),
+        ReplayInput::Event(event) => {
+            world.resource_mut::<GameEvent>().push(event);
+        }
+    }
+}
+
+pub struct BotPlugin;
+
+impl Plugin for BotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Bots>()
+            .add_systems(Update, bot_input.run_if(in_state(AppStates::Playing)));
+    }
+}
@@ -0,0 +1,260 @@
+use crate::bots::heuristic::HeuristicBot;
+use crate::bots::{decide, Bot};
+use crate::cards::components::factions::CardFaction;
+use crate::engine::replay::ReplayInput;
+use crate::engine::{CardLibrary, GameState, RuleError};
+use crate::game::events::GameEvents;
+use crate::prelude::*;
+use crate::states::turn::TurnStates;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+use thiserror::Error;
+
+//a game still running after this many inputs is stopped and counted as unfinished
+pub const MAX_INPUTS: usize = 5000;
+
+//what happened in one headless game, as needed by the statistics
+#[derive(Debug, Clone, Default)]
+pub struct GameRecord {
+    pub winner: Option<u8>,
+    pub winners: Vec<u8>, //every seat of the winning team, empty if the game did not finish
+    pub turns: u32,
+    pub offered: Vec<String>, //cards that appeared in the market row
+    pub acquired: Vec<(u8, String, u32)>, //player, card, turn of the player it was acquired on
+    pub factions: Vec<Option<CardFaction>>, //faction most acquired by each player, if any
+}
+
+//play a game between bots (one per seat) and note every card entering the market row or a deck
+pub fn play_game(
+    library: Arc<CardLibrary>,
+    setup: GameEvents,
+    bots: &mut [Box<dyn Bot>],
+) -> Result<GameRecord, RuleError> {
+    let mut state = GameState::new(library);
+    state.apply(setup)?;
+    let mut record = GameRecord::default();
+    let mut turns = vec![0; state.players().len()];
+    let mut current = None;
+    let mut previous: Vec<(CardOwners, Stacks)> = Vec::new();
+    for _ in 0..MAX_INPUTS {
+        if let Some(player) = new_turn(&state, &mut current) {
+            turns[player as usize] += 1;
+            record.turns += 1;
+        }
+        for (id, card) in state.cards().iter().enumerate() {
+            let before = previous.get(id);
+            if card.owner == CardOwners::Market
+                && card.stack == Stacks::MarketRow
+                && before.is_none_or(|(_, s)| *s != Stacks::MarketRow)
+            {
+                record.offered.push(card.name.clone());
+            }
+            if let (CardOwners::Player(p), Some((CardOwners::Market, _))) = (card.owner, before) {
+                record
+                    .acquired
+                    .push((p, card.name.clone(), turns[p as usize]));
+            }
+        }
+        previous = state.cards().iter().map(|c| (c.owner, c.stack)).collect();
+        let Some(player) = state
+            .pending()
+            .map(|d| d.player())
+            .or(state.current_player())
+        else {
+            break;
+        };
+        let Some(input) = bots
+            .get_mut(player as usize)
+            .and_then(|bot| decide(bot.as_mut(), &state))
+        else {
+            break;
+        };
+        //a refused input is replaced by the heuristic answer to the pending decision, or ends the
+        //turn, so a confused bot can not stall the game. if even that is refused, the game is
+        //stopped and counted as unfinished
+        if state.input(input).is_err() {
+            let fallback = match state.pending() {
+                Some(decision) => HeuristicBot.answer(&state, decision),
+                None => ReplayInput::Event(GameEvents::PassTurn),
+            };
+            if state.input(fallback).is_err() {
+                break;
+            }
+        }
+    }
+    //the turn started by the last input of a game stopped at MAX_INPUTS
+    if new_turn(&state, &mut current).is_some() {
+        record.turns += 1;
+    }
+    if let TurnStates::GameOver { winner, .. } = state.turn() {
+        record.winner = Some(winner);
+        record.winners = state.mode().winners(winner, state.players().len() as u8);
+    }
+    record.factions = (0..state.players().len() as u8)
+        .map(|player| {
+            let mut counts: Vec<(CardFaction, usize)> = Vec::new();
+            for (_, name, _) in record.acquired.iter().filter(|(p, ..)| *p == player) {
+                let Some(card) = state.cards().iter().find(|c| c.name == *name) else {
+                    continue;
+                };
+                for faction in card.factions.0.iter() {
+                    match counts.iter_mut().find(|(f, _)| f == faction) {
+                        Some((_, count)) => *count += 1,
+                        None => counts.push((*faction, 1)),
+                    }
+                }
+            }
+            counts
+                .into_iter()
+                .filter(|(faction, _)| *faction != CardFaction::Neutral)
+                .max_by_key(|(_, count)| *count)
+                .map(|(faction, _)| faction)
+        })
+        .collect();
+    Ok(record)
+}
+
+//the player whose turn started since the last call, if any
+fn new_turn(state: &GameState, current: &mut Option<u8>) -> Option<u8> {
+    if state.current_player() == *current {
+        return None;
+    }
+    *current = state.current_player();
+    *current
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CardStats {
+    pub offered: u32,     //times it appeared in the market row
+    pub bought: u32,      //times a player acquired it
+    pub buy_rate: f32,    //bought / offered
+    pub owned_games: u32, //finished games and players it was acquired in (once per player and game)
+    pub owned_wins: u32,
+    pub win_rate_when_owned: f32,
+    pub average_turn_bought: f32,
+    #[serde(skip)]
+    turns_bought: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct FactionStats {
+    pub players: u32, //players of finished games who acquired mostly cards of this faction
+    pub wins: u32,
+    pub win_rate: f32,
+}
+
+//aggregate of many games, written as RON for the designers
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SimulationStats {
+    pub games: u32,
+    pub unfinished: u32,
+    pub average_turns: f32,
+    pub wins_by_seat: Vec<u32>,
+    pub cards: BTreeMap<String, CardStats>,
+    pub factions: BTreeMap<String, FactionStats>,
+    #[serde(skip)]
+    turns: u64,
+}
+
+#[derive(Error, Debug)]
+pub enum StatsError {
+    #[error("could not write the statistics: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not write RON: {0}")]
+    Ron(#[from] ron::Error),
+}
+
+impl SimulationStats {
+    pub fn add(&mut self, record: &GameRecord) {
+        self.games += 1;
+        self.turns += record.turns as u64;
+        if record.winner.is_none() {
+            self.unfinished += 1;
+        }
+        //in team modes, every seat of the winning team is credited with the win
+        for &winner in &record.winners {
+            if self.wins_by_seat.len() <= winner as usize {
+                self.wins_by_seat.resize(winner as usize + 1, 0);
+            }
+            self.wins_by_seat[winner as usize] += 1;
+        }
+        for name in &record.offered {
+            self.cards.entry(name.clone()).or_default().offered += 1;
+        }
+        //the win rates only count games that have a winner
+        let finished = record.winner.is_some();
+        let mut owners: Vec<(u8, &String)> = Vec::new();
+        for (player, name, turn) in &record.acquired {
+            let card = self.cards.entry(name.clone()).or_default();
+            card.bought += 1;
+            card.turns_bought += *turn as u64;
+            if finished && !owners.contains(&(*player, name)) {
+                owners.push((*player, name));
+                card.owned_games += 1;
+                if record.winners.contains(player) {
+                    card.owned_wins += 1;
+                }
+            }
+        }
+        for (player, faction) in record.factions.iter().enumerate() {
+            if let (true, Some(faction)) = (finished, faction) {
+                let stats = self.factions.entry(format!("{faction:?}")).or_default();
+                stats.players += 1;
+                if record.winners.contains(&(player as u8)) {
+                    stats.wins += 1;
+                }
+            }
+        }
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        self.games += other.games;
+        self.unfinished += other.unfinished;
+        self.turns += other.turns;
+        if self.wins_by_seat.len() < other.wins_by_seat.len() {
+            self.wins_by_seat.resize(other.wins_by_seat.len(), 0);
+        }
+        for (seat, wins) in other.wins_by_seat.into_iter().enumerate() {
+            self.wins_by_seat[seat] += wins;
+        }
+        for (name, other) in other.cards {
+            let card = self.cards.entry(name).or_default();
+            card.offered += other.offered;
+            card.bought += other.bought;
+            card.owned_games += other.owned_games;
+            card.owned_wins += other.owned_wins;
+            card.turns_bought += other.turns_bought;
+        }
+        for (name, other) in other.factions {
+            let faction = self.factions.entry(name).or_default();
+            faction.players += other.players;
+            faction.wins += other.wins;
+        }
+    }
+
+    //fill the rates and averages from the counters
+    pub fn finish(&mut self) {
+        let ratio = |a: u64, b: u64| if b == 0 { 0. } else { a as f32 / b as f32 };
+        self.average_turns = ratio(self.turns, self.games as u64);
+        for card in self.cards.values_mut() {
+            card.buy_rate = ratio(card.bought as u64, card.offered as u64);
+            card.win_rate_when_owned = ratio(card.owned_wins as u64, card.owned_games as u64);
+            card.average_turn_bought = ratio(card.turns_bought, card.bought as u64);
+        }
+        for faction in self.factions.values_mut() {
+            faction.win_rate = ratio(faction.wins as u64, faction.players as u64);
+        }
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), StatsError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+use crate::cards::components::factions::CardFaction;
+use crate::players::{FactionTurnTracker, PlayerTurnTracker};
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+//turn counters of a player readable by card actions (see Action::DrawPer and ActionSet::If)
+//ex: DrawPer(Faction(Blob, ShipsPlayed)), If(counter: Any(BasesInPlay), at_least: 2, then: [One(Draw(2))])
+#[derive(Serialize, Deserialize, Debug, Reflect, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Counter {
+    CardsDrawn,
+    LifeLost,
+    LifeGained,
+    Any(FactionCounter), //counted over every card, whatever their factions
+    Faction(CardFaction, FactionCounter), //counted over the cards of a faction
+}
+
+//fields of FactionTurnTracker
+#[derive(Serialize, Deserialize, Debug, Reflect, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum FactionCounter {
+    BasesInPlay,
+    BasesPlayed,
+    BasesDiscarded,
+    BasesScrapped,
+    ShipsInPlay,
+    ShipsPlayed,
+    ShipsDiscarded,
+    ShipsScrapped,
+}
+
+impl FactionCounter {
+    pub fn value(&self, tracker: &FactionTurnTracker) -> usize {
+        match self {
+            FactionCounter::BasesInPlay => tracker.bases_in_play,
+            FactionCounter::BasesPlayed => tracker.bases_played,
+            FactionCounter::BasesDiscarded => tracker.bases_discarded,
+            FactionCounter::BasesScrapped => tracker.bases_scrapped,
+            FactionCounter::ShipsInPlay => tracker.ships_in_play,
+            FactionCounter::ShipsPlayed => tracker.ships_played,
+            FactionCounter::ShipsDiscarded => tracker.ship_discarded,
+            FactionCounter::ShipsScrapped => tracker.ships_scrapped,
+        }
+    }
+}
+
+impl Counter {
+    pub fn value(&self, tracker: &PlayerTurnTracker) -> usize {
+        match self {
+            Counter::CardsDrawn => tracker.cards_drawn,
+            Counter::LifeLost => tracker.life_lost,
+            Counter::LifeGained => tracker.life_gained,
+            Counter::Any(counter) => counter.value(&tracker.common),
+            Counter::Faction(faction, counter) => tracker
+                .faction_counters
+                .get(faction)
+                .map_or(0, |t| counter.value(t)),
+        }
+    }
+}
+
+impl Display for FactionCounter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FactionCounter::BasesInPlay => f.write_str("bases in play"),
+            FactionCounter::BasesPlayed => f.write_str("bases played this turn"),
+            FactionCounter::BasesDiscarded => f.write_str("bases discarded this turn"),
+            FactionCounter::BasesScrapped => f.write_str("bases scrapped this turn"),
+            FactionCounter::ShipsInPlay => f.write_str("ships in play"),
+            FactionCounter::ShipsPlayed => f.write_str("ships played this turn"),
+            FactionCounter::ShipsDiscarded => f.write_str("ships discarded this turn"),
+            FactionCounter::ShipsScrapped => f.write_str("ships scrapped this turn"),
+        }
+    }
+}
+
+impl Display for Counter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Counter::CardsDrawn => f.write_str("cards drawn this turn"),
+            Counter::LifeLost => f.write_str("life lost this turn"),
+            Counter::LifeGained => f.write_str("life gained this turn"),
+            Counter::Any(counter) => f.write_fmt(format_args!("{counter}")),
+            Counter::Faction(faction, counter) => {
+                f.write_fmt(format_args!("{faction:?} {counter}"))
+            }
+        }
+    }
+}
@@ -1,5 +1,7 @@
-mod uniques;
+pub mod counters;
+pub mod uniques;
 
+use crate::cards::actions::counters::{Counter, FactionCounter};
 use crate::cards::actions::uniques::Uniques;
 use crate::cards::assets::Card;
 use crate::cards::components::factions::CardFaction;
@@ -27,6 +29,7 @@ pub enum Action {
     Atk(u8),            //gain attack
     Life(u8),           //gain life
     Draw(u8),           //draw x cards
+    DrawPer(Counter),   //draw a card per unit of a turn counter
     Discard,            //discard a card
     ScrapHand,          //scrap a card from hand
     ScrapDiscard,       //scrap a card from the discard pile
@@ -35,6 +38,7 @@ pub enum Action {
     ScrapSelf, //scrap the card that triggered this action (not to confuse with the scrap condition, this can be used as part of Do/Ally action)
     DestroyBase, //target and put in discard pile target base
     EnemyDiscard, //force targeted player to discard
+    PlayerDiscard(u8), //the given player discards a card at the start of their turn, picked by EnemyDiscard
     FreeBuy {
         //allow imediate aquisition of a card on the market matching the condition
         min_cost: u8,
@@ -60,7 +64,7 @@ impl Default for Action {
 //- after:
 //actions: [(None, One(Atk(1)))]
 
-#[derive(Serialize, Deserialize, Debug, Default, Reflect, Copy, Clone, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Default, Reflect, Clone, Eq, PartialEq)]
 pub enum ActionCondition {
     #[default]
     None,
@@ -142,6 +146,9 @@ impl Display for Action {
                     f.write_fmt(format_args!("draw {qty} cards"))
                 }
             }
+            Action::DrawPer(counter) => {
+                f.write_fmt(format_args!("draw a card for each of your {counter}"))
+            }
             Action::Discard => f.write_str("discard a card"),
             Action::ScrapHand => f.write_str("scrap a card in your hand"),
             Action::ScrapDiscard => f.write_str("scrap a card in your discard pile"),
@@ -149,6 +156,9 @@ impl Display for Action {
             Action::ScrapMarket => f.write_str("scrap a card in the trade row"),
             Action::DestroyBase => f.write_str("destroy target base"),
             Action::EnemyDiscard => f.write_str("target opponent discard a card"),
+            Action::PlayerDiscard(player) => {
+                f.write_fmt(format_args!("player {player} discards a card"))
+            }
             Action::FreeBuy {
                 min_cost,
                 max_cost,
@@ -172,47 +182,142 @@ impl Display for Action {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Default, Reflect, Copy, Clone, Eq, PartialEq)]
+//action sets are trees: leaves are actions, nodes are sequences, optional groups and choices
+//ex: All([One(Atk(2)), Choose(min: 1, max: 1, of: [One(Draw(1)), One(Eco(2))])])
+//reflected as an opaque value: bevy_reflect cannot derive recursive types
+#[derive(Serialize, Deserialize, Debug, Default, Reflect, Clone, Eq, PartialEq)]
+#[reflect_value(Debug, Default, PartialEq, Serialize, Deserialize)]
 pub enum ActionSet {
     #[default]
     None,
-    // One(Vec<Action>),
-    // Multi(Vec<Action>),
     One(Action),
-    Optional(Action),
-    OneAndOptional(Action, Action),
-    Two(Action, Action),
-    Three(Action, Action, Action),
-    Four(Action, Action, Action, Action),
-    AnyOf2(Action, Action),
-    OneOf2(Action, Action),
+    All(Vec<ActionSet>),      //every set, in order
+    Optional(Vec<ActionSet>), //the player may apply the whole group (in order) or nothing
+    Choose {
+        //the player picks between min and max of the sets, applied in order
+        min: u8,
+        max: u8,
+        of: Vec<ActionSet>,
+    },
+    If {
+        //checked when the set resolves, the sets are applied in order if the counter is high enough
+        counter: Counter,
+        at_least: u8,
+        then: Vec<ActionSet>,
+    },
 }
 
-impl Display for ActionSet {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+impl ActionSet {
+    //EnemyDiscard with several possible targets: the player picks who discards, by player id
+    pub fn discard_choice(targets: Vec<u8>) -> Self {
+        ActionSet::Choose {
+            min: 1,
+            max: 1,
+            of: targets
+                .into_iter()
+                .map(|target| ActionSet::One(Action::PlayerDiscard(target)))
+                .collect(),
+        }
+    }
+
+    //sets to resolve for an If node, given the turn counters of the owner
+    pub fn checked(&self, tracker: &PlayerTurnTracker) -> Vec<ActionSet> {
         match self {
-            ActionSet::None => f.write_str("BUG: empty action set"),
-            ActionSet::One(action) => f.write_fmt(format_args!("{action}.")),
-            ActionSet::Optional(option) => f.write_fmt(format_args!("you may {option}.")),
-            ActionSet::OneAndOptional(action, option) => {
-                f.write_fmt(format_args!("{action}, you may {option}."))
+            ActionSet::If {
+                counter,
+                at_least,
+                then,
+            } if counter.value(tracker) >= *at_least as usize => then.clone(),
+            _ => vec![],
+        }
+    }
+
+    //true if resolving this node requires an input from the player (choice ui)
+    pub fn is_choice(&self) -> bool {
+        matches!(self, ActionSet::Optional(_) | ActionSet::Choose { .. })
+    }
+
+    //the sets offered by a choice node, indexed like the choice buttons
+    pub fn options(&self) -> Vec<ActionSet> {
+        match self {
+            ActionSet::Optional(group) => vec![ActionSet::All(group.clone())],
+            ActionSet::Choose { of, .. } => of.clone(),
+            _ => vec![],
+        }
+    }
+
+    //inclusive bounds on the number of options that can be picked for a choice node
+    pub fn choice_bounds(&self) -> (u8, u8) {
+        match self {
+            ActionSet::Optional(_) => (0, 1),
+            ActionSet::Choose { min, max, of } => {
+                let count = of.len() as u8;
+                ((*min).min(count), (*max).min(count))
             }
-            ActionSet::Two(first, second) => f.write_fmt(format_args!("{first}, {second}.")),
-            ActionSet::Three(first, second, third) => {
-                f.write_fmt(format_args!("{first}, {second}, {third}."))
+            _ => (0, 0),
+        }
+    }
+
+    //returns the sets to resolve in order for the given (sorted, deduplicated) choices,
+    //or None if the choices are not valid for this node
+    pub fn resolve_choices(&self, choices: &[u8]) -> Option<Vec<ActionSet>> {
+        let (min, max) = self.choice_bounds();
+        let options = self.options();
+        if !self.is_choice()
+            || choices.len() < min as usize
+            || choices.len() > max as usize
+            || choices.windows(2).any(|w| w[0] >= w[1])
+        {
+            return None;
+        }
+        choices
+            .iter()
+            .map(|&c| options.get(c as usize).cloned())
+            .collect()
+    }
+
+    //text of the set without the final dot, used by the choice buttons
+    pub fn label(&self) -> String {
+        let join = |sets: &Vec<ActionSet>, separator: &str| {
+            sets.iter()
+                .map(|s| s.label())
+                .collect::<Vec<_>>()
+                .join(separator)
+        };
+        match self {
+            ActionSet::None => "BUG: empty action set".to_string(),
+            ActionSet::One(action) => format!("{action}"),
+            ActionSet::All(sets) => join(sets, ", "),
+            ActionSet::Optional(sets) => format!("you may {}", join(sets, ", ")),
+            ActionSet::Choose { min: 1, max: 1, of } => join(of, " or "),
+            ActionSet::Choose { min: 0, max, of } if *max as usize >= of.len() => {
+                format!("you may {}", join(of, " and/or "))
             }
-            ActionSet::Four(first, second, third, fourth) => {
-                f.write_fmt(format_args!("{first}, {second}, {third}, {fourth}.",))
+            ActionSet::Choose { min, max, of } if min == max => {
+                format!("choose {min}: {}", join(of, " / "))
             }
-            ActionSet::AnyOf2(first, second) => {
-                f.write_fmt(format_args!("you may {first} and/or {second}."))
+            ActionSet::Choose { min, max, of } => {
+                format!("choose {min} to {max}: {}", join(of, " / "))
             }
-            ActionSet::OneOf2(first, second) => f.write_fmt(format_args!("{first} or {second}.")),
+            ActionSet::If {
+                counter,
+                at_least,
+                then,
+            } => format!("if you have {at_least}+ {counter}, {}", join(then, ", ")),
         }
     }
 }
 
-#[derive(Component, Reflect, Default, Debug, Clone)]
+impl Display for ActionSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ActionSet::None => f.write_str("BUG: empty action set"),
+            _ => f.write_fmt(format_args!("{}.", self.label())),
+        }
+    }
+}
+
+#[derive(Component, Reflect, Serialize, Deserialize, Default, Debug, Clone, Eq, PartialEq)]
 #[reflect(Component)]
 pub struct CardActions {
     actions: Vec<(ActionCondition, bool)>,
@@ -235,6 +340,10 @@ impl CardActions {
         }
     }
 
+    pub fn condition(&self, index: u8) -> Option<&ActionCondition> {
+        self.actions.get(index as usize).map(|(c, _)| c)
+    }
+
     pub fn is_action_real(&self, index: u8) -> bool {
         self.actions
             .get(index as usize)
@@ -288,7 +397,10 @@ impl Plugin for GameActionsPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<Action>()
             .register_type::<ActionSet>()
+            .register_type::<Counter>()
+            .register_type::<FactionCounter>()
             .register_type::<ActionCondition>()
-            .register_type::<CardActions>();
+            .register_type::<CardActions>()
+            .register_type::<uniques::StealthNeedleCopy>();
     }
 }
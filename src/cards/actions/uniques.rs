@@ -1,18 +1,26 @@
-use crate::cards::actions::KindMask;
+use crate::cards::actions::{Action, CardActions, KindMask};
 use crate::cards::components::factions::CardFaction;
 use crate::game::routines::{RoutineManager, SelectionFilter};
-use crate::players::{Player, PlayerTurnTracker};
+use crate::game::triggers::{Trigger, TriggerDuration, TriggerListener, Triggers};
+use crate::players::{player_counter_mut, PlayerTurnTracker};
 use crate::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 
+//inserted on a Stealth Needle copying another ship, holds what it had before the copy
+//so it can be restored once the needle leaves play
+#[derive(Component, Reflect, Serialize, Deserialize, Default, Debug, Clone, Eq, PartialEq)]
+#[reflect(Component)]
+pub struct StealthNeedleCopy {
+    pub actions: CardActions,
+    pub factions: CardFactions,
+}
+
 #[derive(Serialize, Deserialize, Debug, Default, Reflect, Copy, Clone, Eq, PartialEq)]
 pub enum Uniques {
     #[default]
     Unimplemented,
-    BlobWorld,
     BrainWorld,
-    EmbassyYacht,
     FleetHQ,
     RecyclingStation,
     StealthNeedle,
@@ -21,28 +29,6 @@ pub enum Uniques {
 impl Uniques {
     pub fn card_action(&self, world: &mut World, card: Entity, owner: u8) {
         match self {
-            Uniques::BlobWorld => {
-                //simple check for the amount of blob cards played and queueing X draws
-                let draw = if owner == 0 {
-                    world
-                        .query_filtered::<&PlayerTurnTracker, With<Player<0>>>()
-                        .get_single_mut(world)
-                } else {
-                    world
-                        .query_filtered::<&PlayerTurnTracker, With<Player<1>>>()
-                        .get_single_mut(world)
-                }
-                .unwrap()
-                .faction_counters
-                .get(&CardFaction::Blob)
-                .unwrap()
-                .ships_played;
-                let mut routines = world.resource_mut::<RoutineManager>();
-                routines.finish();
-                for _ in 0..draw {
-                    routines.draw(owner, false);
-                }
-            }
             Uniques::BrainWorld => {
                 //equivalent to scrap hand/discard, but we use the size of the return to redraw cards
                 let mut selection =
@@ -70,31 +56,16 @@ impl Uniques {
                     );
                 }
             }
-            Uniques::EmbassyYacht => {
-                //simple check for the amount of bases in play and queuing 2 draws
-                let draw = if owner == 0 {
-                    world
-                        .query_filtered::<&PlayerTurnTracker, With<Player<0>>>()
-                        .get_single_mut(world)
-                } else {
-                    world
-                        .query_filtered::<&PlayerTurnTracker, With<Player<1>>>()
-                        .get_single_mut(world)
-                }
-                .unwrap()
-                .common
-                .bases_in_play
-                    >= 2;
-                let mut routines = world.resource_mut::<RoutineManager>();
-                routines.finish();
-                if draw {
-                    routines.draw(owner, false);
-                    routines.draw(owner, false);
-                }
-            }
             Uniques::FleetHQ => {
-                //toggle a flag/resource/player component that detects when a ship is played and increase the attack
-                world.resource_mut::<RoutineManager>().finish(); //FIXME: for now, do nothing
+                //every ship played after this one this turn gives 1 attack
+                world.resource_mut::<Triggers>().register(TriggerListener {
+                    card,
+                    owner,
+                    trigger: Trigger::ShipPlayed,
+                    duration: TriggerDuration::ThisTurn,
+                    action: Action::Atk(1),
+                });
+                world.resource_mut::<RoutineManager>().finish();
             }
             Uniques::RecyclingStation => {
                 //equivalent to immediate discard, but we use the size of the return to redraw cards
@@ -122,20 +93,87 @@ impl Uniques {
                 }
             }
             Uniques::StealthNeedle => {
-                //now that one hard card to do...
-                world.resource_mut::<RoutineManager>().finish(); //FIXME: for now, do nothing
+                //select another ship played this turn, the needle itself can't be copied
+                let mut selection =
+                    world.resource_mut::<crate::game::routines::card_action::Selection>();
+                if selection.finished {
+                    selection.finished = false;
+                    let test: Vec<Entity> = selection.cards.drain(..).collect();
+                    world.resource_mut::<RoutineManager>().finish();
+                    if let Some(&copied) = test.iter().find(|&&c| c != card) {
+                        copy_ship(world, card, copied, owner);
+                    }
+                } else {
+                    world.resource_mut::<RoutineManager>().selection_without(
+                        card,
+                        0,
+                        1,
+                        vec![SelectionFilter {
+                            stacks: vec![Stacks::UsedCards],
+                            owners: vec![CardOwners::Player(owner)],
+                            kinds: KindMask::Ships,
+                            min_cost: 0,
+                            max_cost: 255,
+                        }],
+                    );
+                }
             }
             _ => {}
         }
     }
 }
 
+//the needle takes the actions of the copied ship (unused) and its factions in addition to its own,
+//then plays the copied primary ability as if it was just played
+fn copy_ship(world: &mut World, needle: Entity, copied: Entity, owner: u8) {
+    let (Some(actions), Some(factions), Some(original_actions), Some(original_factions)) = (
+        world.get::<CardActions>(copied).cloned(),
+        world.get::<CardFactions>(copied).cloned(),
+        world.get::<CardActions>(needle).cloned(),
+        world.get::<CardFactions>(needle).cloned(),
+    ) else {
+        return;
+    };
+    let mut actions = actions;
+    actions.reset();
+    let gained: Vec<CardFaction> = factions
+        .0
+        .difference(&original_factions.0)
+        .copied()
+        .collect();
+    let mut merged = original_factions.clone();
+    merged.0.extend(gained.iter().copied());
+    let mut entity = world.entity_mut(needle);
+    for faction in gained.iter() {
+        faction.insert_world(&mut entity);
+    }
+    entity.insert((
+        actions,
+        merged,
+        StealthNeedleCopy {
+            actions: original_actions,
+            factions: original_factions,
+        },
+    ));
+    let Some(mut tracker) = player_counter_mut::<PlayerTurnTracker>(world, owner) else {
+        return;
+    };
+    tracker.ship_gained_factions(&gained);
+    let tracker = tracker.clone();
+    let set = world
+        .get_mut::<CardActions>(needle)
+        .and_then(|mut a| a.use_action(0, &tracker).map(|(set, _)| set.clone()));
+    if let Some(set) = set {
+        world
+            .resource_mut::<RoutineManager>()
+            .activate_card(owner, needle, 0, set);
+    }
+}
+
 impl Display for Uniques {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::BlobWorld => f.write_str("draw a card for each Blob card that you've played this turn"),
             Self::BrainWorld => f.write_str("Scrap up to 2 cards from you hand and/or discard pile. Draw a card for each card scrapped this way"),
-            Self::EmbassyYacht => f.write_str("if you have 2+ bases in play, draw 2 cards"),
             Self::FleetHQ => f.write_str("Whenever you play a ship this turn, gain 1 attack"),
             Self::RecyclingStation => f.write_str("discard up to two cards, then draw that many cards"),
             Self::StealthNeedle => f.write_str("Copy another ship you've played this turn. Stealth Needle has that ship's faction in addition to Machine Cult"),
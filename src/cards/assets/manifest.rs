@@ -0,0 +1,150 @@
+use crate::cards::assets::serializer::AssetLoadderError;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+//folder of the sets in the assets, each set is a folder holding a manifest and its decks
+pub const SETS: &str = "sets";
+pub const MANIFEST: &str = "set.ron";
+
+#[derive(Reflect, Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum DeckRole {
+    Market, //shuffled into the market deck, the market row is drawn from it
+    Player, //starting deck, every player gets a copy
+    Joker,  //always available in the market (the explorers)
+}
+
+impl DeckRole {
+    pub const ALL: [DeckRole; 3] = [DeckRole::Market, DeckRole::Player, DeckRole::Joker];
+}
+
+//rules a set can change, the defaults are the ones of the base game
+#[derive(Reflect, Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq)]
+#[serde(default)]
+pub struct SetRules {
+    pub starting_life: i32, //life of a player in free-for-all, the other modes are scaled from it
+    pub hand_size: u8,      //cards drawn at the end of a turn
+    pub first_hand_size: u8, //cards drawn by the starting player before their first turn
+    pub market_size: u8,    //cards in the market row
+}
+
+impl Default for SetRules {
+    fn default() -> Self {
+        Self {
+            starting_life: 50,
+            hand_size: 5,
+            first_hand_size: 3,
+            market_size: 5,
+        }
+    }
+}
+
+//content of assets/sets/<folder>/set.ron
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SetManifest {
+    pub id: u8, //given by the setup event, so saves, replays and network games find the set back
+    pub name: String,
+    pub decks: HashMap<DeckRole, String>, //paths from the folder of the set
+    #[serde(default = "default_card_paths")]
+    pub card_paths: Vec<String>, //folders of the assets searched in order for the cards of the decks
+    #[serde(default)]
+    pub rules: SetRules,
+}
+
+fn default_card_paths() -> Vec<String> {
+    vec!["cards".to_string()]
+}
+
+impl SetManifest {
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, AssetLoadderError> {
+        Ok(ron::de::from_bytes(&fs::read(path)?)?)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SetEntry {
+    pub folder: String,
+    pub manifest: SetManifest,
+}
+
+impl SetEntry {
+    //path of a deck from the assets folder
+    pub fn deck(&self, role: DeckRole) -> Result<String, AssetLoadderError> {
+        match self.manifest.decks.get(&role) {
+            Some(path) => Ok(format!("{SETS}/{}/{path}", self.folder)),
+            None => Err(AssetLoadderError::MissingDeck(
+                self.manifest.name.clone(),
+                role,
+            )),
+        }
+    }
+
+    //path of a card from the assets folder: the first search path holding it
+    pub fn card(&self, assets: impl AsRef<Path>, name: &str) -> String {
+        let paths = self
+            .manifest
+            .card_paths
+            .iter()
+            .map(|folder| format!("{folder}/{name}.card.ron"))
+            .collect::<Vec<_>>();
+        paths
+            .iter()
+            .find(|path| assets.as_ref().join(path).is_file())
+            .or(paths.first())
+            .cloned()
+            .unwrap_or_else(|| format!("cards/{name}.card.ron"))
+    }
+}
+
+//every set found in the assets, sorted by id
+#[derive(Resource, Debug, Clone, Default)]
+pub struct SetCatalog(pub Vec<SetEntry>);
+
+impl SetCatalog {
+    //folders of assets/sets with a manifest, the broken ones and the duplicated ids are skipped
+    pub fn discover(assets: impl AsRef<Path>) -> Self {
+        let mut sets: Vec<SetEntry> = Vec::new();
+        let folders = fs::read_dir(assets.as_ref().join(SETS))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|entry| entry.path().join(MANIFEST).is_file());
+        for folder in folders {
+            let name = folder.file_name().to_string_lossy().to_string();
+            match SetManifest::read(folder.path().join(MANIFEST)) {
+                Ok(manifest) => sets.push(SetEntry {
+                    folder: name,
+                    manifest,
+                }),
+                Err(err) => warn!("could not read the manifest of the set {name}: {err}"),
+            }
+        }
+        sets.sort_by(|a, b| (a.manifest.id, &a.folder).cmp(&(b.manifest.id, &b.folder)));
+        sets.dedup_by(|b, a| {
+            let duplicate = a.manifest.id == b.manifest.id;
+            if duplicate {
+                warn!(
+                    "the sets {} and {} have the same id, {} is ignored",
+                    a.folder, b.folder, b.folder
+                );
+            }
+            duplicate
+        });
+        Self(sets)
+    }
+
+    pub fn get(&self, id: u8) -> Option<&SetEntry> {
+        self.0.iter().find(|set| set.manifest.id == id)
+    }
+
+    //a set given by its id, its folder or its name
+    pub fn find(&self, key: &str) -> Option<&SetEntry> {
+        key.parse().ok().and_then(|id| self.get(id)).or_else(|| {
+            self.0
+                .iter()
+                .find(|set| set.folder == key || set.manifest.name.eq_ignore_ascii_case(key))
+        })
+    }
+}
@@ -1,17 +1,24 @@
+pub mod manifest;
 pub mod prepare_models;
 pub mod serializer;
 
 use crate::cards::actions::{ActionCondition, ActionSet};
+use crate::cards::assets::manifest::{DeckRole, SetCatalog, SetEntry, SetRules};
 use crate::cards::assets::prepare_models::prepare_models;
-use crate::cards::assets::serializer::{CardLoadder, DeckLoadder};
+use crate::cards::assets::serializer::{AssetLoadderError, CardLoadder, DeckLoadder};
 use crate::cards::components::factions::{CardFaction, CardFactions};
 use crate::cards::components::kinds::CardKinds;
+use crate::engine::CardLibrary;
+use crate::game::replay::ReplayFile;
+use crate::network::client::ServerGame;
+use crate::network::NetworkSession;
 use crate::states::app::AppStates;
 use bevy::asset::LoadedFolder;
 use bevy::gltf::Gltf;
 use bevy::prelude::*;
 use bevy::utils::HashMap;
 use serde::Deserialize;
+use std::sync::Arc;
 
 #[derive(Asset, Debug, Deserialize, Default, Reflect, Clone)]
 #[reflect(Debug)]
@@ -29,56 +36,102 @@ pub struct Card {
 #[reflect(Debug)]
 pub struct Deck(pub Vec<(u32, String)>);
 
+//decks of the set of the current game, loaded when a setup event asks for it
 #[derive(Resource, Default, Debug, Reflect, Clone)]
 #[reflect(Resource)]
 pub struct LoadedSet {
-    pub ready: bool,
+    pub id: Option<u8>,
+    pub ready: bool, //decks and their cards loaded
+    pub rules: SetRules,
     pub market_deck: Handle<Deck>,
     pub player_deck: Handle<Deck>,
     pub joker_deck: Handle<Deck>,
-    pub cards: HashMap<String, Handle<Card>>,
+    pub cards: HashMap<String, Handle<Card>>, //cards of every set loaded so far
 }
 
-fn load_decks(mut set: ResMut<LoadedSet>, asset_server: Res<AssetServer>) {
-    //TODO: add selection of set/game mode
+//rules engine library of the LoadedSet, built once its cards are loaded
+#[derive(Resource, Default)]
+pub struct LoadedLibrary(pub Option<Arc<CardLibrary>>);
+
+impl LoadedSet {
+    //start loading the decks of a set, load_cards loads their cards once they are read
+    pub fn load(
+        &mut self,
+        set: &SetEntry,
+        asset_server: &AssetServer,
+    ) -> Result<(), AssetLoadderError> {
+        self.market_deck = asset_server.load(set.deck(DeckRole::Market)?);
+        self.player_deck = asset_server.load(set.deck(DeckRole::Player)?);
+        self.joker_deck = asset_server.load(set.deck(DeckRole::Joker)?);
+        self.id = Some(set.manifest.id);
+        self.rules = set.manifest.rules;
+        self.ready = false;
+        Ok(())
+    }
+}
 
-    // let set_name = "debug_bases";
-    let set_name = "default";
-    set.market_deck = asset_server.load(format!("sets/{set_name}/market.deck.ron"));
-    set.player_deck = asset_server.load(format!("sets/{set_name}/player.deck.ron"));
-    set.joker_deck = asset_server.load(format!("sets/{set_name}/explorer.deck.ron"));
+fn discover_sets(mut commands: Commands) {
+    commands.insert_resource(SetCatalog::discover("assets"));
 }
 
 fn load_cards(
-    mut once: Local<bool>,
     mut set: ResMut<LoadedSet>,
+    catalog: Res<SetCatalog>,
     asset_server: Res<AssetServer>,
     decks: Res<Assets<Deck>>,
+    cards: Res<Assets<Card>>,
 ) {
-    if !*once {
-        if let Some(market) = decks.get(&set.market_deck) {
-            if let Some(player) = decks.get(&set.player_deck) {
-                if let Some(explorer) = decks.get(&set.joker_deck) {
-                    for (_, name) in market
-                        .0
-                        .iter()
-                        .chain(player.0.iter())
-                        .chain(explorer.0.iter())
-                    {
-                        if set.cards.contains_key(name) {
-                            continue;
-                        }
-                        set.cards.insert(
-                            name.clone(),
-                            asset_server.load(format!("cards/{}.card.ron", name)),
-                        );
-                    }
-                    set.ready = true;
-                    *once = true;
-                }
+    if set.ready {
+        return;
+    }
+    let Some(entry) = set.id.and_then(|id| catalog.get(id)) else {
+        return;
+    };
+    let (Some(market), Some(player), Some(joker)) = (
+        decks.get(&set.market_deck),
+        decks.get(&set.player_deck),
+        decks.get(&set.joker_deck),
+    ) else {
+        return;
+    };
+    let names: Vec<String> = market
+        .0
+        .iter()
+        .chain(player.0.iter())
+        .chain(joker.0.iter())
+        .map(|(_, name)| name.clone())
+        .collect();
+    let mut loaded = true;
+    for name in names {
+        match set.cards.get(&name) {
+            Some(card) => loaded &= cards.contains(card),
+            None => {
+                let card = asset_server.load(entry.card("assets", &name));
+                set.cards.insert(name, card);
+                loaded = false;
             }
         }
     }
+    set.ready = loaded;
+}
+
+fn build_library(
+    set: Res<LoadedSet>,
+    mut library: ResMut<LoadedLibrary>,
+    decks: Res<Assets<Deck>>,
+    cards: Res<Assets<Card>>,
+) {
+    //rebuilt when the set gets ready, not each frame
+    if !set.is_changed() || !set.ready || library.0.as_ref().is_some_and(|l| Some(l.id) == set.id) {
+        return;
+    }
+    library.0 = CardLibrary::from_loaded(&set, &decks, &cards).map(Arc::new);
+    if library.0.is_none() {
+        error!(
+            "the cards of set {:?} are loaded but could not be read",
+            set.id
+        );
+    }
 }
 
 #[derive(Resource, Reflect, Default, Debug)]
@@ -117,10 +170,20 @@ pub struct LoadedModels {
 pub fn finished_loadding(
     mut state: ResMut<NextState<AppStates>>,
     models: Res<LoadedModels>,
-    set: Res<LoadedSet>,
+    replay: Res<ReplayFile>,
+    session: Option<Res<NetworkSession>>,
+    server: Option<Res<ServerGame>>,
 ) {
-    if models.ready && set.ready {
-        state.set(AppStates::Playing); //TODO: should switch to main menu instead
+    //the cards of a set are only loaded when a game asks for them
+    if models.ready {
+        if replay.0.is_some() {
+            state.set(AppStates::Replay);
+        } else if session.is_some() || server.is_some() {
+            //games hosted or joined from the command line start right away
+            state.set(AppStates::Playing);
+        } else {
+            state.set(AppStates::MainMenu);
+        }
     }
 }
 
@@ -132,6 +195,7 @@ impl Plugin for SetPlugin {
             .init_resource::<RawModel>()
             .register_type::<LoadedSet>()
             .init_resource::<LoadedSet>()
+            .init_resource::<LoadedLibrary>()
             .register_type::<LoadedModels>()
             .init_resource::<LoadedModels>()
             .init_asset::<Card>()
@@ -140,18 +204,18 @@ impl Plugin for SetPlugin {
             .init_asset::<Deck>()
             .register_asset_reflect::<Deck>()
             .init_asset_loader::<DeckLoadder>()
+            .init_resource::<SetCatalog>()
             .add_systems(
                 Startup,
-                (load_raw_models, load_decks).run_if(in_state(AppStates::Loading)),
+                (load_raw_models, discover_sets).run_if(in_state(AppStates::Loading)),
             )
             .add_systems(
                 First,
                 (
-                    load_cards,
-                    prepare_models.run_if(raw_models_ready),
-                    finished_loadding,
-                )
-                    .run_if(in_state(AppStates::Loading)),
+                    (prepare_models.run_if(raw_models_ready), finished_loadding)
+                        .run_if(in_state(AppStates::Loading)),
+                    (load_cards, build_library).chain(),
+                ),
             );
     }
 }
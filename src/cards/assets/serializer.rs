@@ -1,3 +1,4 @@
+use crate::cards::assets::manifest::DeckRole;
 use crate::cards::assets::{Card, Deck};
 use bevy::asset::io::Reader;
 use bevy::asset::{AssetLoader, AsyncReadExt, BoxedFuture, LoadContext};
@@ -17,6 +18,10 @@ pub enum AssetLoadderError {
     Io(#[from] std::io::Error),
     #[error("Could not parse RON: {0}")]
     RonSpannedError(#[from] ron::error::SpannedError),
+    #[error("Unknown card set: {0}")]
+    UnknownSet(String),
+    #[error("The set {0} has no {1:?} deck")]
+    MissingDeck(String, DeckRole),
 }
 
 impl AssetLoader for CardLoadder {
@@ -14,7 +14,7 @@ pub enum CardFaction {
     StarEmpire,
 }
 
-#[derive(Component, Debug, Default, Reflect, Clone, Eq, PartialEq)]
+#[derive(Component, Serialize, Deserialize, Debug, Default, Reflect, Clone, Eq, PartialEq)]
 #[reflect(Component)]
 pub struct CardFactions(pub HashSet<CardFaction>);
 
@@ -34,7 +34,9 @@ impl CardKinds {
     }
 }
 
-#[derive(Component, Debug, Default, Reflect, Copy, Clone, Eq, PartialEq)]
+#[derive(
+    Component, Serialize, Deserialize, Debug, Default, Reflect, Copy, Clone, Eq, PartialEq,
+)]
 #[reflect(Component)]
 pub struct BaseLife(pub i32);
 
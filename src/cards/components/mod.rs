@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 pub mod factions;
 pub mod kinds;
@@ -9,19 +10,42 @@ pub mod prelude {
         factions::{Blob, CardFactions, MachineCult, Neutral, StarEmpire, TradeFederation},
         kinds::{Base, CardKinds, Outpost, Ship},
         owners::{CardOwners, MarketOwned},
-        CardCost, CardIndex, CardVisibility,
+        CardCost, CardId, CardIndex, CardVisibility,
     };
 }
 
-#[derive(Component, Reflect, Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(
+    Component, Reflect, Serialize, Deserialize, Default, Debug, Copy, Clone, Eq, PartialEq, Hash,
+)]
 #[reflect(Component)]
 pub struct CardIndex(pub usize);
 
+//stable identifier of a card, given in spawn order by the setup event handler
+#[derive(
+    Component,
+    Reflect,
+    Serialize,
+    Deserialize,
+    Default,
+    Debug,
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+)]
+#[reflect(Component)]
+pub struct CardId(pub usize);
+
 #[derive(Component, Reflect, Default, Debug, Copy, Clone, Eq, PartialEq)]
 #[reflect(Component)]
 pub struct CardCost(pub i32);
 
-#[derive(Component, Reflect, Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(
+    Component, Reflect, Serialize, Deserialize, Default, Debug, Copy, Clone, Eq, PartialEq, Hash,
+)]
 #[reflect(Component)]
 pub enum CardVisibility {
     #[default]
@@ -38,6 +62,7 @@ pub struct CardComponentsPlugin;
 impl Plugin for CardComponentsPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<CardIndex>()
+            .register_type::<CardId>()
             .register_type::<CardCost>()
             .register_type::<CardVisibility>()
             .register_type::<Focused>()
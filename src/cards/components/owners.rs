@@ -1,4 +1,4 @@
-use crate::players::{Player, MAXIMUM_PLAYERS};
+use crate::players::PlayerOwned;
 use crate::utils::filter_enum::FilterEnumInserter;
 use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
@@ -25,17 +25,8 @@ impl FilterEnumInserter for CardOwners {
             Self::Market => {
                 entity.insert(MarketOwned);
             }
-            Self::Player(0) => {
-                entity.insert(Player::<0>);
-            }
-            Self::Player(1) => {
-                entity.insert(Player::<1>);
-            }
-            Self::Player(e) => {
-                panic!(
-                    "invalid player id: {e}, can only go up to: {}",
-                    MAXIMUM_PLAYERS - 1
-                );
+            Self::Player(_) => {
+                entity.insert(PlayerOwned);
             }
         }
     }
@@ -46,17 +37,8 @@ impl FilterEnumInserter for CardOwners {
             Self::Market => {
                 entity.remove::<MarketOwned>();
             }
-            Self::Player(0) => {
-                entity.remove::<Player<0>>();
-            }
-            Self::Player(1) => {
-                entity.remove::<Player<1>>();
-            }
-            Self::Player(e) => {
-                panic!(
-                    "invalid player id: {e}, can only go up to: {}",
-                    MAXIMUM_PLAYERS - 1
-                );
+            Self::Player(_) => {
+                entity.remove::<PlayerOwned>();
             }
         }
     }
@@ -67,17 +49,8 @@ impl FilterEnumInserter for CardOwners {
             Self::Market => {
                 entity.insert(MarketOwned);
             }
-            Self::Player(0) => {
-                entity.insert(Player::<0>);
-            }
-            Self::Player(1) => {
-                entity.insert(Player::<1>);
-            }
-            Self::Player(e) => {
-                panic!(
-                    "invalid player id: {e}, can only go up to: {}",
-                    MAXIMUM_PLAYERS - 1
-                );
+            Self::Player(_) => {
+                entity.insert(PlayerOwned);
             }
         }
     }
@@ -88,17 +61,8 @@ impl FilterEnumInserter for CardOwners {
             Self::Market => {
                 entity.remove::<MarketOwned>();
             }
-            Self::Player(0) => {
-                entity.remove::<Player<0>>();
-            }
-            Self::Player(1) => {
-                entity.remove::<Player<1>>();
-            }
-            Self::Player(e) => {
-                panic!(
-                    "invalid player id: {e}, can only go up to: {}",
-                    MAXIMUM_PLAYERS - 1
-                );
+            Self::Player(_) => {
+                entity.remove::<PlayerOwned>();
             }
         }
     }
@@ -33,6 +33,9 @@ impl Plugin for CardsPlugin {
             components::CardComponentsPlugin,
             transition::TransitionsPlugin,
         ))
-        .add_systems(PreUpdate, spawn_card.run_if(in_state(AppStates::Playing)));
+        .add_systems(
+            PreUpdate,
+            spawn_card.run_if(in_state(AppStates::Playing).or_else(in_state(AppStates::Replay))),
+        );
     }
 }
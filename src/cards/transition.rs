@@ -4,12 +4,13 @@ use crate::stacks::Stacks;
 use crate::utils::filter_enum::FilterEnumInserter;
 use bevy::prelude::*;
 use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
 
 #[derive(Resource, Reflect, Default, Debug, Copy, Clone)]
 #[reflect(Resource)]
 pub struct PlayBackSpeed(pub f32);
 
-#[derive(Reflect, Default, Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Reflect, Serialize, Deserialize, Default, Debug, Copy, Clone, Eq, PartialEq)]
 pub struct CardStateSnapshot {
     pub owner: CardOwners,
     pub stack: Stacks,
@@ -2,8 +2,65 @@ use crate::cards::transition::PositionGenerator;
 use crate::prelude::*;
 use bevy::utils::HashMap;
 
+//placement of a player's stacks on the table: the layout of player 0 is scaled around its origin,
+//and mirrored for the seats on the other side of the market. seats are counted from the player
+//sitting at the bottom of the screen, so the table turns instead of the camera (the cards stay
+//readable)
+struct Seat {
+    origin: Vec3,
+    scale: f32,
+    mirrored: bool,
+}
+
+impl Seat {
+    //2 players sit face to face, more players are split in two rows of smaller seats
+    //(first row in one direction, second row back in the other, so turns go around the table)
+    fn new(seat: u8, players: u8) -> Self {
+        if players <= 2 {
+            return Self {
+                origin: Vec3::ZERO,
+                scale: 1.,
+                mirrored: seat == 1,
+            };
+        }
+        let bottom = players.div_ceil(2);
+        let per_row = bottom as f32;
+        let scale = 1. / per_row;
+        let (column, mirrored) = if seat < bottom {
+            (seat as f32, false)
+        } else {
+            ((players - 1 - seat) as f32, true)
+        };
+        let x = 64. * ((column + 0.5) / per_row - 0.5);
+        //pushed away from the market so the smaller seats don't overlap it
+        let z = 10. * (1. - scale);
+        Self {
+            origin: Vec3::new(x, 0., if mirrored { z } else { -z }),
+            scale,
+            mirrored,
+        }
+    }
+
+    fn place(&self, mut generator: PositionGenerator) -> PositionGenerator {
+        if self.mirrored {
+            generator.root.z = -generator.root.z;
+            generator.index_offset.z = -generator.index_offset.z;
+        }
+        generator.root = self.origin + generator.root * self.scale;
+        generator.scale *= self.scale;
+        generator
+    }
+}
+
 impl Default for TransitionTransforms {
     fn default() -> Self {
+        Self::with_seats(2, 0)
+    }
+}
+
+impl TransitionTransforms {
+    //bottom: player whose hand is shown at the bottom of the screen
+    pub fn with_seats(players: u8, bottom: u8) -> Self {
         let mut positions = HashMap::new();
 
         positions.insert(
@@ -57,56 +114,57 @@ impl Default for TransitionTransforms {
             },
         );
 
-        for p in 0..=1 {
+        for p in 0..players {
+            let seat = Seat::new((p + players - bottom % players) % players, players);
             positions.insert(
                 (CardOwners::Player(p), Stacks::PlayerDeck),
-                PositionGenerator {
-                    root: Vec3::new(32., 0., if p == 0 { -15. } else { 15. }),
+                seat.place(PositionGenerator {
+                    root: Vec3::new(32., 0., -15.),
                     index_offset: Vec3::new(0., -CARD_DEPTH, 0.),
                     scale: Vec3::new(1., 1., 1.),
                     inverted_indexes: true,
                     keep_base_vertical: true,
-                },
+                }),
             );
             positions.insert(
                 (CardOwners::Player(p), Stacks::Hand),
-                PositionGenerator {
-                    root: Vec3::new(23.5, 0., if p == 0 { -15. } else { 15. }),
+                seat.place(PositionGenerator {
+                    root: Vec3::new(23.5, 0., -15.),
                     index_offset: Vec3::new(-CARD_WIDTH * 1.4, 0., 0.),
                     scale: Vec3::new(1., 1., 1.),
                     inverted_indexes: false,
                     keep_base_vertical: false,
-                },
+                }),
             );
             positions.insert(
                 (CardOwners::Player(p), Stacks::UsedCards),
-                PositionGenerator {
-                    root: Vec3::new(25., CARD_DEPTH, if p == 0 { -7.5 } else { 7.5 }),
+                seat.place(PositionGenerator {
+                    root: Vec3::new(25., CARD_DEPTH, -7.5),
                     index_offset: Vec3::new(-CARD_WIDTH * 1.4, 0., 0.),
                     scale: Vec3::new(0.5, 0.5, 0.5),
                     inverted_indexes: false,
                     keep_base_vertical: true,
-                },
+                }),
             );
             positions.insert(
                 (CardOwners::Player(p), Stacks::Bases),
-                PositionGenerator {
-                    root: Vec3::new(-24., CARD_DEPTH, if p == 0 { -17. } else { 17. }),
-                    index_offset: Vec3::new(0.95, CARD_DEPTH, if p == 0 { 0.5 } else { -0.5 }),
+                seat.place(PositionGenerator {
+                    root: Vec3::new(-24., CARD_DEPTH, -17.),
+                    index_offset: Vec3::new(0.95, CARD_DEPTH, 0.5),
                     scale: Vec3::new(1., 1., 1.),
                     inverted_indexes: false,
                     keep_base_vertical: false,
-                },
+                }),
             );
             positions.insert(
                 (CardOwners::Player(p), Stacks::DiscardPile),
-                PositionGenerator {
-                    root: Vec3::new(-32., 0., if p == 0 { -15. } else { 15. }),
+                seat.place(PositionGenerator {
+                    root: Vec3::new(-32., 0., -15.),
                     index_offset: Vec3::new(0., -CARD_DEPTH, 0.),
                     scale: Vec3::new(1., 1., 1.),
                     inverted_indexes: true,
                     keep_base_vertical: true,
-                },
+                }),
             );
         }
 
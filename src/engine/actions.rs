@@ -0,0 +1,334 @@
+use crate::cards::actions::uniques::Uniques;
+use crate::cards::actions::{Action, KindMask};
+use crate::cards::components::factions::CardFaction;
+use crate::engine::{CardKey, Decision, GameState, Listener, Pending, Task};
+use crate::game::routines::SelectionFilter;
+use crate::game::triggers::{Trigger, TriggerDuration};
+use crate::prelude::*;
+
+//engine side of the ActivateCard/CardAction routines and of Uniques::card_action
+
+impl GameState {
+    pub(super) fn queue_set(&mut self, player: u8, card: CardKey, set: ActionSet) {
+        self.tasks.push_back(Task::Set { player, card, set });
+    }
+
+    //mirror of the activate_card routine: parts of a set resolve before the tasks queued after it
+    pub(super) fn queue_nested(&mut self, player: u8, card: CardKey, sets: Vec<ActionSet>) {
+        for set in sets.into_iter().rev() {
+            self.tasks.push_front(Task::Set { player, card, set });
+        }
+    }
+
+    pub(super) fn execute(&mut self, task: Task) {
+        let (player, card, action) = match task {
+            Task::Set {
+                player,
+                card,
+                ref set,
+            } => {
+                match set {
+                    ActionSet::None => {}
+                    ActionSet::One(action) => self.tasks.push_back(Task::Action {
+                        player,
+                        card,
+                        action: *action,
+                    }),
+                    ActionSet::All(sets) => self.queue_nested(player, card, sets.clone()),
+                    ActionSet::If { .. } => {
+                        let sets = set.checked(&self.players[player as usize].tracker);
+                        self.queue_nested(player, card, sets);
+                    }
+                    ActionSet::Optional(_) | ActionSet::Choose { .. } => {
+                        self.pending = Some(Pending {
+                            decision: Decision::Choice {
+                                player,
+                                set: set.clone(),
+                            },
+                            task,
+                        });
+                    }
+                }
+                return;
+            }
+            Task::ForcedDiscard { player } => {
+                //mirror of the forced_discard routine
+                let hand = self.stack(CardOwners::Player(player), Stacks::Hand);
+                let state = &mut self.players[player as usize];
+                let count = state.tracker.cards_to_discard.min(hand.len());
+                if count == 0 {
+                    state.tracker.cards_to_discard = 0;
+                } else {
+                    self.pending = Some(Pending {
+                        decision: Decision::Selection {
+                            player,
+                            min: count,
+                            max: count,
+                            cards: hand,
+                        },
+                        task,
+                    });
+                }
+                return;
+            }
+            Task::Action {
+                player,
+                card,
+                action,
+            } => (player, card, action),
+        };
+        let owner = CardOwners::Player(player);
+        let state = &mut self.players[player as usize];
+        match action {
+            Action::Eco(qty) => state.economy += qty as i32,
+            Action::Atk(qty) => state.attack += qty as i32,
+            Action::Life(qty) => self.change_life(player, qty as i32),
+            Action::Draw(qty) => {
+                for _ in 0..qty {
+                    self.draw(player);
+                }
+            }
+            Action::DrawPer(counter) => {
+                for _ in 0..counter.value(&state.tracker) {
+                    self.draw(player);
+                }
+            }
+            Action::Discard | Action::ScrapHand => {
+                self.request_selection(task, 1, 1, vec![filter(vec![Stacks::Hand], owner)]);
+            }
+            Action::ScrapDiscard => {
+                self.request_selection(task, 1, 1, vec![filter(vec![Stacks::DiscardPile], owner)]);
+            }
+            Action::ScrapHandOrDiscard => {
+                self.request_selection(
+                    task,
+                    1,
+                    1,
+                    vec![filter(vec![Stacks::Hand, Stacks::DiscardPile], owner)],
+                );
+            }
+            Action::ScrapMarket => {
+                self.request_selection(
+                    task,
+                    1,
+                    1,
+                    vec![filter(vec![Stacks::MarketRow], CardOwners::Market)],
+                );
+            }
+            Action::ScrapSelf => self.scrap(card),
+            Action::DestroyBase => {
+                //outposts of a player must be destroyed before their other bases
+                let filters = self
+                    .targets(player)
+                    .into_iter()
+                    .map(|target| {
+                        let target = CardOwners::Player(target);
+                        let outposts = self
+                            .stack(target, Stacks::Bases)
+                            .into_iter()
+                            .any(|c| matches!(self.cards[c].kind, CardKinds::Outpost(_)));
+                        SelectionFilter {
+                            kinds: if outposts {
+                                KindMask::Outposts
+                            } else {
+                                KindMask::NonShip
+                            },
+                            ..filter(vec![Stacks::Bases], target)
+                        }
+                    })
+                    .collect();
+                self.request_selection(task, 1, 1, filters);
+            }
+            Action::EnemyDiscard => {
+                //with several possible targets, the player chooses one of them
+                let targets = self.targets(player);
+                if targets.len() > 1 {
+                    self.queue_nested(player, card, vec![ActionSet::discard_choice(targets)]);
+                } else if let Some(&target) = targets.first() {
+                    self.players[target as usize].tracker.cards_to_discard += 1;
+                }
+            }
+            Action::PlayerDiscard(target) => {
+                if let Some(state) = self.players.get_mut(target as usize) {
+                    state.tracker.cards_to_discard += 1;
+                }
+            }
+            Action::FreeBuy {
+                min_cost,
+                max_cost,
+                valid_kinds,
+            } => {
+                self.request_selection(
+                    task,
+                    1,
+                    1,
+                    vec![SelectionFilter {
+                        stacks: vec![Stacks::MarketRow],
+                        owners: vec![CardOwners::Market],
+                        kinds: valid_kinds,
+                        min_cost,
+                        max_cost,
+                    }],
+                );
+            }
+            Action::NextBuyOnDeck(kinds) => state.next_buy_on_deck = kinds,
+            Action::Unique(unique) => match unique {
+                Uniques::BrainWorld => {
+                    self.request_selection(
+                        task,
+                        0,
+                        2,
+                        vec![filter(vec![Stacks::Hand, Stacks::DiscardPile], owner)],
+                    );
+                }
+                Uniques::RecyclingStation => {
+                    self.request_selection(task, 0, 2, vec![filter(vec![Stacks::Hand], owner)]);
+                }
+                Uniques::StealthNeedle => {
+                    self.request_selection(
+                        task,
+                        0,
+                        1,
+                        vec![SelectionFilter {
+                            kinds: KindMask::Ships,
+                            ..filter(vec![Stacks::UsedCards], owner)
+                        }],
+                    );
+                }
+                Uniques::FleetHQ => self.triggers.push(Listener {
+                    card,
+                    owner: player,
+                    trigger: Trigger::ShipPlayed,
+                    duration: TriggerDuration::ThisTurn,
+                    action: Action::Atk(1),
+                }),
+                Uniques::Unimplemented => {}
+            },
+        }
+    }
+
+    //if no card matches the filters, the selection is resolved immediately with no card
+    //(same as the selection routine auto closing)
+    fn request_selection(
+        &mut self,
+        task: Task,
+        min: usize,
+        max: usize,
+        filters: Vec<SelectionFilter>,
+    ) {
+        let Task::Action {
+            player,
+            card,
+            action,
+        } = task
+        else {
+            return;
+        };
+        //the card asking is never one of the options (the Stealth Needle can't copy itself)
+        let cards: Vec<CardKey> = (0..self.cards.len())
+            .filter(|&c| c != card)
+            .filter(|&c| {
+                let state = &self.cards[c];
+                filters.iter().any(|f| {
+                    f.owners.contains(&state.owner)
+                        && f.stacks.contains(&state.stack)
+                        && state.kind.in_mask(f.kinds)
+                        && state.cost >= f.min_cost as i32
+                        && state.cost <= f.max_cost as i32
+                })
+            })
+            .collect();
+        if cards.is_empty() {
+            self.complete_selection(player, card, action, cards);
+        } else {
+            self.pending = Some(Pending {
+                decision: Decision::Selection {
+                    player,
+                    min: min.min(cards.len()),
+                    max,
+                    cards,
+                },
+                task,
+            });
+        }
+    }
+
+    pub(super) fn complete_selection(
+        &mut self,
+        player: u8,
+        source: CardKey,
+        action: Action,
+        selected: Vec<CardKey>,
+    ) {
+        for card in selected {
+            match action {
+                Action::Discard | Action::Unique(Uniques::RecyclingStation) => {
+                    self.discard(player, card);
+                }
+                Action::ScrapHand | Action::ScrapDiscard | Action::ScrapHandOrDiscard => {
+                    self.scrap(card);
+                }
+                Action::ScrapMarket => {
+                    let slot = self.cards[card].index as u8;
+                    self.scrap(card);
+                    self.reload_market(slot);
+                }
+                Action::DestroyBase => {
+                    if let CardOwners::Player(owner) = self.cards[card].owner {
+                        self.discard(owner, card);
+                    }
+                }
+                Action::FreeBuy { .. } => {
+                    let slot = self.cards[card].index as u8;
+                    self.acquire(player, card);
+                    self.reload_market(slot);
+                }
+                Action::Unique(Uniques::BrainWorld) => {
+                    self.scrap(card);
+                }
+                Action::Unique(Uniques::StealthNeedle) if card != source => {
+                    self.copy_ship(player, source, card);
+                }
+                _ => {}
+            }
+            if let Action::Unique(Uniques::BrainWorld | Uniques::RecyclingStation) = action {
+                self.draw(player);
+            }
+        }
+    }
+}
+
+impl GameState {
+    //mirror of uniques::copy_ship
+    fn copy_ship(&mut self, player: u8, needle: CardKey, copied: CardKey) {
+        let mut actions = self.cards[copied].actions.clone();
+        actions.reset();
+        let gained: Vec<CardFaction> = self.cards[copied]
+            .factions
+            .0
+            .difference(&self.cards[needle].factions.0)
+            .copied()
+            .collect();
+        let state = &mut self.cards[needle];
+        let original = (state.actions.clone(), state.factions.clone());
+        state.actions = actions;
+        state.factions.0.extend(gained.iter().copied());
+        state.copy = Some(original);
+        let tracker = &mut self.players[player as usize].tracker;
+        tracker.ship_gained_factions(&gained);
+        if let Some((set, _)) = self.cards[needle].actions.use_action(0, tracker) {
+            let set = set.clone();
+            self.queue_set(player, needle, set);
+        }
+    }
+}
+
+fn filter(stacks: Vec<Stacks>, owner: CardOwners) -> SelectionFilter {
+    SelectionFilter {
+        stacks,
+        owners: vec![owner],
+        kinds: KindMask::Any,
+        min_cost: 0,
+        max_cost: 255,
+    }
+}
@@ -0,0 +1,65 @@
+use crate::engine::CardKey;
+use crate::prelude::*;
+use crate::states::turn::TurnStates;
+use serde::{Deserialize, Serialize};
+
+//what the board needs to do to mirror a change of the rules engine
+//(a renderer can replay those as transitions, a headless client can ignore them)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Effect {
+    SpawnCard {
+        card: CardKey,
+        name: String,
+    },
+    MoveCard {
+        card: CardKey,
+        owner: CardOwners,
+        stack: Stacks,
+        index: usize,
+        visibility: CardVisibility,
+    },
+    BaseLife {
+        card: CardKey,
+        life: i32,
+    },
+    Life {
+        player: u8,
+        value: i32,
+    },
+    Attack {
+        player: u8,
+        value: i32,
+    },
+    Economy {
+        player: u8,
+        value: i32,
+    },
+    Turn(TurnStates),
+    Decision(Decision),
+}
+
+//input the engine is waiting for before accepting any new event
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub enum Decision {
+    //pick between min and max cards in the list
+    Selection {
+        player: u8,
+        min: usize,
+        max: usize,
+        cards: Vec<CardKey>,
+    },
+    //pick the indexes of the actions to apply from an action set (same layout as the choice ui)
+    Choice {
+        player: u8,
+        set: ActionSet,
+    },
+}
+
+impl Decision {
+    pub fn player(&self) -> u8 {
+        match self {
+            Decision::Selection { player, .. } => *player,
+            Decision::Choice { player, .. } => *player,
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use thiserror::Error;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum RuleError {
+    #[error("the game is already set up")]
+    AlreadySetup,
+    #[error("the game is not set up yet")]
+    NotSetup,
+    #[error("invalid setup: {0}")]
+    InvalidSetup(&'static str),
+    #[error("unknown card: {0}")]
+    UnknownCard(String),
+    #[error("the game is over")]
+    GameOver,
+    #[error("invalid player: {0}")]
+    InvalidPlayer(u8),
+    #[error("a decision must be answered before any other event")]
+    DecisionPending,
+    #[error("there is no decision to answer")]
+    NoDecisionPending,
+    #[error("no card at index {0}")]
+    NoCard(u32),
+    #[error("action {0} is not available")]
+    ActionUnavailable(u8),
+    #[error("not enough economy: {cost} needed, {available} available")]
+    NotEnoughEconomy { cost: i32, available: i32 },
+    #[error("no attack left")]
+    NoAttack,
+    #[error("invalid target player: {0}")]
+    InvalidTarget(u8),
+    #[error("outposts must be destroyed first")]
+    OutpostInTheWay,
+    #[error("invalid selection")]
+    InvalidSelection,
+    #[error("invalid choice")]
+    InvalidChoice,
+    #[error("event not supported by the rules engine")]
+    Unsupported,
+}
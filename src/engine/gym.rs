@@ -0,0 +1,212 @@
+use crate::engine::replay::ReplayInput;
+use crate::engine::view::{CardView, PlayerView};
+use crate::engine::{CardLibrary, Decision, GameState, RuleError};
+use crate::game::events::GameEvents;
+use crate::game::modes::GameMode;
+use crate::prelude::*;
+use crate::states::turn::TurnStates;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+//answers listed by legal_actions for one selection or choice
+pub const MAX_ANSWERS: usize = 256;
+
+//what the acting player has to do
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum ObservationMode {
+    #[default]
+    Main, //play, activate, buy, attack or pass
+    Selection,
+    Choice,
+    GameOver,
+}
+
+//fixed-size encoding of the board as seen by the acting player (see Environment::observation_size)
+//layout, with T the number of different cards of the set (sorted by name) and P the players, the
+//acting player first then the others in seat order:
+//- P * [life, attack, economy, hand, deck, discard pile] counters
+//- T counts for each zone: own hand, own play area (ships and bases), own discard pile, market row,
+//  then the play area and the discard pile of each opponent
+//- pending decision: [minimum, maximum, options] then T counts of the selectable cards
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Observation {
+    pub player: u8,
+    pub mode: ObservationMode,
+    pub features: Vec<f32>,
+}
+
+//reinforcement learning environment over the rules engine, one step per input of the acting
+//player whoever they are (self play), the rewards are given to the player who acted
+pub struct Environment {
+    pub players: u8,
+    pub mode: GameMode,
+    library: Arc<CardLibrary>,
+    names: Vec<String>, //card types, index of the features
+    state: GameState,
+}
+
+impl Environment {
+    pub fn new(library: impl Into<Arc<CardLibrary>>, players: u8, mode: GameMode) -> Self {
+        let library = library.into();
+        let mut names: Vec<String> = library.cards.keys().cloned().collect();
+        names.sort();
+        Self {
+            players,
+            mode,
+            state: GameState::new(library.clone()),
+            library,
+            names,
+        }
+    }
+
+    pub fn observation_size(&self) -> usize {
+        let (types, players) = (self.names.len(), self.players as usize);
+        6 * players + types * (4 + 2 * players.saturating_sub(1)) + 3 + types
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    //start a new game, the seed also picks the first player
+    pub fn reset(&mut self, seed: u64) -> Result<Observation, RuleError> {
+        self.state = GameState::new(self.library.clone());
+        self.state.apply(GameEvents::Setup {
+            seed,
+            set: self.library.id,
+            players: self.players,
+            starting_player: (seed % self.players.max(1) as u64) as u8,
+            mode: self.mode,
+        })?;
+        Ok(self.observe(self.acting_player().unwrap_or(0)))
+    }
+
+    //player expected to give the next input
+    pub fn acting_player(&self) -> Option<u8> {
+        self.state
+            .pending()
+            .map(|d| d.player())
+            .or(self.state.current_player())
+    }
+
+    pub fn done(&self) -> bool {
+        self.acting_player().is_none()
+    }
+
+    //inputs step accepts, conceding excepted. the answers to a large selection are cut after
+    //MAX_ANSWERS
+    pub fn legal_actions(&self) -> Vec<ReplayInput> {
+        if self.state.pending().is_some() {
+            return self.state.legal_answers(MAX_ANSWERS);
+        }
+        self.state
+            .legal_events()
+            .into_iter()
+            .filter(|e| !matches!(e, GameEvents::Concede(_)))
+            .map(ReplayInput::Event)
+            .collect()
+    }
+
+    //1 for the winning team and -1 for the others once the game is over, 0 before
+    pub fn rewards(&self) -> Vec<f32> {
+        (0..self.players)
+            .map(|player| match self.state.turn() {
+                TurnStates::GameOver { winner, .. }
+                    if self.mode.won(player, winner, self.players) =>
+                {
+                    1.
+                }
+                TurnStates::GameOver { .. } => -1.,
+                _ => 0.,
+            })
+            .collect()
+    }
+
+    //play the input of the acting player, the reward is theirs (see rewards for the others)
+    pub fn step(&mut self, action: ReplayInput) -> Result<(Observation, f32, bool), RuleError> {
+        let player = self.acting_player().ok_or(RuleError::GameOver)?;
+        self.state.input(action)?;
+        let reward = self.rewards()[player as usize];
+        let observation = self.observe(self.acting_player().unwrap_or(player));
+        Ok((observation, reward, self.done()))
+    }
+
+    //the board from the view of a player
+    pub fn observe(&self, player: u8) -> Observation {
+        let view = self.state.view(player);
+        let mut features = Vec::with_capacity(self.observation_size());
+        let seats: Vec<u8> = (0..self.players)
+            .map(|offset| (player + offset) % self.players)
+            .collect();
+        for &seat in &seats {
+            let owner = CardOwners::Player(seat);
+            let counters = view.players.get(seat as usize);
+            features.extend([
+                counters.map_or(0, |c| c.life) as f32,
+                counters.map_or(0, |c| c.attack) as f32,
+                counters.map_or(0, |c| c.economy) as f32,
+                view.count(owner, Stacks::Hand) as f32,
+                view.count(owner, Stacks::PlayerDeck) as f32,
+                view.count(owner, Stacks::DiscardPile) as f32,
+            ]);
+        }
+        let own = CardOwners::Player(player);
+        let mut zones = vec![
+            self.counts(&view, |c| c.owner == own && c.stack == Stacks::Hand),
+            self.counts(&view, |c| {
+                c.owner == own && matches!(c.stack, Stacks::UsedCards | Stacks::Bases)
+            }),
+            self.counts(&view, |c| c.owner == own && c.stack == Stacks::DiscardPile),
+            self.counts(&view, |c| {
+                c.owner == CardOwners::Market && c.stack == Stacks::MarketRow
+            }),
+        ];
+        for &seat in &seats[1..] {
+            let owner = CardOwners::Player(seat);
+            zones.push(self.counts(&view, |c| {
+                c.owner == owner && matches!(c.stack, Stacks::UsedCards | Stacks::Bases)
+            }));
+            zones.push(self.counts(&view, |c| {
+                c.owner == owner && c.stack == Stacks::DiscardPile
+            }));
+        }
+        features.extend(zones.into_iter().flatten());
+        let mode = match (&view.pending, self.state.turn()) {
+            (_, TurnStates::GameOver { .. }) => ObservationMode::GameOver,
+            (
+                Some(Decision::Selection {
+                    min, max, cards, ..
+                }),
+                _,
+            ) => {
+                features.extend([*min as f32, *max as f32, cards.len() as f32]);
+                features.extend(self.counts(&view, |c| cards.contains(&c.id)));
+                ObservationMode::Selection
+            }
+            (Some(Decision::Choice { set, .. }), _) => {
+                let (min, max) = set.choice_bounds();
+                features.extend([min as f32, max as f32, set.options().len() as f32]);
+                ObservationMode::Choice
+            }
+            _ => ObservationMode::Main,
+        };
+        //no decision, or no selectable card for a choice: the rest stays at 0
+        features.resize(self.observation_size(), 0.);
+        Observation {
+            player,
+            mode,
+            features,
+        }
+    }
+
+    //number of visible cards of each type matching the filter
+    fn counts(&self, view: &PlayerView, filter: impl Fn(&CardView) -> bool) -> Vec<f32> {
+        let mut counts = vec![0.; self.names.len()];
+        for card in view.cards.iter().filter(|c| filter(c)) {
+            if let Ok(index) = self.names.binary_search(&card.name) {
+                counts[index] += 1.;
+            }
+        }
+        counts
+    }
+}
@@ -0,0 +1,209 @@
+use crate::engine::{CardKey, GameState};
+use crate::game::history::BoardSnapshot;
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+//the part of the board two peers must agree on: where each card is, the life of the bases and
+//the counters of the players. its hash is compared after each input to detect desyncs
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+pub struct CanonicalBoard {
+    pub cards: Vec<CanonicalCard>, //by CardKey
+    pub players: Vec<CanonicalPlayer>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct CanonicalCard {
+    pub owner: CardOwners,
+    pub stack: Stacks,
+    pub index: usize,
+    pub life: Option<i32>, //bases and outposts
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct CanonicalPlayer {
+    pub life: i32,
+    pub attack: i32,
+    pub economy: i32,
+}
+
+//FNV-1a over fixed-width little-endian fields, the hash must not change between runs, machines
+//or versions of rust
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.bytes(&[value]);
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn i32(&mut self, value: i32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    //tag then player, the tags are written out so reordering the variants keeps the hash
+    fn owner(&mut self, owner: CardOwners) {
+        match owner {
+            CardOwners::Market => self.bytes(&[0, 0]),
+            CardOwners::Player(player) => self.bytes(&[1, player]),
+        }
+    }
+
+    fn stack(&mut self, stack: Stacks) {
+        self.u8(match stack {
+            Stacks::MarketDeck => 0,
+            Stacks::MarketRow => 1,
+            Stacks::JokerDeck => 2,
+            Stacks::Scrapyard => 3,
+            Stacks::PlayerDeck => 4,
+            Stacks::Hand => 5,
+            Stacks::UsedCards => 6,
+            Stacks::Bases => 7,
+            Stacks::DiscardPile => 8,
+            Stacks::Focused => 9,
+            Stacks::Selection => 10,
+            Stacks::Log => 11,
+        });
+    }
+}
+
+impl CanonicalBoard {
+    //the cards by CardKey (owner, stack, index, life), then the players (life, attack, economy),
+    //each list preceded by its length
+    pub fn hash(&self) -> u64 {
+        let mut hash = Fnv1a::new();
+        hash.u64(self.cards.len() as u64);
+        for card in &self.cards {
+            hash.owner(card.owner);
+            hash.stack(card.stack);
+            hash.u64(card.index as u64);
+            match card.life {
+                Some(life) => {
+                    hash.u8(1);
+                    hash.i32(life);
+                }
+                None => hash.u8(0),
+            }
+        }
+        hash.u64(self.players.len() as u64);
+        for player in &self.players {
+            hash.i32(player.life);
+            hash.i32(player.attack);
+            hash.i32(player.economy);
+        }
+        hash.0
+    }
+
+    pub fn diff(&self, other: &Self) -> BoardDiff {
+        let card = |board: &Self, id: CardKey| board.cards.get(id).cloned();
+        let player = |board: &Self, p: usize| board.players.get(p).cloned();
+        BoardDiff {
+            cards: (0..self.cards.len().max(other.cards.len()))
+                .filter(|&id| card(self, id) != card(other, id))
+                .map(|id| (id, card(self, id), card(other, id)))
+                .collect(),
+            players: (0..self.players.len().max(other.players.len()))
+                .filter(|&p| player(self, p) != player(other, p))
+                .map(|p| (p as u8, player(self, p), player(other, p)))
+                .collect(),
+        }
+    }
+}
+
+//what differs between our board and theirs
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+pub struct BoardDiff {
+    pub cards: Vec<(CardKey, Option<CanonicalCard>, Option<CanonicalCard>)>,
+    pub players: Vec<(u8, Option<CanonicalPlayer>, Option<CanonicalPlayer>)>,
+}
+
+impl BoardDiff {
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty() && self.players.is_empty()
+    }
+}
+
+impl Display for BoardDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (id, ours, theirs) in &self.cards {
+            writeln!(f, "card {id}: {ours:?} here, {theirs:?} there")?;
+        }
+        for (player, ours, theirs) in &self.players {
+            writeln!(f, "player {player}: {ours:?} here, {theirs:?} there")?;
+        }
+        Ok(())
+    }
+}
+
+impl GameState {
+    pub fn canonical(&self) -> CanonicalBoard {
+        CanonicalBoard {
+            cards: self
+                .cards
+                .iter()
+                .map(|card| CanonicalCard {
+                    owner: card.owner,
+                    stack: card.stack,
+                    index: card.index,
+                    life: match card.kind {
+                        CardKinds::Base(_) | CardKinds::Outpost(_) => Some(card.life.0),
+                        _ => None,
+                    },
+                })
+                .collect(),
+            players: self
+                .players
+                .iter()
+                .map(|p| CanonicalPlayer {
+                    life: p.life,
+                    attack: p.attack,
+                    economy: p.economy,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn hash(&self) -> u64 {
+        self.canonical().hash()
+    }
+}
+
+//the same board taken from the bevy app, which hashes it as the engine does
+impl From<&BoardSnapshot> for CanonicalBoard {
+    fn from(board: &BoardSnapshot) -> Self {
+        CanonicalBoard {
+            cards: board
+                .cards
+                .iter()
+                .map(|card| CanonicalCard {
+                    owner: card.state.owner,
+                    stack: card.state.stack,
+                    index: card.state.index.0,
+                    life: card.base_life.map(|life| life.0),
+                })
+                .collect(),
+            players: board
+                .players
+                .iter()
+                .map(|p| CanonicalPlayer {
+                    life: p.life,
+                    attack: p.attack,
+                    economy: p.economy,
+                })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,328 @@
+use crate::cards::actions::KindMask;
+use crate::engine::replay::{Replay, ReplayError, ReplayInput};
+use crate::engine::{CardKey, CardLibrary, GameState, Listener};
+use crate::game::events::GameEvents;
+use crate::game::history::TriggerSnapshot;
+use crate::game::rng::GameRng;
+use crate::players::PlayerTurnTracker;
+use crate::prelude::*;
+use crate::states::turn::TurnStates;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+//compact board taken at a turn boundary (after Setup and PassTurn), when nothing is pending:
+//no card is in use, no action was used and no copy is active, so where each card lies is enough
+//to rebuild the board. stacks are listed by index (from the top for decks)
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct Keyframe {
+    pub step: usize, //index of the replay input after which it was taken
+    pub turn: TurnStates,
+    pub rng: GameRng,
+    pub players: Vec<PlayerKeyframe>,
+    pub market_row: Vec<Option<CardKey>>, //by slot
+    pub market_deck: Vec<CardKey>,
+    pub scrapyard: Vec<CardKey>,
+    pub joker_deck: Vec<CardKey>,
+    pub triggers: Vec<TriggerSnapshot>,
+    //bases destroyed on another player's turn keep their life until their owner's turn ends
+    #[serde(default)]
+    pub destroyed: Vec<(CardKey, i32)>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct PlayerKeyframe {
+    pub life: i32,
+    pub attack: i32,
+    pub economy: i32,
+    pub next_buy_on_deck: KindMask,
+    pub tracker: PlayerTurnTracker,
+    pub hand: Vec<Option<CardKey>>, //by slot
+    pub deck: Vec<CardKey>,
+    pub discard: Vec<CardKey>,
+    pub bases: Vec<(CardKey, i32)>, //with their remaining life
+}
+
+//keyframes are taken after these events
+pub fn is_turn_boundary(event: &GameEvents) -> bool {
+    matches!(event, GameEvents::Setup { .. } | GameEvents::PassTurn)
+}
+
+//same visibility as the one given by the moves of the engine
+fn visibility(stack: Stacks) -> CardVisibility {
+    match stack {
+        Stacks::MarketDeck | Stacks::PlayerDeck | Stacks::Hand => CardVisibility::Hidden,
+        _ => CardVisibility::Visible,
+    }
+}
+
+impl GameState {
+    fn slots(&self, owner: CardOwners, stack: Stacks) -> Vec<Option<CardKey>> {
+        let cards = self.stack(owner, stack);
+        let len = cards
+            .iter()
+            .map(|&c| self.cards[c].index + 1)
+            .max()
+            .unwrap_or(0);
+        let mut slots = vec![None; len];
+        for card in cards {
+            slots[self.cards[card].index] = Some(card);
+        }
+        slots
+    }
+
+    //None if the board is not at a turn boundary (decision pending, cards in use, game over...)
+    pub fn keyframe(&self, step: usize) -> Option<Keyframe> {
+        if !matches!(self.turn, TurnStates::PlayerTurn(_))
+            || self.pending.is_some()
+            || !self.tasks.is_empty()
+            || self.cards.iter().any(|c| {
+                c.copy.is_some()
+                    || matches!(
+                        c.stack,
+                        Stacks::UsedCards | Stacks::Focused | Stacks::Selection
+                    )
+            })
+        {
+            return None;
+        }
+        let market = CardOwners::Market;
+        Some(Keyframe {
+            step,
+            turn: self.turn,
+            rng: self.rng,
+            players: self
+                .players
+                .iter()
+                .enumerate()
+                .map(|(player, state)| {
+                    let owner = CardOwners::Player(player as u8);
+                    PlayerKeyframe {
+                        life: state.life,
+                        attack: state.attack,
+                        economy: state.economy,
+                        next_buy_on_deck: state.next_buy_on_deck,
+                        tracker: state.tracker.clone(),
+                        hand: self.slots(owner, Stacks::Hand),
+                        deck: self.stack(owner, Stacks::PlayerDeck),
+                        discard: self.stack(owner, Stacks::DiscardPile),
+                        bases: self
+                            .stack(owner, Stacks::Bases)
+                            .into_iter()
+                            .map(|c| (c, self.cards[c].life.0))
+                            .collect(),
+                    }
+                })
+                .collect(),
+            market_row: self.slots(market, Stacks::MarketRow),
+            market_deck: self.stack(market, Stacks::MarketDeck),
+            scrapyard: self.stack(market, Stacks::Scrapyard),
+            joker_deck: self.stack(market, Stacks::JokerDeck),
+            triggers: self
+                .triggers
+                .iter()
+                .map(|l| TriggerSnapshot {
+                    card: CardId(l.card),
+                    owner: l.owner,
+                    trigger: l.trigger,
+                    duration: l.duration,
+                    action: l.action,
+                })
+                .collect(),
+            destroyed: self
+                .cards
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| c.stack != Stacks::Bases)
+                .filter_map(|(key, c)| match c.kind {
+                    CardKinds::Base(life) | CardKinds::Outpost(life) if c.life.0 != life => {
+                        Some((key, c.life.0))
+                    }
+                    _ => None,
+                })
+                .collect(),
+        })
+    }
+
+    //put the board of a keyframe on a state set up by the same setup event
+    pub fn restore_keyframe(&mut self, keyframe: &Keyframe) -> Result<(), ReplayError> {
+        let invalid = ReplayError::InvalidKeyframe(keyframe.step);
+        if keyframe.players.len() != self.players.len() {
+            return Err(invalid);
+        }
+        let market = CardOwners::Market;
+        let mut places: Vec<(CardKey, CardOwners, Stacks, usize)> = Vec::new();
+        let mut listed = |owner, stack, cards: &[CardKey]| {
+            for (index, &card) in cards.iter().enumerate() {
+                places.push((card, owner, stack, index));
+            }
+        };
+        listed(market, Stacks::MarketDeck, &keyframe.market_deck);
+        listed(market, Stacks::Scrapyard, &keyframe.scrapyard);
+        listed(market, Stacks::JokerDeck, &keyframe.joker_deck);
+        for (player, state) in keyframe.players.iter().enumerate() {
+            let owner = CardOwners::Player(player as u8);
+            listed(owner, Stacks::PlayerDeck, &state.deck);
+            listed(owner, Stacks::DiscardPile, &state.discard);
+            let bases: Vec<CardKey> = state.bases.iter().map(|&(c, _)| c).collect();
+            listed(owner, Stacks::Bases, &bases);
+        }
+        let slotted = keyframe
+            .market_row
+            .iter()
+            .enumerate()
+            .map(|(index, card)| (card, market, Stacks::MarketRow, index))
+            .chain(
+                keyframe
+                    .players
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(player, state)| {
+                        state.hand.iter().enumerate().map(move |(index, card)| {
+                            (card, CardOwners::Player(player as u8), Stacks::Hand, index)
+                        })
+                    }),
+            );
+        for (card, owner, stack, index) in slotted {
+            if let Some(&card) = card.as_ref() {
+                places.push((card, owner, stack, index));
+            }
+        }
+        //every card must be placed exactly once
+        let mut placed = vec![false; self.cards.len()];
+        for &(card, ..) in &places {
+            if card >= placed.len() || placed[card] {
+                return Err(invalid);
+            }
+            placed[card] = true;
+        }
+        if placed.contains(&false) {
+            return Err(invalid);
+        }
+        for (card, owner, stack, index) in places {
+            let state = &mut self.cards[card];
+            state.owner = owner;
+            state.stack = stack;
+            state.index = index;
+            state.visibility = visibility(stack);
+            if let Some((actions, factions)) = state.copy.take() {
+                state.actions = actions;
+                state.factions = factions;
+            }
+            state.actions.reset();
+            if let CardKinds::Base(life) | CardKinds::Outpost(life) = state.kind {
+                state.life.0 = life;
+            }
+        }
+        for &(card, life) in &keyframe.destroyed {
+            self.cards
+                .get_mut(card)
+                .ok_or(ReplayError::InvalidKeyframe(keyframe.step))?
+                .life
+                .0 = life;
+        }
+        for (player, state) in keyframe.players.iter().enumerate() {
+            for &(card, life) in &state.bases {
+                self.cards[card].life.0 = life;
+            }
+            self.players[player] = crate::engine::PlayerState {
+                life: state.life,
+                attack: state.attack,
+                economy: state.economy,
+                tracker: state.tracker.clone(),
+                next_buy_on_deck: state.next_buy_on_deck,
+            };
+        }
+        self.turn = keyframe.turn;
+        self.rng = keyframe.rng;
+        self.tasks.clear();
+        self.pending = None;
+        self.triggers = keyframe
+            .triggers
+            .iter()
+            .map(|t| Listener {
+                card: t.card.0,
+                owner: t.owner,
+                trigger: t.trigger,
+                duration: t.duration,
+                action: t.action,
+            })
+            .collect();
+        Ok(())
+    }
+}
+
+impl Replay {
+    //play the whole replay again and keep a keyframe at each turn boundary
+    pub fn build_keyframes(&self, library: Arc<CardLibrary>) -> Result<Vec<Keyframe>, ReplayError> {
+        let mut keyframes = Vec::new();
+        let mut state = GameState::new(library);
+        self.play(&mut state, |step, state| {
+            if let ReplayInput::Event(event) = &self.inputs[step] {
+                if is_turn_boundary(event) {
+                    keyframes.extend(state.keyframe(step));
+                }
+            }
+        })?;
+        Ok(keyframes)
+    }
+
+    //compare the recorded hashes and keyframes with a new simulation, to detect changes of the
+    //rules. the error gives the first input after which the boards differ
+    pub fn verify(&self, library: Arc<CardLibrary>) -> Result<(), ReplayError> {
+        let mut keyframes = Vec::new();
+        let mut diverged = None;
+        let mut state = GameState::new(library);
+        self.play(&mut state, |step, state| {
+            if let ReplayInput::Event(event) = &self.inputs[step] {
+                if is_turn_boundary(event) {
+                    keyframes.extend(state.keyframe(step));
+                }
+            }
+            if diverged.is_none() && self.hashes.get(step).is_some_and(|&h| h != state.hash()) {
+                diverged = Some(step);
+            }
+        })?;
+        for (recorded, simulated) in self.keyframes.iter().zip(keyframes.iter()) {
+            if recorded != simulated {
+                let step = recorded.step.min(simulated.step);
+                diverged = Some(diverged.map_or(step, |d: usize| d.min(step)));
+                break;
+            }
+        }
+        if diverged.is_none() && self.keyframes.len() != keyframes.len() {
+            let step = self.keyframes.len().min(keyframes.len());
+            diverged = Some(
+                self.keyframes
+                    .get(step)
+                    .or(keyframes.get(step))
+                    .map_or(0, |k| k.step),
+            );
+        }
+        match diverged {
+            Some(step) => Err(ReplayError::Diverged(step)),
+            None => Ok(()),
+        }
+    }
+
+    //state after the given input, played from the closest keyframe instead of the first input
+    pub fn seek(&self, library: Arc<CardLibrary>, step: usize) -> Result<GameState, ReplayError> {
+        let mut state = GameState::new(library);
+        let Some(ReplayInput::Event(setup)) = self.inputs.first() else {
+            return Err(ReplayError::MissingSetup);
+        };
+        state
+            .apply(*setup)
+            .map_err(|error| ReplayError::Rule { step: 0, error })?;
+        let mut from = 1;
+        if let Some(keyframe) = self.keyframes.iter().rev().find(|k| k.step <= step) {
+            state.restore_keyframe(keyframe)?;
+            from = keyframe.step + 1;
+        }
+        for (step, input) in self.inputs.iter().enumerate().take(step + 1).skip(from) {
+            state
+                .input(input.clone())
+                .map_err(|error| ReplayError::Rule { step, error })?;
+        }
+        Ok(state)
+    }
+}
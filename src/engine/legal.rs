@@ -0,0 +1,145 @@
+use crate::engine::replay::ReplayInput;
+use crate::engine::{Decision, GameState};
+use crate::game::events::{BuyFrom, GameEvents};
+use crate::prelude::*;
+use crate::states::turn::TurnStates;
+
+impl GameState {
+    //every event the player expected to act (the one answering the pending decision, otherwise
+    //the current player) may send, in no particular order. Setup (free parameters) and Debug are
+    //not listed. while a decision is pending only their concession is
+    pub fn legal_events(&self) -> Vec<GameEvents> {
+        match self.pending().map(|d| d.player()).or(self.current_player()) {
+            Some(player) => self.legal_events_for(player),
+            None => Vec::new(),
+        }
+    }
+
+    //every event apply accepts from this player: their own concession at any time while they are
+    //in the game, and the events of their turn once nothing is pending
+    pub fn legal_events_for(&self, player: u8) -> Vec<GameEvents> {
+        let mut out = Vec::new();
+        match self.turn {
+            TurnStates::Setup | TurnStates::GameOver { .. } => return out,
+            _ => {}
+        }
+        if self.alive().get(player as usize) == Some(&true) {
+            out.push(GameEvents::Concede(player));
+        }
+        if self.current_player() != Some(player) || self.pending.is_some() {
+            return out;
+        }
+        let owner = CardOwners::Player(player);
+        let state = &self.players[player as usize];
+        out.push(GameEvents::PassTurn);
+        out.extend(
+            self.stack(owner, Stacks::Hand)
+                .into_iter()
+                .map(|c| GameEvents::PlayCard(self.cards[c].index as u8)),
+        );
+        for (stack, base) in [(Stacks::UsedCards, false), (Stacks::Bases, true)] {
+            for card in self.stack(owner, stack) {
+                let card = &self.cards[card];
+                out.extend(
+                    (0..card.actions.len())
+                        .filter(|&action| card.actions.is_action_available(action, &state.tracker))
+                        .map(|action| GameEvents::ActivateCard {
+                            base,
+                            index: card.index as u32,
+                            action,
+                        }),
+                );
+            }
+        }
+        let market = self
+            .stack(CardOwners::Market, Stacks::MarketRow)
+            .into_iter()
+            .map(|c| (c, BuyFrom::Market(self.cards[c].index as u8)));
+        let joker = self
+            .card_at(CardOwners::Market, Stacks::JokerDeck, 0)
+            .map(|c| (c, BuyFrom::Joker));
+        out.extend(
+            market
+                .chain(joker)
+                .filter(|(c, _)| self.cards[*c].cost <= state.economy)
+                .map(|(_, from)| GameEvents::BuyCard(from)),
+        );
+        if state.attack > 0 {
+            for target in self.targets(player) {
+                let bases = self.stack(CardOwners::Player(target), Stacks::Bases);
+                let outposts = bases
+                    .iter()
+                    .any(|&c| matches!(self.cards[c].kind, CardKinds::Outpost(_)));
+                //outposts have to be destroyed before the bases and the player
+                let targets = bases
+                    .into_iter()
+                    .filter(|&c| match self.cards[c].kind {
+                        CardKinds::Ship => false,
+                        CardKinds::Base(_) => !outposts,
+                        CardKinds::Outpost(_) => true,
+                    })
+                    .map(|c| Some(self.cards[c].index as u32))
+                    .chain((!outposts).then_some(None));
+                for base_index in targets {
+                    out.extend([true, false].map(|as_much_as_possible| GameEvents::Attack {
+                        player: target,
+                        as_much_as_possible,
+                        base_index,
+                    }));
+                }
+            }
+        }
+        out
+    }
+
+    //whether apply accepts the event from this player: the check the event dispatcher of the app
+    //and the authoritative server run before playing an event
+    pub fn is_legal(&self, player: u8, event: GameEvents) -> bool {
+        self.input_player(&ReplayInput::Event(event)) == Some(player)
+            && self.legal_events_for(player).contains(&event)
+    }
+
+    //answers to the pending decision that select or choose accept, the smallest ones first and
+    //at most limit of them (a large selection has too many subsets to list them all)
+    pub fn legal_answers(&self, limit: usize) -> Vec<ReplayInput> {
+        match self.pending() {
+            Some(Decision::Selection {
+                min, max, cards, ..
+            }) => subsets(cards.len(), *min, *max, limit)
+                .into_iter()
+                .map(|s| ReplayInput::Select(s.into_iter().map(|i| cards[i]).collect()))
+                .collect(),
+            Some(Decision::Choice { set, .. }) => {
+                let (min, max) = set.choice_bounds();
+                subsets(set.options().len(), min as usize, max as usize, limit)
+                    .into_iter()
+                    .map(|s| ReplayInput::Choose(s.into_iter().map(|i| i as u8).collect()))
+                    .collect()
+            }
+            None => Vec::new(),
+        }
+    }
+}
+
+//sorted subsets of 0..count with a size between min and max, smallest first
+fn subsets(count: usize, min: usize, max: usize, limit: usize) -> Vec<Vec<usize>> {
+    let mut out = Vec::new();
+    for size in min..=max.min(count) {
+        let mut indices: Vec<usize> = (0..size).collect();
+        loop {
+            if out.len() >= limit {
+                return out;
+            }
+            out.push(indices.clone());
+            //next combination in lexicographic order
+            let Some(i) = (0..size).rev().find(|&i| indices[i] < count - size + i) else {
+                break;
+            };
+            indices[i] += 1;
+            for j in i + 1..size {
+                indices[j] = indices[j - 1] + 1;
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,96 @@
+use crate::cards::assets::manifest::{DeckRole, SetCatalog, SetEntry, SetRules};
+use crate::cards::assets::serializer::AssetLoadderError;
+use crate::cards::assets::{Card, Deck, LoadedSet};
+use bevy::asset::Assets;
+use bevy::utils::HashMap;
+use std::fs;
+use std::path::Path;
+
+//file system equivalent of the LoadedSet resource, for use without an asset server
+#[derive(Debug, Default, Clone)]
+pub struct CardLibrary {
+    pub id: u8, //set of the setup events this library can play
+    pub rules: SetRules,
+    pub market_deck: Deck,
+    pub player_deck: Deck,
+    pub joker_deck: Deck,
+    pub cards: HashMap<String, Card>,
+}
+
+impl CardLibrary {
+    //set given by its id, folder or name (see SetCatalog::find)
+    pub fn load<P: AsRef<Path>>(assets: P, set: &str) -> Result<Self, AssetLoadderError> {
+        let assets = assets.as_ref();
+        let catalog = SetCatalog::discover(assets);
+        let entry = catalog
+            .find(set)
+            .ok_or_else(|| AssetLoadderError::UnknownSet(set.to_string()))?;
+        Self::from_set(assets, entry)
+    }
+
+    pub fn from_set<P: AsRef<Path>>(assets: P, set: &SetEntry) -> Result<Self, AssetLoadderError> {
+        let assets = assets.as_ref();
+        let deck = |role: DeckRole| -> Result<Deck, AssetLoadderError> {
+            let bytes = fs::read(assets.join(set.deck(role)?))?;
+            Ok(Deck(ron::de::from_bytes::<Vec<(u32, String)>>(&bytes)?))
+        };
+        let mut library = Self {
+            id: set.manifest.id,
+            rules: set.manifest.rules,
+            market_deck: deck(DeckRole::Market)?,
+            player_deck: deck(DeckRole::Player)?,
+            joker_deck: deck(DeckRole::Joker)?,
+            cards: HashMap::new(),
+        };
+        let names: Vec<String> = library
+            .market_deck
+            .0
+            .iter()
+            .chain(library.player_deck.0.iter())
+            .chain(library.joker_deck.0.iter())
+            .map(|(_, name)| name.clone())
+            .collect();
+        for name in names {
+            if library.cards.contains_key(&name) {
+                continue;
+            }
+            let bytes = fs::read(assets.join(set.card(assets, &name)))?;
+            library
+                .cards
+                .insert(name, ron::de::from_bytes::<Card>(&bytes)?);
+        }
+        Ok(library)
+    }
+
+    //the same library from the assets of the LoadedSet, None until its decks and cards are ready
+    pub fn from_loaded(
+        set: &LoadedSet,
+        decks: &Assets<Deck>,
+        cards: &Assets<Card>,
+    ) -> Option<Self> {
+        if !set.ready {
+            return None;
+        }
+        let mut library = Self {
+            id: set.id?,
+            rules: set.rules,
+            market_deck: decks.get(&set.market_deck)?.clone(),
+            player_deck: decks.get(&set.player_deck)?.clone(),
+            joker_deck: decks.get(&set.joker_deck)?.clone(),
+            cards: HashMap::new(),
+        };
+        let names: Vec<String> = library
+            .market_deck
+            .0
+            .iter()
+            .chain(library.player_deck.0.iter())
+            .chain(library.joker_deck.0.iter())
+            .map(|(_, name)| name.clone())
+            .collect();
+        for name in names {
+            let card = cards.get(set.cards.get(&name)?)?.clone();
+            library.cards.insert(name, card);
+        }
+        Some(library)
+    }
+}
@@ -0,0 +1,968 @@
+mod actions;
+mod effects;
+mod error;
+pub mod gym;
+pub mod hash;
+pub mod keyframes;
+mod legal;
+mod library;
+pub mod replay;
+pub mod view;
+
+pub use effects::{Decision, Effect};
+pub use error::RuleError;
+pub use library::CardLibrary;
+
+use crate::cards::actions::{Action, CardActions, KindMask};
+use crate::cards::components::kinds::BaseLife;
+use crate::cards::transition::CardStateSnapshot;
+use crate::game::events::{BuyFrom, GameEvents};
+use crate::game::modes::GameMode;
+use crate::game::rng::GameRng;
+use crate::game::triggers::{leaves_play, Trigger, TriggerDuration};
+use crate::players::{PlayerTurnTracker, MAXIMUM_PLAYERS};
+use crate::prelude::*;
+use crate::states::turn::{GameOverReason, TurnStates};
+use bevy::utils::HashSet;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+//headless version of the rules: same events, same card data, but no entities, no routines
+//waiting on animations and no window. the bevy app and the engine are meant to agree on the
+//outcome of every event, so the engine can be used to test rules, run bots or simulate games
+
+//cards are identified by their spawn order (same order as the setup event handler), the value
+//held by the CardId component of the matching entity
+pub type CardKey = usize;
+
+#[derive(Debug, Clone)]
+pub struct CardState {
+    pub name: String,
+    pub kind: CardKinds,
+    pub factions: CardFactions,
+    pub cost: i32,
+    pub actions: CardActions,
+    pub owner: CardOwners,
+    pub stack: Stacks,
+    pub index: usize,
+    pub visibility: CardVisibility,
+    pub life: BaseLife,
+    pub copy: Option<(CardActions, CardFactions)>, //original actions and factions of a Stealth Needle copying a ship
+}
+
+#[derive(Debug, Clone)]
+pub struct PlayerState {
+    pub life: i32,
+    pub attack: i32,
+    pub economy: i32,
+    pub tracker: PlayerTurnTracker,
+    pub next_buy_on_deck: KindMask,
+}
+
+impl Default for PlayerState {
+    fn default() -> Self {
+        Self {
+            life: 50,
+            attack: 0,
+            economy: 0,
+            tracker: PlayerTurnTracker::default(),
+            next_buy_on_deck: KindMask::None,
+        }
+    }
+}
+
+//equivalent of the Routines queue, minus the routines that only exist to wait for animations
+#[derive(Debug, Clone)]
+enum Task {
+    Action {
+        player: u8,
+        card: CardKey,
+        action: Action,
+    },
+    Set {
+        player: u8,
+        card: CardKey,
+        set: ActionSet,
+    },
+    ForcedDiscard {
+        player: u8,
+    },
+}
+
+//engine version of TriggerListener
+#[derive(Debug, Clone)]
+struct Listener {
+    card: CardKey,
+    owner: u8,
+    trigger: Trigger,
+    duration: TriggerDuration,
+    action: Action,
+}
+
+#[derive(Debug, Clone)]
+struct Pending {
+    decision: Decision,
+    task: Task,
+}
+
+#[derive(Debug, Clone)]
+pub struct GameState {
+    library: Arc<CardLibrary>,
+    rng: GameRng,
+    mode: GameMode,
+    turn: TurnStates,
+    cards: Vec<CardState>,
+    players: Vec<PlayerState>,
+    tasks: VecDeque<Task>,
+    pending: Option<Pending>,
+    triggers: Vec<Listener>,
+}
+
+impl GameState {
+    pub fn new(library: impl Into<Arc<CardLibrary>>) -> Self {
+        Self {
+            library: library.into(),
+            rng: GameRng::default(),
+            mode: GameMode::default(),
+            turn: TurnStates::Setup,
+            cards: Vec::new(),
+            players: Vec::new(),
+            tasks: VecDeque::new(),
+            pending: None,
+            triggers: Vec::new(),
+        }
+    }
+
+    pub fn turn(&self) -> TurnStates {
+        self.turn
+    }
+
+    pub fn current_player(&self) -> Option<u8> {
+        match self.turn {
+            TurnStates::PlayerTurn(p) => Some(p),
+            _ => None,
+        }
+    }
+
+    pub fn cards(&self) -> &[CardState] {
+        &self.cards
+    }
+
+    pub fn card(&self, card: CardKey) -> Option<&CardState> {
+        self.cards.get(card)
+    }
+
+    pub fn players(&self) -> &[PlayerState] {
+        &self.players
+    }
+
+    pub fn player(&self, player: u8) -> Option<&PlayerState> {
+        self.players.get(player as usize)
+    }
+
+    pub fn pending(&self) -> Option<&Decision> {
+        self.pending.as_ref().map(|p| &p.decision)
+    }
+
+    //cards of a stack, sorted by index
+    pub fn stack(&self, owner: CardOwners, stack: Stacks) -> Vec<CardKey> {
+        let mut out: Vec<CardKey> = (0..self.cards.len())
+            .filter(|&c| self.cards[c].owner == owner && self.cards[c].stack == stack)
+            .collect();
+        out.sort_by_key(|&c| self.cards[c].index);
+        out
+    }
+
+    pub fn card_at(&self, owner: CardOwners, stack: Stacks, index: usize) -> Option<CardKey> {
+        (0..self.cards.len()).find(|&c| {
+            let card = &self.cards[c];
+            card.owner == owner && card.stack == stack && card.index == index
+        })
+    }
+
+    pub fn mode(&self) -> GameMode {
+        self.mode
+    }
+
+    //indexed by player id, a player is out of the game once their life reaches 0
+    pub fn alive(&self) -> Vec<bool> {
+        self.players.iter().map(|p| p.life > 0).collect()
+    }
+
+    //players that can be targeted by this player, depending on the mode
+    pub fn targets(&self, player: u8) -> Vec<u8> {
+        self.mode
+            .targets(player, self.players.len() as u8, &self.alive())
+    }
+
+    //validate and apply an event, returning what changed on the board
+    //on error the state is left untouched
+    pub fn apply(&mut self, event: GameEvents) -> Result<Vec<Effect>, RuleError> {
+        if let TurnStates::GameOver { .. } = self.turn {
+            return Err(RuleError::GameOver);
+        }
+        //a concession interrupts everything, even a pending decision
+        if let GameEvents::Concede(player) = event {
+            let before = self.capture();
+            self.concede(player)?;
+            self.run();
+            return Ok(self.effects(before));
+        }
+        if self.pending.is_some() {
+            return Err(RuleError::DecisionPending);
+        }
+        let before = self.capture();
+        match event {
+            GameEvents::Setup {
+                seed,
+                set,
+                players,
+                starting_player,
+                mode,
+            } => self.setup(seed, set, players, starting_player, mode)?,
+            GameEvents::PlayCard(slot) => self.play_card(self.acting_player()?, slot)?,
+            GameEvents::ActivateCard {
+                base,
+                index,
+                action,
+            } => self.activate_card(self.acting_player()?, base, index, action)?,
+            GameEvents::BuyCard(from) => self.buy_card(self.acting_player()?, from)?,
+            GameEvents::Attack {
+                player,
+                as_much_as_possible,
+                base_index,
+            } => self.attack(
+                self.acting_player()?,
+                player,
+                as_much_as_possible,
+                base_index,
+            )?,
+            GameEvents::PassTurn => self.pass_turn(self.acting_player()?),
+            GameEvents::Concede(_) => unreachable!(),
+            GameEvents::Debug => return Err(RuleError::Unsupported),
+        }
+        self.run();
+        Ok(self.effects(before))
+    }
+
+    //answer a pending Decision::Selection
+    pub fn select(&mut self, cards: Vec<CardKey>) -> Result<Vec<Effect>, RuleError> {
+        let Some(Pending {
+            decision:
+                Decision::Selection {
+                    min,
+                    max,
+                    cards: valid,
+                    ..
+                },
+            ..
+        }) = &self.pending
+        else {
+            return Err(RuleError::NoDecisionPending);
+        };
+        let unique: HashSet<CardKey> = cards.iter().copied().collect();
+        if cards.len() < *min
+            || cards.len() > *max
+            || unique.len() != cards.len()
+            || cards.iter().any(|c| !valid.contains(c))
+        {
+            return Err(RuleError::InvalidSelection);
+        }
+        //the app resolves the selected cards by CardKey, whatever order they were picked in
+        let mut cards = cards;
+        cards.sort();
+        let before = self.capture();
+        match self.pending.take().map(|p| p.task) {
+            Some(Task::Action {
+                player,
+                card,
+                action,
+            }) => self.complete_selection(player, card, action, cards),
+            Some(Task::ForcedDiscard { player }) => {
+                for card in cards {
+                    self.discard(player, card);
+                }
+                self.players[player as usize].tracker.cards_to_discard = 0;
+            }
+            _ => {}
+        }
+        self.run();
+        Ok(self.effects(before))
+    }
+
+    //answer a pending Decision::Choice
+    pub fn choose(&mut self, mut choices: Vec<u8>) -> Result<Vec<Effect>, RuleError> {
+        let Some(Pending {
+            task: Task::Set { player, card, set },
+            ..
+        }) = self.pending.clone()
+        else {
+            return Err(RuleError::NoDecisionPending);
+        };
+        choices.sort();
+        let chosen = set
+            .resolve_choices(&choices)
+            .ok_or(RuleError::InvalidChoice)?;
+        let before = self.capture();
+        self.pending = None;
+        self.queue_nested(player, card, chosen);
+        self.run();
+        Ok(self.effects(before))
+    }
+
+    fn acting_player(&self) -> Result<u8, RuleError> {
+        self.current_player().ok_or(RuleError::NotSetup)
+    }
+
+    fn setup(
+        &mut self,
+        seed: u64,
+        set: u8,
+        players: u8,
+        starting_player: u8,
+        mode: GameMode,
+    ) -> Result<(), RuleError> {
+        if self.turn != TurnStates::Setup {
+            return Err(RuleError::AlreadySetup);
+        }
+        if players as usize > MAXIMUM_PLAYERS || !mode.valid_player_count(players) {
+            return Err(RuleError::InvalidSetup("invalid number of players"));
+        }
+        if starting_player >= players {
+            return Err(RuleError::InvalidSetup("invalid starting player"));
+        }
+        if set != self.library.id {
+            return Err(RuleError::InvalidSetup(
+                "the set is not the one of the library",
+            ));
+        }
+        let library = self.library.clone();
+        for (_, name) in library
+            .market_deck
+            .0
+            .iter()
+            .chain(library.player_deck.0.iter())
+            .chain(library.joker_deck.0.iter())
+        {
+            if !library.cards.contains_key(name) {
+                return Err(RuleError::UnknownCard(name.clone()));
+            }
+        }
+        self.rng = GameRng::from_seed(seed);
+        self.mode = mode;
+        self.players = (0..players)
+            .map(|p| PlayerState {
+                life: mode.starting_life(p, players, library.rules.starting_life),
+                ..Default::default()
+            })
+            .collect();
+        //same spawn order as the setup event handler
+        let mut index = 0;
+        for (qty, name) in &library.market_deck.0 {
+            for _ in 0..*qty {
+                self.spawn(name, CardOwners::Market, Stacks::MarketDeck, index);
+                index += 1;
+            }
+        }
+        let mut index = 0;
+        for (qty, name) in &library.player_deck.0 {
+            for _ in 0..*qty {
+                for p in 0..players {
+                    self.spawn(name, CardOwners::Player(p), Stacks::PlayerDeck, index);
+                }
+                index += 1;
+            }
+        }
+        let mut index = 0;
+        for (qty, name) in &library.joker_deck.0 {
+            for _ in 0..*qty {
+                self.spawn(name, CardOwners::Market, Stacks::JokerDeck, index);
+                index += 1;
+            }
+        }
+        self.shuffle(CardOwners::Market, Stacks::MarketDeck);
+        for p in 0..players {
+            self.shuffle(CardOwners::Player(p), Stacks::PlayerDeck);
+        }
+        let rules = library.rules;
+        for slot in 0..rules.market_size {
+            self.reload_market(slot);
+        }
+        //the starting player draws fewer cards
+        for i in 0..rules.hand_size {
+            for p in 0..players {
+                if i < rules.first_hand_size || p != starting_player {
+                    self.draw(p);
+                }
+            }
+        }
+        //the first hands are not drawn during a turn
+        for state in self.players.iter_mut() {
+            state.tracker.turn_finished();
+        }
+        self.start_turn(starting_player);
+        Ok(())
+    }
+
+    fn spawn(&mut self, name: &str, owner: CardOwners, stack: Stacks, index: usize) {
+        let card = &self.library.cards[name];
+        let mut factions = HashSet::with_capacity(card.factions.len());
+        factions.extend(card.factions.iter().copied());
+        self.cards.push(CardState {
+            name: name.to_string(),
+            kind: card.kind,
+            factions: CardFactions(factions),
+            cost: card.cost,
+            actions: CardActions::from_serialized_card(card),
+            owner,
+            stack,
+            index,
+            visibility: if stack == Stacks::JokerDeck {
+                CardVisibility::Visible
+            } else {
+                CardVisibility::Hidden
+            },
+            life: BaseLife(match card.kind {
+                CardKinds::Ship => 0,
+                CardKinds::Base(life) | CardKinds::Outpost(life) => life,
+            }),
+            copy: None,
+        });
+    }
+
+    fn play_card(&mut self, player: u8, slot: u8) -> Result<(), RuleError> {
+        let owner = CardOwners::Player(player);
+        let card = self
+            .card_at(owner, Stacks::Hand, slot as usize)
+            .ok_or(RuleError::NoCard(slot as u32))?;
+        let ship = self.cards[card].kind == CardKinds::Ship;
+        //as the play_card handler: the primary ability is queued before the move, so it resolves
+        //before the triggers fired by the ship being played
+        if ship {
+            let Self { cards, players, .. } = self;
+            let tracker = &players[player as usize].tracker;
+            if let Some((set, _)) = cards[card].actions.use_action(0, tracker) {
+                let set = set.clone();
+                self.queue_set(player, card, set);
+            }
+        }
+        self.push_card(
+            card,
+            owner,
+            if ship {
+                Stacks::UsedCards
+            } else {
+                Stacks::Bases
+            },
+            0,
+            CardVisibility::Visible,
+        );
+        Ok(())
+    }
+
+    fn activate_card(
+        &mut self,
+        player: u8,
+        base: bool,
+        index: u32,
+        action: u8,
+    ) -> Result<(), RuleError> {
+        let card = self
+            .card_at(
+                CardOwners::Player(player),
+                if base {
+                    Stacks::Bases
+                } else {
+                    Stacks::UsedCards
+                },
+                index as usize,
+            )
+            .ok_or(RuleError::NoCard(index))?;
+        let Self { cards, players, .. } = self;
+        let tracker = &players[player as usize].tracker;
+        let (set, scrap) = cards[card]
+            .actions
+            .use_action(action, tracker)
+            .map(|(set, scrap)| (set.clone(), scrap))
+            .ok_or(RuleError::ActionUnavailable(action))?;
+        if scrap {
+            self.scrap(card);
+        }
+        self.queue_set(player, card, set);
+        Ok(())
+    }
+
+    fn buy_card(&mut self, player: u8, from: BuyFrom) -> Result<(), RuleError> {
+        let (card, slot) = match from {
+            BuyFrom::Market(slot) => (
+                self.card_at(CardOwners::Market, Stacks::MarketRow, slot as usize),
+                Some(slot),
+            ),
+            BuyFrom::Joker => (self.card_at(CardOwners::Market, Stacks::JokerDeck, 0), None),
+        };
+        let card = card.ok_or(RuleError::NoCard(slot.unwrap_or(0) as u32))?;
+        let cost = self.cards[card].cost;
+        let available = self.players[player as usize].economy;
+        if cost > available {
+            return Err(RuleError::NotEnoughEconomy { cost, available });
+        }
+        self.players[player as usize].economy -= cost;
+        self.acquire(player, card);
+        if let Some(slot) = slot {
+            self.reload_market(slot);
+        }
+        Ok(())
+    }
+
+    fn attack(
+        &mut self,
+        by: u8,
+        target: u8,
+        as_much_as_possible: bool,
+        base_index: Option<u32>,
+    ) -> Result<(), RuleError> {
+        if !self.targets(by).contains(&target) {
+            return Err(RuleError::InvalidTarget(target));
+        }
+        let attack = self.players[by as usize].attack;
+        if attack <= 0 {
+            return Err(RuleError::NoAttack);
+        }
+        let owner = CardOwners::Player(target);
+        let outposts = self
+            .stack(owner, Stacks::Bases)
+            .into_iter()
+            .any(|c| matches!(self.cards[c].kind, CardKinds::Outpost(_)));
+        if let Some(index) = base_index {
+            let card = self
+                .card_at(owner, Stacks::Bases, index as usize)
+                .ok_or(RuleError::NoCard(index))?;
+            match self.cards[card].kind {
+                CardKinds::Ship => return Err(RuleError::NoCard(index)),
+                CardKinds::Base(_) if outposts => return Err(RuleError::OutpostInTheWay),
+                _ => {}
+            }
+            let life = self.cards[card].life.0;
+            let damage = if as_much_as_possible {
+                life.min(attack)
+            } else {
+                1
+            };
+            self.cards[card].life.0 -= damage;
+            self.players[by as usize].attack -= damage;
+            if self.cards[card].life.0 <= 0 {
+                self.discard(target, card);
+            }
+        } else {
+            if outposts {
+                return Err(RuleError::OutpostInTheWay);
+            }
+            let life = self.players[target as usize].life;
+            let damage = if as_much_as_possible {
+                life.min(attack)
+            } else {
+                1
+            };
+            self.change_life(target, -damage);
+            self.players[by as usize].attack -= damage;
+        }
+        Ok(())
+    }
+
+    fn pass_turn(&mut self, player: u8) {
+        let owner = CardOwners::Player(player);
+        self.triggers
+            .retain(|l| l.owner != player || l.duration != TriggerDuration::ThisTurn);
+        for card in self.stack(owner, Stacks::Hand) {
+            self.discard(player, card);
+        }
+        for card in self.stack(owner, Stacks::UsedCards) {
+            self.discard(player, card);
+        }
+        for card in self.cards.iter_mut() {
+            //damage dealt to a base only lasts for the turn, whoever owns the base
+            if let CardKinds::Base(life) | CardKinds::Outpost(life) = card.kind {
+                card.life.0 = life;
+            }
+            if card.owner == owner && card.stack == Stacks::Bases {
+                card.actions.reset();
+            }
+        }
+        let state = &mut self.players[player as usize];
+        state.attack = 0;
+        state.economy = 0;
+        state.next_buy_on_deck = KindMask::None;
+        for _ in 0..self.library.rules.hand_size {
+            self.draw(player);
+        }
+        //after the draws, so the next turn starts with no card drawn
+        self.players[player as usize].tracker.turn_finished();
+        let next = self
+            .mode
+            .next_player(player, self.players.len() as u8, &self.alive());
+        self.start_turn(next);
+    }
+
+    //mirror of the systems reacting to a new PlayerTurn state (forced discards)
+    fn start_turn(&mut self, player: u8) {
+        self.turn = TurnStates::PlayerTurn(player);
+        if self.players[player as usize].tracker.cards_to_discard > 0 {
+            self.tasks.push_back(Task::ForcedDiscard { player });
+        }
+    }
+
+    //mirror of Triggers::fire
+    fn fire(&mut self, trigger: Trigger, player: u8) {
+        for listener in self
+            .triggers
+            .iter()
+            .filter(|l| l.trigger == trigger && l.owner == player)
+        {
+            self.tasks.push_back(Task::Action {
+                player: listener.owner,
+                card: listener.card,
+                action: listener.action,
+            });
+        }
+    }
+
+    //apply a life change to a player and to everyone sharing their life total
+    fn change_life(&mut self, player: u8, delta: i32) {
+        for p in self.mode.life_sharers(player, self.players.len() as u8) {
+            let state = &mut self.players[p as usize];
+            state.life += delta;
+            state.tracker.life_changed(delta);
+        }
+    }
+
+    //move a card and update the trackers, mirror of the end of a CardTransition
+    fn relocate(
+        &mut self,
+        card: CardKey,
+        owner: CardOwners,
+        stack: Stacks,
+        index: usize,
+        visibility: CardVisibility,
+    ) {
+        let previous = self.snapshot(card);
+        if !previous.stack.keep_empty_spaces() {
+            for (c, other) in self.cards.iter_mut().enumerate() {
+                if c != card
+                    && other.owner == previous.owner
+                    && other.stack == previous.stack
+                    && other.index > previous.index.0
+                {
+                    other.index -= 1;
+                }
+            }
+        }
+        let state = &mut self.cards[card];
+        state.owner = owner;
+        state.stack = stack;
+        state.index = index;
+        state.visibility = visibility;
+        //scrapped explorers go back to the joker deck, to be bought again unused
+        if matches!(
+            stack,
+            Stacks::DiscardPile | Stacks::Scrapyard | Stacks::JokerDeck
+        ) {
+            state.actions.reset();
+        }
+        let next = self.snapshot(card);
+        let Self { cards, players, .. } = self;
+        if let CardOwners::Player(p) = previous.owner {
+            players[p as usize]
+                .tracker
+                .card_snapshots(&previous, &next, &cards[card].factions);
+        }
+        if next.owner != previous.owner {
+            if let CardOwners::Player(p) = next.owner {
+                players[p as usize]
+                    .tracker
+                    .card_snapshots(&previous, &next, &cards[card].factions);
+            }
+        }
+        if let TurnStates::PlayerTurn(_) = self.turn {
+            if let Some((trigger, player)) = Trigger::from_move(&previous, &next) {
+                self.fire(trigger, player);
+            }
+        }
+        if leaves_play(&previous, &next) {
+            self.triggers.retain(|l| l.card != card);
+        }
+        //a Stealth Needle leaving play loses what it copied
+        if stack == Stacks::DiscardPile || stack == Stacks::Scrapyard {
+            let state = &mut self.cards[card];
+            if let Some((mut actions, factions)) = state.copy.take() {
+                actions.reset();
+                state.actions = actions;
+                state.factions = factions;
+            }
+        }
+    }
+
+    //move a card and push the cards of the target stack, mirror of the PushCardToStack routine
+    fn push_card(
+        &mut self,
+        card: CardKey,
+        owner: CardOwners,
+        stack: Stacks,
+        index: usize,
+        visibility: CardVisibility,
+    ) {
+        self.relocate(card, owner, stack, index, visibility);
+        for (c, other) in self.cards.iter_mut().enumerate() {
+            if c != card && other.owner == owner && other.stack == stack && other.index >= index {
+                other.index += 1;
+            }
+        }
+    }
+
+    fn snapshot(&self, card: CardKey) -> CardStateSnapshot {
+        let state = &self.cards[card];
+        CardStateSnapshot {
+            owner: state.owner,
+            stack: state.stack,
+            index: CardIndex(state.index),
+            visibility: state.visibility,
+        }
+    }
+
+    fn discard(&mut self, player: u8, card: CardKey) {
+        self.push_card(
+            card,
+            CardOwners::Player(player),
+            Stacks::DiscardPile,
+            0,
+            CardVisibility::Visible,
+        );
+    }
+
+    fn scrap(&mut self, card: CardKey) {
+        let stack = if self.cards[card].name == "Explorer" {
+            Stacks::JokerDeck
+        } else {
+            Stacks::Scrapyard
+        };
+        self.push_card(card, CardOwners::Market, stack, 0, CardVisibility::Visible);
+    }
+
+    //discard a card bought from the market, or put it on top of the deck if a flag is active
+    fn acquire(&mut self, player: u8, card: CardKey) {
+        let mask = self.players[player as usize].next_buy_on_deck;
+        if self.cards[card].stack == Stacks::MarketRow && self.cards[card].kind.in_mask(mask) {
+            self.players[player as usize].next_buy_on_deck = KindMask::None;
+            self.push_card(
+                card,
+                CardOwners::Player(player),
+                Stacks::PlayerDeck,
+                0,
+                CardVisibility::Hidden,
+            );
+        } else {
+            self.discard(player, card);
+        }
+    }
+
+    fn shuffle(&mut self, owner: CardOwners, stack: Stacks) {
+        //same order as the shuffle routine: by spawn order, not by index
+        let mut cards = self.stack(owner, stack);
+        cards.sort();
+        self.rng.shuffle(&mut cards);
+        for (index, &card) in cards.iter().enumerate() {
+            self.cards[card].index = index;
+            self.cards[card].visibility = CardVisibility::Hidden;
+        }
+    }
+
+    fn draw(&mut self, player: u8) {
+        let owner = CardOwners::Player(player);
+        let mut deck = self.stack(owner, Stacks::PlayerDeck);
+        let discard_pile = self.stack(owner, Stacks::DiscardPile);
+        //with both the deck and the discard pile empty nothing is drawn, nor shuffled
+        if deck.is_empty() && !discard_pile.is_empty() {
+            for card in discard_pile {
+                self.relocate(card, owner, Stacks::PlayerDeck, 0, CardVisibility::Hidden);
+            }
+            self.shuffle(owner, Stacks::PlayerDeck);
+            deck = self.stack(owner, Stacks::PlayerDeck);
+        }
+        if let Some(&top) = deck.first() {
+            let hand: Vec<usize> = self
+                .stack(owner, Stacks::Hand)
+                .into_iter()
+                .map(|c| self.cards[c].index)
+                .collect();
+            let slot = (0..).find(|i| !hand.contains(i)).unwrap_or(0);
+            self.relocate(top, owner, Stacks::Hand, slot, CardVisibility::Hidden);
+        }
+    }
+
+    fn reload_market(&mut self, slot: u8) {
+        let mut deck = self.stack(CardOwners::Market, Stacks::MarketDeck);
+        if deck.is_empty() {
+            for card in self.stack(CardOwners::Market, Stacks::Scrapyard) {
+                self.relocate(
+                    card,
+                    CardOwners::Market,
+                    Stacks::MarketDeck,
+                    0,
+                    CardVisibility::Hidden,
+                );
+            }
+            self.shuffle(CardOwners::Market, Stacks::MarketDeck);
+            deck = self.stack(CardOwners::Market, Stacks::MarketDeck);
+        }
+        if let Some(&top) = deck.first() {
+            self.relocate(
+                top,
+                CardOwners::Market,
+                Stacks::MarketRow,
+                slot as usize,
+                CardVisibility::Visible,
+            );
+        }
+    }
+
+    fn run(&mut self) {
+        while self.pending.is_none() {
+            if let Some(task) = self.tasks.pop_front() {
+                self.execute(task);
+            } else {
+                break;
+            }
+        }
+        self.check_victory();
+    }
+
+    fn concede(&mut self, player: u8) -> Result<(), RuleError> {
+        if self.turn == TurnStates::Setup {
+            return Err(RuleError::NotSetup);
+        }
+        //players who are out can not concede again
+        if self
+            .players
+            .get(player as usize)
+            .is_none_or(|p| p.life <= 0)
+        {
+            return Err(RuleError::InvalidPlayer(player));
+        }
+        //the player (and their team if they share a life total) is out, the others may keep playing
+        let players = self.players.len() as u8;
+        for p in self.mode.life_sharers(player, players) {
+            self.players[p as usize].life = 0;
+        }
+        let alive = self.alive();
+        if let Some(winner) = self.mode.winner(players, &alive) {
+            self.game_over(winner, GameOverReason::Conceded);
+        } else if let TurnStates::PlayerTurn(current) = self.turn {
+            if !alive[current as usize] {
+                self.tasks.clear();
+                self.pending = None;
+                self.start_turn(self.mode.next_player(current, players, &alive));
+            }
+        }
+        Ok(())
+    }
+
+    //the game ends as soon as the mode has a winner
+    fn check_victory(&mut self) {
+        if let TurnStates::PlayerTurn(_) | TurnStates::PlayerCleanup(_) = self.turn {
+            if let Some(winner) = self.mode.winner(self.players.len() as u8, &self.alive()) {
+                self.game_over(winner, GameOverReason::LifeDepleted);
+            }
+        }
+    }
+
+    fn game_over(&mut self, winner: u8, reason: GameOverReason) {
+        self.turn = TurnStates::GameOver { winner, reason };
+        self.tasks.clear();
+        self.pending = None;
+    }
+
+    fn capture(&self) -> Capture {
+        Capture {
+            turn: self.turn,
+            cards: self
+                .cards
+                .iter()
+                .map(|c| (c.owner, c.stack, c.index, c.visibility, c.life.0))
+                .collect(),
+            players: self
+                .players
+                .iter()
+                .map(|p| (p.life, p.attack, p.economy))
+                .collect(),
+        }
+    }
+
+    fn effects(&self, before: Capture) -> Vec<Effect> {
+        let mut effects = Vec::new();
+        for (card, state) in self.cards.iter().enumerate() {
+            let previous = before.cards.get(card);
+            if previous.is_none() {
+                effects.push(Effect::SpawnCard {
+                    card,
+                    name: state.name.clone(),
+                });
+            }
+            if previous.is_none_or(|&(o, s, i, v, _)| {
+                (o, s, i, v) != (state.owner, state.stack, state.index, state.visibility)
+            }) {
+                effects.push(Effect::MoveCard {
+                    card,
+                    owner: state.owner,
+                    stack: state.stack,
+                    index: state.index,
+                    visibility: state.visibility,
+                });
+            }
+            if previous.is_some_and(|&(.., life)| life != state.life.0) {
+                effects.push(Effect::BaseLife {
+                    card,
+                    life: state.life.0,
+                });
+            }
+        }
+        for (player, state) in self.players.iter().enumerate() {
+            let player = player as u8;
+            let (life, attack, economy) = before
+                .players
+                .get(player as usize)
+                .copied()
+                .unwrap_or_default();
+            if life != state.life {
+                effects.push(Effect::Life {
+                    player,
+                    value: state.life,
+                });
+            }
+            if attack != state.attack {
+                effects.push(Effect::Attack {
+                    player,
+                    value: state.attack,
+                });
+            }
+            if economy != state.economy {
+                effects.push(Effect::Economy {
+                    player,
+                    value: state.economy,
+                });
+            }
+        }
+        if before.turn != self.turn {
+            effects.push(Effect::Turn(self.turn));
+        }
+        if let Some(pending) = &self.pending {
+            effects.push(Effect::Decision(pending.decision.clone()));
+        }
+        effects
+    }
+}
+
+//state of the board before an event, used to compute the effects
+struct Capture {
+    turn: TurnStates,
+    cards: Vec<(CardOwners, Stacks, usize, CardVisibility, i32)>,
+    players: Vec<(i32, i32, i32)>,
+}
@@ -0,0 +1,246 @@
+use crate::cards::actions::uniques::StealthNeedleCopy;
+use crate::cards::actions::KindMask;
+use crate::cards::assets::serializer::AssetLoadderError;
+use crate::cards::transition::CardStateSnapshot;
+use crate::engine::keyframes::{is_turn_boundary, Keyframe};
+use crate::engine::{CardKey, Effect, GameState, Listener, RuleError};
+use crate::game::events::GameEvents;
+use crate::game::history::{BoardSnapshot, CardSnapshot, PlayerSnapshot, TriggerSnapshot};
+use crate::prelude::*;
+use crate::states::turn::TurnStates;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use thiserror::Error;
+
+//everything a game needs to be played again exactly: the events, and the answers to the
+//decisions they opened (which are not part of the event log)
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub enum ReplayInput {
+    Event(GameEvents),
+    Select(Vec<CardKey>),
+    Choose(Vec<u8>),
+}
+
+impl std::fmt::Display for ReplayInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayInput::Event(event) => f.write_fmt(format_args!("{event:?}")),
+            ReplayInput::Select(cards) => f.write_fmt(format_args!("select {cards:?}")),
+            ReplayInput::Choose(choices) => f.write_fmt(format_args!("choose {choices:?}")),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error("could not access the replay file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not write RON: {0}")]
+    Ron(#[from] ron::Error),
+    #[error("could not parse RON: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("could not load the cards: {0}")]
+    Library(#[from] AssetLoadderError),
+    #[error("the replay does not start with a setup event")]
+    MissingSetup,
+    #[error("input {step} was refused: {error}")]
+    Rule { step: usize, error: RuleError },
+    #[error("the keyframe of input {0} does not match the cards of the game")]
+    InvalidKeyframe(usize),
+    #[error("the game diverges from the recording after input {0}")]
+    Diverged(usize),
+}
+
+//recorded game, the first input is expected to be the setup event
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+pub struct Replay {
+    pub inputs: Vec<ReplayInput>,
+    #[serde(default)]
+    pub keyframes: Vec<Keyframe>, //see Replay::build_keyframes
+    #[serde(default)]
+    pub hashes: Vec<u64>, //GameState::hash after each input
+}
+
+impl GameState {
+    pub fn input(&mut self, input: ReplayInput) -> Result<Vec<Effect>, RuleError> {
+        match input {
+            ReplayInput::Event(event) => self.apply(event),
+            ReplayInput::Select(cards) => self.select(cards),
+            ReplayInput::Choose(choices) => self.choose(choices),
+        }
+    }
+
+    //player allowed to give an input: the one answering the pending decision, the current player
+    //for events, or anyone for their own concession
+    pub fn input_player(&self, input: &ReplayInput) -> Option<u8> {
+        match input {
+            ReplayInput::Event(GameEvents::Concede(player)) => Some(*player),
+            ReplayInput::Event(GameEvents::Setup { .. }) => None,
+            ReplayInput::Event(_) => self.current_player(),
+            ReplayInput::Select(_) | ReplayInput::Choose(_) => {
+                self.pending().map(|decision| decision.player())
+            }
+        }
+    }
+
+    //board of the engine in the format used by undo and saves, so the bevy app can display it
+    //(reveal_hands shows every hand face up, as seen by a spectator)
+    pub fn board_snapshot(&self, reveal_hands: bool) -> BoardSnapshot {
+        let current = self.current_player().unwrap_or(0) as usize;
+        BoardSnapshot {
+            cards: self
+                .cards
+                .iter()
+                .enumerate()
+                .map(|(id, card)| CardSnapshot {
+                    id: CardId(id),
+                    state: CardStateSnapshot {
+                        owner: card.owner,
+                        stack: card.stack,
+                        index: CardIndex(card.index),
+                        visibility: if reveal_hands && card.stack == Stacks::Hand {
+                            CardVisibility::Visible
+                        } else {
+                            card.visibility
+                        },
+                    },
+                    actions: card.actions.clone(),
+                    factions: card.factions.clone(),
+                    copy: card
+                        .copy
+                        .clone()
+                        .map(|(actions, factions)| StealthNeedleCopy { actions, factions }),
+                    base_life: match card.kind {
+                        CardKinds::Ship => None,
+                        _ => Some(card.life),
+                    },
+                })
+                .collect(),
+            players: self
+                .players
+                .iter()
+                .enumerate()
+                .map(|(player, state)| PlayerSnapshot {
+                    player: player as u8,
+                    life: state.life,
+                    attack: state.attack,
+                    economy: state.economy,
+                    tracker: state.tracker.clone(),
+                })
+                .collect(),
+            next_buy_on_deck: self
+                .players
+                .get(current)
+                .map_or(Default::default(), |p| p.next_buy_on_deck),
+            triggers: self
+                .triggers
+                .iter()
+                .map(|l| TriggerSnapshot {
+                    card: CardId(l.card),
+                    owner: l.owner,
+                    trigger: l.trigger,
+                    duration: l.duration,
+                    action: l.action,
+                })
+                .collect(),
+            rng: self.rng,
+        }
+    }
+}
+
+impl GameState {
+    //inverse of board_snapshot, on a state set up by the same setup event: lets the engine take
+    //over a board of the bevy app (the snapshot must be taken at rest, nothing pending)
+    pub fn restore_board(&mut self, board: &BoardSnapshot, turn: TurnStates) {
+        for card in &board.cards {
+            let Some(state) = self.cards.get_mut(card.id.0) else {
+                continue;
+            };
+            state.owner = card.state.owner;
+            state.stack = card.state.stack;
+            state.index = card.state.index.0;
+            state.visibility = card.state.visibility;
+            state.actions = card.actions.clone();
+            state.factions = card.factions.clone();
+            state.copy = card.copy.clone().map(|copy| (copy.actions, copy.factions));
+            if let Some(life) = card.base_life {
+                state.life = life;
+            }
+        }
+        for player in &board.players {
+            if let Some(state) = self.players.get_mut(player.player as usize) {
+                state.life = player.life;
+                state.attack = player.attack;
+                state.economy = player.economy;
+                state.tracker = player.tracker.clone();
+                state.next_buy_on_deck = KindMask::None;
+            }
+        }
+        self.turn = turn;
+        if let Some(player) = self.current_player() {
+            self.players[player as usize].next_buy_on_deck = board.next_buy_on_deck;
+        }
+        self.triggers = board
+            .triggers
+            .iter()
+            .map(|t| Listener {
+                card: t.card.0,
+                owner: t.owner,
+                trigger: t.trigger,
+                duration: t.duration,
+                action: t.action,
+            })
+            .collect();
+        self.rng = board.rng;
+        self.tasks.clear();
+        self.pending = None;
+    }
+}
+
+impl Replay {
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        Ok(ron::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), ReplayError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    //apply an input to the state and keep it in the replay if it was accepted
+    pub fn record(
+        &mut self,
+        state: &mut GameState,
+        input: ReplayInput,
+    ) -> Result<Vec<Effect>, RuleError> {
+        let effects = state.input(input.clone())?;
+        if let ReplayInput::Event(event) = &input {
+            if is_turn_boundary(event) {
+                self.keyframes.extend(state.keyframe(self.inputs.len()));
+            }
+        }
+        self.inputs.push(input);
+        self.hashes.push(state.hash());
+        Ok(effects)
+    }
+
+    //play the inputs from a fresh state, visit is called after each input with its index
+    pub fn play(
+        &self,
+        state: &mut GameState,
+        mut visit: impl FnMut(usize, &GameState),
+    ) -> Result<(), ReplayError> {
+        for (step, input) in self.inputs.iter().enumerate() {
+            state
+                .input(input.clone())
+                .map_err(|error| ReplayError::Rule { step, error })?;
+            visit(step, state);
+        }
+        Ok(())
+    }
+}
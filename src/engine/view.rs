@@ -0,0 +1,177 @@
+use crate::engine::{CardKey, Decision, GameState};
+use crate::game::modes::GameMode;
+use crate::game::rng::GameRng;
+use crate::prelude::*;
+use crate::states::turn::TurnStates;
+use serde::{Deserialize, Serialize};
+
+//what one player is allowed to know of the board: the cards they can see, and only the size of
+//the other stacks (their deck, the market deck, the hands of their opponents)
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+pub struct PlayerView {
+    pub player: u8, //seat of the player this view was made for
+    pub set: u8,
+    pub mode: GameMode,
+    pub turn: TurnStates,
+    pub players: Vec<CounterView>,
+    pub cards: Vec<CardView>,
+    pub hidden: Vec<HiddenStack>,
+    pub pending: Option<Decision>, //only the decisions of this player
+    pub waiting_for: Option<u8>,   //player expected to give the next input
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct CounterView {
+    pub life: i32,
+    pub attack: i32,
+    pub economy: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct CardView {
+    pub id: CardKey,
+    pub name: String,
+    pub owner: CardOwners,
+    pub stack: Stacks,
+    pub index: usize,
+    pub life: Option<i32>, //bases and outposts
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct HiddenStack {
+    pub owner: CardOwners,
+    pub stack: Stacks,
+    pub count: usize,
+}
+
+impl PlayerView {
+    pub fn card(&self, card: CardKey) -> Option<&CardView> {
+        self.cards.iter().find(|c| c.id == card)
+    }
+
+    //visible cards of a stack, sorted by index
+    pub fn stack(&self, owner: CardOwners, stack: Stacks) -> Vec<&CardView> {
+        let mut out: Vec<&CardView> = self
+            .cards
+            .iter()
+            .filter(|c| c.owner == owner && c.stack == stack)
+            .collect();
+        out.sort_by_key(|c| c.index);
+        out
+    }
+
+    //number of cards of a stack, seen or not
+    pub fn count(&self, owner: CardOwners, stack: Stacks) -> usize {
+        self.stack(owner, stack).len()
+            + self
+                .hidden
+                .iter()
+                .find(|h| h.owner == owner && h.stack == stack)
+                .map_or(0, |h| h.count)
+    }
+}
+
+impl GameState {
+    //true if the player can see this card: face up cards and their own hand. being asked to select
+    //a card never reveals it, the selections only offer cards their player already sees
+    pub fn visible_to(&self, card: CardKey, player: u8) -> bool {
+        let state = &self.cards[card];
+        state.visibility == CardVisibility::Visible
+            || (state.stack == Stacks::Hand && state.owner == CardOwners::Player(player))
+    }
+
+    //the board as seen by a player, nothing in it tells the order or the content of hidden stacks
+    pub fn view(&self, player: u8) -> PlayerView {
+        let mut cards = Vec::new();
+        let mut hidden: Vec<HiddenStack> = Vec::new();
+        for (id, state) in self.cards.iter().enumerate() {
+            if self.visible_to(id, player) {
+                cards.push(CardView {
+                    id,
+                    name: state.name.clone(),
+                    owner: state.owner,
+                    stack: state.stack,
+                    index: state.index,
+                    life: match state.kind {
+                        CardKinds::Base(_) | CardKinds::Outpost(_) => Some(state.life.0),
+                        _ => None,
+                    },
+                });
+            } else if let Some(stack) = hidden
+                .iter_mut()
+                .find(|h| h.owner == state.owner && h.stack == state.stack)
+            {
+                stack.count += 1;
+            } else {
+                hidden.push(HiddenStack {
+                    owner: state.owner,
+                    stack: state.stack,
+                    count: 1,
+                });
+            }
+        }
+        let pending = self.pending();
+        PlayerView {
+            player,
+            set: self.library.id,
+            mode: self.mode,
+            turn: self.turn,
+            players: self
+                .players
+                .iter()
+                .map(|p| CounterView {
+                    life: p.life,
+                    attack: p.attack,
+                    economy: p.economy,
+                })
+                .collect(),
+            cards,
+            hidden,
+            pending: pending.filter(|d| d.player() == player).cloned(),
+            waiting_for: pending.map(|d| d.player()).or(self.current_player()),
+        }
+    }
+
+    //one of the boards this player could be looking at: the cards they cannot see trade places
+    //with the other unseen cards of the same owner (the hand and the deck of an opponent, or the
+    //market deck) and the future shuffles use a new seed, as neither can be known by the player
+    pub fn determinize(&mut self, player: u8, seed: u64) {
+        let mut rng = GameRng::from_seed(seed);
+        let mut groups: Vec<(CardOwners, Option<Stacks>, Vec<CardKey>)> = Vec::new();
+        for id in 0..self.cards.len() {
+            if self.visible_to(id, player) {
+                continue;
+            }
+            let owner = self.cards[id].owner;
+            //the stacks of the market do not mix (the explorers are not in the market deck)
+            let stack = match owner {
+                CardOwners::Market => Some(self.cards[id].stack),
+                CardOwners::Player(_) => None,
+            };
+            match groups
+                .iter_mut()
+                .find(|(o, s, _)| *o == owner && *s == stack)
+            {
+                Some((_, _, cards)) => cards.push(id),
+                None => groups.push((owner, stack, vec![id])),
+            }
+        }
+        for (_, _, cards) in groups {
+            let mut places: Vec<(Stacks, usize, CardVisibility)> = cards
+                .iter()
+                .map(|&c| {
+                    let card = &self.cards[c];
+                    (card.stack, card.index, card.visibility)
+                })
+                .collect();
+            rng.shuffle(&mut places);
+            for (card, (stack, index, visibility)) in cards.into_iter().zip(places) {
+                let card = &mut self.cards[card];
+                card.stack = stack;
+                card.index = index;
+                card.visibility = visibility;
+            }
+        }
+        self.rng = GameRng::from_seed(!seed);
+    }
+}
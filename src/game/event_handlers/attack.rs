@@ -1,35 +1,48 @@
 use crate::cards::components::kinds::BaseLife;
 use crate::game::events::{GameEvent, GameEvents};
+use crate::game::modes::GameMode;
 use crate::game::routines::RoutineManager;
-use crate::players::{Player, PlayerAttack, PlayerLife};
+use crate::players::{PlayerAttack, PlayerCount, PlayerLife, PlayerOwned, PlayerTurnTracker};
 use crate::prelude::*;
 use crate::states::turn::TurnStates;
+use bevy::ecs::system::SystemParam;
 
-pub fn attack<const BY: u8, const PLAYER: u8>(
-    mut events: ResMut<GameEvent>,
-    turn: Res<State<TurnStates>>,
-    indexes: Query<(Entity, &CardIndex, &CardKinds), (With<Player<PLAYER>>, With<Bases>)>,
-    mut bases: Query<
-        &mut BaseLife,
+type InPlay<T, U> = (With<Bases>, With<T>, Without<U>);
+
+//the bases in play, by index and split by kind
+#[derive(SystemParam)]
+pub struct BasesInPlay<'w, 's> {
+    indexes: Query<
+        'w,
+        's,
         (
-            With<Player<PLAYER>>,
-            With<Bases>,
-            With<Base>,
-            Without<Outpost>,
+            Entity,
+            &'static CardIndex,
+            &'static CardKinds,
+            &'static CardOwners,
         ),
+        With<Bases>,
     >,
-    mut outposts: Query<
-        &mut BaseLife,
+    bases: Query<'w, 's, (&'static mut BaseLife, &'static CardOwners), InPlay<Base, Outpost>>,
+    outposts: Query<'w, 's, (&'static mut BaseLife, &'static CardOwners), InPlay<Outpost, Base>>,
+}
+
+pub fn attack(
+    mut events: ResMut<GameEvent>,
+    turn: Res<State<TurnStates>>,
+    mut in_play: BasesInPlay,
+    mut counters: Query<
         (
-            With<Player<PLAYER>>,
-            With<Bases>,
-            With<Outpost>,
-            Without<Base>,
+            &mut PlayerLife,
+            &mut PlayerAttack,
+            &mut PlayerTurnTracker,
+            &CardOwners,
         ),
+        With<PlayerOwned>,
     >,
-    mut life: Query<&mut PlayerLife, With<Player<PLAYER>>>,
-    mut attack: Query<&mut PlayerAttack, With<Player<BY>>>,
     mut routines: ResMut<RoutineManager>,
+    player_count: Res<PlayerCount>,
+    mode: Res<GameMode>,
 ) {
     if let Some(&GameEvents::Attack {
         as_much_as_possible,
@@ -37,16 +50,37 @@ pub fn attack<const BY: u8, const PLAYER: u8>(
         base_index,
     }) = events.get_unprocessed()
     {
+        let BasesInPlay {
+            indexes,
+            bases,
+            outposts,
+        } = &mut in_play;
         let mut ok = false;
+        let target = CardOwners::Player(player);
 
-        if let TurnStates::PlayerTurn(p) = turn.get() {
-            if let Ok(mut attack) = attack.get_single_mut() {
-                if attack.0 > 0 {
+        if let &TurnStates::PlayerTurn(p) = turn.get() {
+            let mut attack = 0;
+            let mut target_life = None;
+            for (l, a, _, o) in counters.iter_mut() {
+                if *o == CardOwners::Player(p) {
+                    attack = a.0;
+                }
+                if *o == target && l.0 > 0 {
+                    target_life = Some(l.0);
+                }
+            }
+            //the mode decides who can be attacked, and a player already out of the game can't be
+            if let (true, Some(target_life)) =
+                (mode.can_target(p, player, player_count.0), target_life)
+            {
+                let outposts_empty = !outposts.iter().any(|(_, o)| *o == target);
+                let mut damage = 0;
+                if attack > 0 {
                     if let Some(index) = base_index {
                         //attack a base
                         let mut card = Entity::PLACEHOLDER;
                         let mut outpost = false;
-                        for (e, i, k) in indexes.iter() {
+                        for (e, i, k, _) in indexes.iter().filter(|(.., o)| **o == target) {
                             if i.0 == index as usize {
                                 match k {
                                     CardKinds::Ship => {
@@ -66,30 +100,28 @@ pub fn attack<const BY: u8, const PLAYER: u8>(
                         }
                         if card != Entity::PLACEHOLDER {
                             if outpost {
-                                let mut life = outposts.get_mut(card).unwrap();
-                                let damage = if as_much_as_possible {
-                                    life.0.min(attack.0)
+                                let (mut life, _) = outposts.get_mut(card).unwrap();
+                                damage = if as_much_as_possible {
+                                    life.0.min(attack)
                                 } else {
                                     1
                                 };
                                 life.0 -= damage;
-                                attack.0 -= damage;
                                 if life.0 == 0 {
-                                    routines.discard(PLAYER, card);
+                                    routines.discard(player, card);
                                 }
                                 ok = true;
                             } else {
-                                if outposts.is_empty() {
-                                    let mut life = bases.get_mut(card).unwrap();
-                                    let damage = if as_much_as_possible {
-                                        life.0.min(attack.0)
+                                if outposts_empty {
+                                    let (mut life, _) = bases.get_mut(card).unwrap();
+                                    damage = if as_much_as_possible {
+                                        life.0.min(attack)
                                     } else {
                                         1
                                     };
                                     life.0 -= damage;
-                                    attack.0 -= damage;
                                     if life.0 == 0 {
-                                        routines.discard(PLAYER, card);
+                                        routines.discard(player, card);
                                     }
                                     ok = true;
                                 } else {
@@ -99,21 +131,36 @@ pub fn attack<const BY: u8, const PLAYER: u8>(
                         }
                     } else {
                         //attack the player itself
-                        if outposts.is_empty() {
-                            let mut life = life.get_single_mut().unwrap();
-                            let damage = if as_much_as_possible {
-                                life.0.min(attack.0)
+                        if outposts_empty {
+                            damage = if as_much_as_possible {
+                                target_life.min(attack)
                             } else {
                                 1
                             };
-                            life.0 -= damage;
-                            attack.0 -= damage;
+                            //in team modes, the whole team loses the life
+                            let sharers: Vec<CardOwners> = mode
+                                .life_sharers(player, player_count.0)
+                                .into_iter()
+                                .map(CardOwners::Player)
+                                .collect();
+                            for (mut life, _, mut tracker, _) in
+                                counters.iter_mut().filter(|(.., o)| sharers.contains(o))
+                            {
+                                life.0 -= damage;
+                                tracker.life_changed(-damage);
+                            }
                             ok = true;
                         } else {
                             //cannot, should make a visual feedback on outposts
                         }
                     }
                 }
+                for (_, mut attack, ..) in counters
+                    .iter_mut()
+                    .filter(|(.., o)| **o == CardOwners::Player(p))
+                {
+                    attack.0 -= damage;
+                }
             }
         }
         if ok {
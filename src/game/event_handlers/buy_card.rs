@@ -1,24 +1,21 @@
 use crate::game::events::{BuyFrom, GameEvent, GameEvents};
 use crate::game::routines::RoutineManager;
-use crate::players::{Player, PlayerEconomy};
+use crate::players::{PlayerEconomy, PlayerOwned};
 use crate::prelude::*;
 use crate::states::turn::TurnStates;
 
-pub fn buy_card<const PLAYER: u8>(
+pub fn buy_card(
     mut event: ResMut<GameEvent>,
     mut routines: ResMut<RoutineManager>,
     turn: Res<State<TurnStates>>,
-    mut eco: Query<&mut PlayerEconomy, With<Player<PLAYER>>>,
+    mut eco: Query<(&mut PlayerEconomy, &CardOwners), With<PlayerOwned>>,
     jokers: Query<(Entity, &CardIndex, &CardCost), With<JokerDeck>>,
     market: Query<(Entity, &CardIndex, &CardCost), With<MarketRow>>,
 ) {
     if let Some(&GameEvents::BuyCard(from)) = event.get_unprocessed() {
-        if let TurnStates::PlayerTurn(p) = turn.get() {
-            if *p != PLAYER {
-                return;
-            }
+        if let &TurnStates::PlayerTurn(p) = turn.get() {
             let mut ok = false;
-            if let Ok(mut eco) = eco.get_single_mut() {
+            if let Some((mut eco, _)) = eco.iter_mut().find(|(_, o)| **o == CardOwners::Player(p)) {
                 let (market, card, cost) = match from {
                     BuyFrom::Market(slot) => {
                         let mut card = None;
@@ -47,7 +44,7 @@ pub fn buy_card<const PLAYER: u8>(
                     if cost <= eco.0 {
                         eco.0 -= cost;
                         ok = true;
-                        routines.discard(PLAYER, card);
+                        routines.discard(p, card);
                         if let Some(slot) = market {
                             routines.reload_market(slot);
                         }
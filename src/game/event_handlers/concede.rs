@@ -0,0 +1,83 @@
+use crate::game::events::{GameEvent, GameEvents};
+use crate::game::modes::GameMode;
+use crate::game::routines::card_action::{Selectable, Selected, Selection};
+use crate::game::routines::RoutineManager;
+use crate::game::GameStates;
+use crate::players::{PlayerCount, PlayerLife};
+use crate::prelude::*;
+use crate::states::turn::{GameOverReason, TurnStates};
+use crate::ui::choice_ui::ChoiceRoot;
+use crate::ui::SelectionValidationButton;
+
+//a conceding player (and their team, if they share a life total) is out of the game. the game
+//only ends if the mode has a winner once they are gone, otherwise the others keep playing. returns
+//whether the turn in progress is over, what it was waiting for is then dropped by abort_turn
+pub fn concede(
+    mut event: ResMut<GameEvent>,
+    current: Res<State<TurnStates>>,
+    mut turn: ResMut<NextState<TurnStates>>,
+    player_count: Res<PlayerCount>,
+    mode: Res<GameMode>,
+    mut lives: Query<(&mut PlayerLife, &CardOwners)>,
+) -> bool {
+    let mut aborted = false;
+    if let Some(&GameEvents::Concede(player)) = event.get_unprocessed() {
+        if player < player_count.0 {
+            let sharers = mode.life_sharers(player, player_count.0);
+            let mut alive = vec![false; player_count.0 as usize];
+            for (mut life, owner) in lives.iter_mut() {
+                if let CardOwners::Player(p) = *owner {
+                    if sharers.contains(&p) {
+                        life.0 = 0;
+                    }
+                    alive[p as usize] = life.0 > 0;
+                }
+            }
+            if let Some(winner) = mode.winner(player_count.0, &alive) {
+                turn.set(TurnStates::GameOver {
+                    winner,
+                    reason: GameOverReason::Conceded,
+                });
+                aborted = true;
+            } else if let &TurnStates::PlayerTurn(current) = current.get() {
+                if !alive[current as usize] {
+                    turn.set(TurnStates::PlayerTurn(mode.next_player(
+                        current,
+                        player_count.0,
+                        &alive,
+                    )));
+                    aborted = true;
+                }
+            }
+            event.set_processed();
+        } else {
+            event.cancel();
+        }
+    }
+    aborted
+}
+
+//the routines left by the turn of a player who is out are dropped, as the selection or the choice
+//they were asked for
+pub fn abort_turn(
+    mut commands: Commands,
+    mut routines: ResMut<RoutineManager>,
+    mut selection: ResMut<Selection>,
+    cards: Query<Entity, With<CardId>>,
+    choices: Query<Entity, With<ChoiceRoot>>,
+    mut validation_button: Query<&mut Visibility, With<SelectionValidationButton>>,
+    mut game_state: ResMut<NextState<GameStates>>,
+) {
+    routines.0.clear();
+    *selection = Selection::default();
+    for card in cards.iter() {
+        commands.entity(card).remove::<(Selectable, Selected)>();
+    }
+    for root in choices.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+    if let Ok(mut visibility) = validation_button.get_single_mut() {
+        *visibility = Visibility::Hidden;
+    }
+    game_state.set(GameStates::MainLoop);
+}
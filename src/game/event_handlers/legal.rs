@@ -0,0 +1,53 @@
+use crate::cards::assets::LoadedLibrary;
+use crate::engine::replay::ReplayInput;
+use crate::engine::GameState;
+use crate::game::events::{GameEvent, GameEvents};
+use crate::game::history::BoardSnapshot;
+use crate::prelude::*;
+use crate::states::turn::TurnStates;
+
+//the board of the app as a rules engine state, so the app, the bots and the network all agree on
+//what can be done
+#[derive(Resource, Default)]
+pub struct EngineBoard {
+    setup: Option<(GameEvents, GameState)>, //state right after the setup of the current game
+}
+
+impl EngineBoard {
+    //None if the game is not set up or the cards of its set are not loaded
+    pub fn state(&mut self, world: &mut World) -> Option<GameState> {
+        let setup = *world.resource::<GameEvent>().log().first()?;
+        if self.setup.as_ref().is_none_or(|(event, _)| *event != setup) {
+            let GameEvents::Setup { set, .. } = setup else {
+                return None;
+            };
+            //the library is built from the assets of the set by the set plugin
+            let library = world.get_resource::<LoadedLibrary>()?.0.clone()?;
+            if library.id != set {
+                return None;
+            }
+            let mut state = GameState::new(library);
+            state.apply(setup).ok()?;
+            self.setup = Some((setup, state));
+        }
+        let mut state = self.setup.as_ref()?.1.clone();
+        let turn = *world.resource::<State<TurnStates>>().get();
+        state.restore_board(&BoardSnapshot::capture(world), turn);
+        Some(state)
+    }
+}
+
+//whether the rules engine lists the event for the player sending it (the conceding player, or
+//the current one). without the cards to build the board, nothing can be checked and the event is
+//refused
+pub fn is_legal(world: &mut World, event: GameEvents) -> bool {
+    world.resource_scope(|world, mut board: Mut<EngineBoard>| {
+        let Some(state) = board.state(world) else {
+            error!("no rules engine board to check {event:?} against, the event is refused");
+            return false;
+        };
+        state
+            .input_player(&ReplayInput::Event(event))
+            .is_some_and(|player| state.is_legal(player, event))
+    })
+}
@@ -1,6 +1,8 @@
 mod activate_card;
 mod attack;
 mod buy_card;
+mod concede;
+mod legal;
 mod pass_turn;
 mod play_card;
 mod setup;
@@ -8,80 +10,103 @@ mod setup;
 use self::activate_card::activate_card;
 use self::attack::attack;
 use self::buy_card::buy_card;
+use self::concede::{abort_turn, concede};
+pub use self::legal::{is_legal, EngineBoard};
 use self::pass_turn::pass_turn;
 use self::play_card::play_card;
 use self::setup::setup;
+use crate::engine::replay::ReplayInput;
 use crate::game::events::{GameEvent, GameEvents};
+use crate::game::history::{is_undoable, record_event, History};
+use crate::game::routines::selection::selection_open;
 use crate::game::routines::RoutineManager;
+use crate::game::GameStates;
+use crate::network::client::ServerGame;
 use crate::prelude::*;
 use crate::states::turn::TurnStates;
+use crate::ui::choice_ui::ChoiceRoot;
 use bevy::ecs::system::RunSystemOnce;
 
 pub fn event_handler_dispatcher(world: &mut World) {
-    //game events can only be processed if no routine is active
-    if world
+    //game events can only be processed if no routine is active, a concession is also accepted
+    //while a player is asked to answer a selection or a choice
+    let idle = world
         .get_resource::<RoutineManager>()
-        .map_or(false, |r| r.is_empty())
-    {
+        .is_some_and(|r| r.is_empty())
+        && *world.resource::<State<GameStates>>().get() == GameStates::MainLoop;
+    let concession = matches!(
+        world
+            .get_resource::<GameEvent>()
+            .and_then(|ge| ge.get_unprocessed()),
+        Some(GameEvents::Concede(_))
+    );
+    if idle || (concession && awaiting_answer(world)) {
         if let Some(&event) = world
             .get_resource::<GameEvent>()
             .and_then(|ge| ge.get_unprocessed())
         {
             let &state = world.get_resource::<State<TurnStates>>().unwrap().get();
+            if let TurnStates::GameOver { .. } = state {
+                //the game is finished, events are left in the log until the board is reset
+                return;
+            }
+            //against a server the event is only an intent, the server checks it and answers with
+            //the board after it
+            if !matches!(event, GameEvents::Setup { .. } | GameEvents::Debug) {
+                if let Some(mut game) = world.get_resource_mut::<ServerGame>() {
+                    game.submit(ReplayInput::Event(event));
+                    world.resource_mut::<GameEvent>().cancel();
+                    return;
+                }
+            }
+            //the handlers accept exactly the legal events, anything else is refused before them
+            if !matches!(event, GameEvents::Setup { .. } | GameEvents::Debug)
+                && !is_legal(world, event)
+            {
+                world.resource_mut::<GameEvent>().cancel();
+                return;
+            }
+            if is_undoable(&event) && matches!(state, TurnStates::PlayerTurn(_)) {
+                record_event(world, event);
+            }
             match event {
                 GameEvents::Setup { .. } => {
                     if state == TurnStates::Setup {
+                        world.resource_mut::<History>().clear();
                         world.run_system_once(setup);
                     }
                 }
                 GameEvents::PlayCard(_) => {
-                    if let TurnStates::PlayerTurn(p) = state {
-                        match p {
-                            0 => world.run_system_once(play_card::<0>),
-                            1 => world.run_system_once(play_card::<1>),
-                            _ => {}
-                        }
+                    if let TurnStates::PlayerTurn(_) = state {
+                        world.run_system_once(play_card);
                     }
                 }
                 GameEvents::ActivateCard { .. } => {
-                    if let TurnStates::PlayerTurn(p) = state {
-                        match p {
-                            0 => world.run_system_once(activate_card::<0>),
-                            1 => world.run_system_once(activate_card::<1>),
-                            _ => {}
-                        }
+                    if let TurnStates::PlayerTurn(_) = state {
+                        world.run_system_once(activate_card);
                     }
                 }
                 GameEvents::BuyCard(_) => {
-                    if let TurnStates::PlayerTurn(p) = state {
-                        match p {
-                            0 => world.run_system_once(buy_card::<0>),
-                            1 => world.run_system_once(buy_card::<1>),
-                            _ => {}
-                        }
+                    if let TurnStates::PlayerTurn(_) = state {
+                        world.run_system_once(buy_card);
                     }
                 }
-                GameEvents::Attack { player, .. } => {
-                    if let TurnStates::PlayerTurn(p) = state {
-                        match p {
-                            0 => match player {
-                                1 => world.run_system_once(attack::<0, 1>),
-                                _ => {}
-                            },
-                            1 => match player {
-                                0 => world.run_system_once(attack::<1, 0>),
-                                _ => {}
-                            },
-                            _ => {}
-                        }
+                GameEvents::Attack { .. } => {
+                    if let TurnStates::PlayerTurn(_) = state {
+                        world.run_system_once(attack);
                     }
                 }
                 GameEvents::PassTurn => {
-                    if let TurnStates::PlayerTurn(p) = state {
-                        match p {
-                            0 => world.run_system_once(pass_turn::<0>),
-                            1 => world.run_system_once(pass_turn::<1>),
-                            _ => {}
+                    if let TurnStates::PlayerTurn(_) = state {
+                        world.resource_mut::<History>().clear();
+                        world.run_system_once(pass_turn);
+                    }
+                }
+                GameEvents::Concede(_) => {
+                    if state != TurnStates::Setup {
+                        world.resource_mut::<History>().clear();
+                        if world.run_system_once(concede) {
+                            world.run_system_once(abort_turn);
                         }
                     }
                 }
@@ -91,3 +116,12 @@ pub fn event_handler_dispatcher(world: &mut World) {
         }
     }
 }
+
+//a selection or a choice is open and nothing else can happen before it is answered
+fn awaiting_answer(world: &mut World) -> bool {
+    selection_open(world)
+        || world
+            .query::<&ChoiceRoot>()
+            .get_single(world)
+            .is_ok_and(|root| !root.finished)
+}
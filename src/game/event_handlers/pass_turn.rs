@@ -1,39 +1,93 @@
 use crate::cards::actions::CardActions;
+use crate::cards::actions::KindMask;
+use crate::cards::assets::LoadedSet;
 use crate::cards::components::kinds::BaseLife;
 use crate::game::events::GameEvent;
-use crate::game::routines::RoutineManager;
-use crate::players::{Player, PlayerAttack, PlayerEconomy, PlayerTurnTracker, MAXIMUM_PLAYERS};
+use crate::game::modes::GameMode;
+use crate::game::routines::move_to_stack::NextBuyOnDeckFlag;
+use crate::game::routines::{RoutineManager, Routines};
+use crate::game::triggers::Triggers;
+use crate::players::{PlayerAttack, PlayerCount, PlayerEconomy, PlayerLife, PlayerOwned};
 use crate::prelude::*;
 use crate::states::turn::TurnStates;
+use bevy::ecs::system::SystemParam;
 
-pub fn pass_turn<const PLAYER: u8>(
+//who plays and who plays next
+#[derive(SystemParam)]
+pub struct TurnOrder<'w> {
+    current: Res<'w, State<TurnStates>>,
+    turn: ResMut<'w, NextState<TurnStates>>,
+    player_count: Res<'w, PlayerCount>,
+    mode: Res<'w, GameMode>,
+}
+
+//what the player leaves on the board at the end of their turn
+#[derive(SystemParam)]
+pub struct PlayedCards<'w, 's> {
+    hand: Query<'w, 's, (Entity, &'static CardIndex, &'static CardOwners), With<Hand>>,
+    used: Query<'w, 's, (Entity, &'static CardIndex, &'static CardOwners), With<UsedCards>>,
+    bases_actions: Query<'w, 's, (&'static mut CardActions, &'static CardOwners), With<Bases>>,
+    bases_life: Query<'w, 's, BaseLives, AnyBase>,
+    next_buy_on_deck: ResMut<'w, NextBuyOnDeckFlag>,
+}
+
+type BaseLives = (
+    &'static mut BaseLife,
+    Option<&'static Base>,
+    Option<&'static Outpost>,
+);
+type AnyBase = Or<(With<Base>, With<Outpost>)>;
+
+pub fn pass_turn(
     mut event: ResMut<GameEvent>,
-    mut turn: ResMut<NextState<TurnStates>>,
+    mut order: TurnOrder,
+    loaded_set: Res<LoadedSet>,
     mut player_trackers: Query<
         (
             &mut PlayerAttack,
             &mut PlayerEconomy,
-            &mut PlayerTurnTracker,
+            &PlayerLife,
+            &CardOwners,
         ),
-        With<Player<PLAYER>>,
+        With<PlayerOwned>,
     >,
     mut routines: ResMut<RoutineManager>,
-    hand: Query<Entity, (With<Hand>, With<Player<PLAYER>>)>,
-    used: Query<Entity, (With<UsedCards>, With<Player<PLAYER>>)>,
-    mut bases_actions: Query<&mut CardActions, (With<Bases>, With<Player<PLAYER>>)>,
-    mut bases_life: Query<
-        (&mut BaseLife, Option<&Base>, Option<&Outpost>),
-        (Or<(With<Base>, With<Outpost>)>, With<Player<PLAYER>>),
-    >,
+    mut triggers: ResMut<Triggers>,
+    mut played: PlayedCards,
 ) {
-    if let Ok((mut attack, mut economy, mut trackers)) = player_trackers.get_single_mut() {
-        attack.0 = 0;
-        economy.0 = 0;
-        trackers.turn_finished();
+    let TurnOrder {
+        current,
+        turn,
+        player_count,
+        mode,
+    } = &mut order;
+    let PlayedCards {
+        hand,
+        used,
+        bases_actions,
+        bases_life,
+        next_buy_on_deck,
+    } = &mut played;
+    let &TurnStates::PlayerTurn(player) = current.get() else {
+        return;
+    };
+    let owner = CardOwners::Player(player);
+    triggers.turn_ended(player);
+    let mut alive = vec![false; player_count.0 as usize];
+    for (mut attack, mut economy, life, o) in player_trackers.iter_mut() {
+        if let CardOwners::Player(p) = *o {
+            alive[p as usize] = life.0 > 0;
+        }
+        if *o == owner {
+            attack.0 = 0;
+            economy.0 = 0;
+        }
     }
-    for mut actions in bases_actions.iter_mut() {
+    next_buy_on_deck.0 = KindMask::None;
+    for (mut actions, _) in bases_actions.iter_mut().filter(|(_, o)| **o == owner) {
         actions.reset();
     }
+    //damage dealt to a base only lasts for the turn, whoever owns the base
     for (mut life, base, outpost) in bases_life.iter_mut() {
         if let Some(Base(v)) = base {
             life.0 = *v;
@@ -42,15 +96,27 @@ pub fn pass_turn<const PLAYER: u8>(
             life.0 = *v;
         }
     }
-    for card in hand.iter() {
-        routines.discard(PLAYER, card);
-    }
-    for card in used.iter() {
-        routines.discard(PLAYER, card);
+    //in stack order, so the discard pile ends up in the same order as in the rules engine
+    let sorted = |cards: &mut Vec<(Entity, &CardIndex, &CardOwners)>| {
+        cards.retain(|(.., o)| **o == owner);
+        cards.sort_by_key(|(_, index, _)| index.0);
+    };
+    let mut in_hand: Vec<_> = hand.iter().collect();
+    let mut in_use: Vec<_> = used.iter().collect();
+    sorted(&mut in_hand);
+    sorted(&mut in_use);
+    for (card, ..) in in_hand.into_iter().chain(in_use) {
+        routines.discard(player, card);
     }
-    for _ in 0..5 {
-        routines.draw(PLAYER, false);
+    for _ in 0..loaded_set.rules.hand_size {
+        routines.draw(player, false);
     }
-    turn.set(TurnStates::PlayerTurn((PLAYER + 1) % MAXIMUM_PLAYERS as u8));
+    //after the draws, so the next turn starts with no card drawn
+    routines.0.push_back(Routines::TurnFinished { player });
+    turn.set(TurnStates::PlayerTurn(mode.next_player(
+        player,
+        player_count.0,
+        &alive,
+    )));
     event.set_processed();
 }
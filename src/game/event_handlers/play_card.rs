@@ -1,27 +1,39 @@
 use crate::cards::actions::CardActions;
 use crate::game::events::{GameEvent, GameEvents};
 use crate::game::routines::RoutineManager;
-use crate::players::{Player, PlayerTurnTracker};
+use crate::players::{PlayerOwned, PlayerTurnTracker};
 use crate::prelude::*;
+use crate::states::turn::TurnStates;
 
-pub fn play_card<const PLAYER: u8>(
+pub fn play_card(
     mut event: ResMut<GameEvent>,
     mut routines: ResMut<RoutineManager>,
+    turn: Res<State<TurnStates>>,
     mut hand: Query<
-        (Entity, &CardIndex, Option<&Ship>, &mut CardActions),
-        (With<Hand>, With<Player<PLAYER>>),
+        (
+            Entity,
+            &CardIndex,
+            &CardOwners,
+            Option<&Ship>,
+            &mut CardActions,
+        ),
+        With<Hand>,
     >,
-    trackers: Query<&PlayerTurnTracker, With<Player<PLAYER>>>,
+    trackers: Query<(&PlayerTurnTracker, &CardOwners), With<PlayerOwned>>,
 ) {
     if let Some(&GameEvents::PlayCard(slot)) = event.get_unprocessed() {
+        let &TurnStates::PlayerTurn(player) = turn.get() else {
+            return;
+        };
+        let owner = CardOwners::Player(player);
         let slot = slot as usize;
-        for (e, i, s, mut a) in hand.iter_mut() {
-            if i.0 == slot {
-                routines.play(PLAYER, e, 0, s.is_none());
-                let trackers = trackers.get_single().unwrap();
+        for (e, i, o, s, mut a) in hand.iter_mut() {
+            if i.0 == slot && *o == owner {
+                routines.play(player, e, 0, s.is_none());
+                let (trackers, _) = trackers.iter().find(|(_, o)| **o == owner).unwrap();
                 if s.is_some() && a.is_action_available(0, trackers) {
                     routines.activate_card(
-                        PLAYER,
+                        player,
                         e,
                         0,
                         a.use_action(0, trackers)
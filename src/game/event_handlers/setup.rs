@@ -1,6 +1,10 @@
+use crate::cards::assets::manifest::SetCatalog;
 use crate::cards::assets::{Deck, LoadedSet};
 use crate::game::events::{GameEvent, GameEvents};
-use crate::game::routines::RoutineManager;
+use crate::game::modes::GameMode;
+use crate::game::rng::GameRng;
+use crate::game::routines::{RoutineManager, Routines};
+use crate::players::{spawn_counters, LocalPlayer, PlayerCount, MAXIMUM_PLAYERS};
 use crate::prelude::*;
 use crate::states::turn::TurnStates;
 
@@ -8,22 +12,59 @@ pub fn setup(
     mut commands: Commands,
     mut event: ResMut<GameEvent>,
     mut turn: ResMut<NextState<TurnStates>>,
-    loaded_set: Res<LoadedSet>,
+    mut loaded_set: ResMut<LoadedSet>,
+    catalog: Res<SetCatalog>,
+    asset_server: Res<AssetServer>,
     decks: Res<Assets<Deck>>,
     mut routines: ResMut<RoutineManager>,
+    mut rng: ResMut<GameRng>,
+    mut player_count: ResMut<PlayerCount>,
+    mut game_mode: ResMut<GameMode>,
+    local_player: Res<LocalPlayer>,
 ) {
     if let Some(GameEvents::Setup {
         seed,
         set,
         players,
         starting_player,
+        mode,
     }) = event.get_unprocessed()
     {
+        let (seed, players, starting_player, mode) = (*seed, *players, *starting_player, *mode);
+        if players as usize > MAXIMUM_PLAYERS
+            || !mode.valid_player_count(players)
+            || starting_player >= players
+        {
+            event.cancel();
+            return;
+        }
+        //the decks of the set are loaded on demand, the event waits for them
+        if loaded_set.id != Some(*set) {
+            match catalog.get(*set) {
+                Some(entry) if loaded_set.load(entry, &asset_server).is_ok() => {}
+                _ => {
+                    error!("unknown or broken set {set}");
+                    event.cancel();
+                }
+            }
+            return;
+        }
+        if !loaded_set.ready {
+            return;
+        }
+        let rules = loaded_set.rules;
+        *rng = GameRng::from_seed(seed);
+        player_count.0 = players;
+        *game_mode = mode;
+        spawn_counters(&mut commands, players, mode, rules.starting_life);
+        commands.insert_resource(TransitionTransforms::with_seats(players, local_player.0));
+        let mut id = 0;
         if let Some(deck) = decks.get(&loaded_set.market_deck) {
             let mut index = 0;
             for (qty, name) in &deck.0 {
                 for _ in 0..*qty {
                     let mut ec = commands.spawn((
+                        CardId(id),
                         CardIndex(index),
                         SpawnCard(name.clone()),
                         SpatialBundle::default(),
@@ -38,6 +79,7 @@ pub fn setup(
                     ));
                     CardOwners::Market.insert(&mut ec);
                     Stacks::MarketDeck.insert(&mut ec);
+                    id += 1;
                     index += 1;
                 }
             }
@@ -46,36 +88,25 @@ pub fn setup(
             let mut index = 0;
             for (qty, name) in &deck.0 {
                 for _ in 0..*qty {
-                    let mut ec = commands.spawn((
-                        CardIndex(index),
-                        SpawnCard(name.clone()),
-                        SpatialBundle::default(),
-                        Name::new(name.clone()),
-                        StartTransition {
-                            owner: CardOwners::Player(0),
-                            stack: Stacks::PlayerDeck,
-                            index: CardIndex(index),
-                            visibility: CardVisibility::Hidden,
-                            length: 0.0,
-                        },
-                    ));
-                    CardOwners::Player(0).insert(&mut ec);
-                    Stacks::PlayerDeck.insert(&mut ec);
-                    let mut ec = commands.spawn((
-                        CardIndex(index),
-                        SpawnCard(name.clone()),
-                        SpatialBundle::default(),
-                        Name::new(name.clone()),
-                        StartTransition {
-                            owner: CardOwners::Player(1),
-                            stack: Stacks::PlayerDeck,
-                            index: CardIndex(index),
-                            visibility: CardVisibility::Hidden,
-                            length: 0.0,
-                        },
-                    ));
-                    CardOwners::Player(1).insert(&mut ec);
-                    Stacks::PlayerDeck.insert(&mut ec);
+                    for player in 0..players {
+                        let mut ec = commands.spawn((
+                            CardId(id),
+                            CardIndex(index),
+                            SpawnCard(name.clone()),
+                            SpatialBundle::default(),
+                            Name::new(name.clone()),
+                            StartTransition {
+                                owner: CardOwners::Player(player),
+                                stack: Stacks::PlayerDeck,
+                                index: CardIndex(index),
+                                visibility: CardVisibility::Hidden,
+                                length: 0.0,
+                            },
+                        ));
+                        CardOwners::Player(player).insert(&mut ec);
+                        Stacks::PlayerDeck.insert(&mut ec);
+                        id += 1;
+                    }
                     index += 1;
                 }
             }
@@ -85,6 +116,7 @@ pub fn setup(
             for (qty, name) in &deck.0 {
                 for _ in 0..*qty {
                     let mut ec = commands.spawn((
+                        CardId(id),
                         CardIndex(index),
                         SpawnCard(name.clone()),
                         SpatialBundle::default(),
@@ -99,29 +131,31 @@ pub fn setup(
                     ));
                     CardOwners::Market.insert(&mut ec);
                     Stacks::JokerDeck.insert(&mut ec);
+                    id += 1;
                     index += 1;
                 }
             }
         }
         routines.shuffle(CardOwners::Market, Stacks::MarketDeck, false);
-        routines.shuffle(CardOwners::Player(0), Stacks::PlayerDeck, false);
-        routines.shuffle(CardOwners::Player(1), Stacks::PlayerDeck, false);
-        for i in 0..5 {
+        for player in 0..players {
+            routines.shuffle(CardOwners::Player(player), Stacks::PlayerDeck, false);
+        }
+        for i in 0..rules.market_size {
             routines.reload_market(i);
         }
-        for i in 0..5 {
-            if i >= 3 {
-                if *starting_player == 0 {
-                    routines.draw(1, false);
-                } else {
-                    routines.draw(0, false);
+        //the starting player draws a smaller first hand
+        for i in 0..rules.hand_size {
+            for player in 0..players {
+                if i < rules.first_hand_size || player != starting_player {
+                    routines.draw(player, false);
                 }
-            } else {
-                routines.draw(0, false);
-                routines.draw(1, false);
             }
         }
-        turn.set(TurnStates::PlayerTurn(*starting_player));
+        //the first hands are not drawn during a turn
+        for player in 0..players {
+            routines.0.push_back(Routines::TurnFinished { player });
+        }
+        turn.set(TurnStates::PlayerTurn(starting_player));
         event.set_processed();
     }
 }
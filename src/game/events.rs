@@ -1,9 +1,10 @@
-use crate::game::event_handlers::event_handler_dispatcher;
-use crate::game::GameStates;
+use crate::engine::replay::ReplayInput;
+use crate::game::event_handlers::{event_handler_dispatcher, EngineBoard};
+use crate::game::modes::GameMode;
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Reflect, Serialize, Deserialize, Copy, Clone, Debug)]
+#[derive(Reflect, Serialize, Deserialize, Copy, Clone, Debug, Eq, PartialEq)]
 pub enum CardActions {
     Primary,
     Ally,
@@ -11,7 +12,7 @@ pub enum CardActions {
     Indexed(u8),
 }
 
-#[derive(Reflect, Serialize, Deserialize, Copy, Clone, Debug)]
+#[derive(Reflect, Serialize, Deserialize, Copy, Clone, Debug, Eq, PartialEq)]
 pub enum BuyFrom {
     Market(u8),
     Joker,
@@ -23,13 +24,14 @@ pub enum BuyFrom {
 //(and the market, of course)
 //this seems easy enough to send over the network
 
-#[derive(Reflect, Serialize, Deserialize, Copy, Clone, Debug, Default)]
+#[derive(Reflect, Serialize, Deserialize, Copy, Clone, Debug, Default, Eq, PartialEq)]
 pub enum GameEvents {
     Setup {
         seed: u64,
         set: u8,
         players: u8,
         starting_player: u8,
+        mode: GameMode,
     },
     PlayCard(u8),
     ActivateCard {
@@ -44,10 +46,46 @@ pub enum GameEvents {
         base_index: Option<u32>,
     },
     PassTurn,
+    Concede(u8), //sent by a player for themselves at any time, not only on their turn
     #[default]
     Debug, //special event that is here to freeze the game (since it can't be cleared by the main event manager)
 }
 
+//parameters of the next game, turned into a setup event when entering AppStates::Playing
+#[derive(Resource, Reflect, Copy, Clone, Debug)]
+#[reflect(Resource)]
+pub struct GameSetup {
+    pub seed: u64,
+    pub set: u8,
+    pub players: u8,
+    pub starting_player: u8,
+    pub mode: GameMode,
+}
+
+impl Default for GameSetup {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            set: 0,
+            players: 2,
+            starting_player: 0,
+            mode: GameMode::FreeForAll,
+        }
+    }
+}
+
+impl GameSetup {
+    pub fn event(&self) -> GameEvents {
+        GameEvents::Setup {
+            seed: self.seed,
+            set: self.set,
+            players: self.players,
+            starting_player: self.starting_player,
+            mode: self.mode,
+        }
+    }
+}
+
 #[derive(Resource, Reflect, Debug, Default)]
 #[reflect(Resource)]
 pub struct GameEvent {
@@ -91,6 +129,50 @@ impl GameEvent {
         self.get_unprocessed()
     }
 
+    //log access offset by 1 (0: no event yet)
+    pub fn head(&self) -> usize {
+        self.head
+    }
+
+    pub fn len(&self) -> usize {
+        self.log.len()
+    }
+
+    //entries of the log their handler already went through, the others may still be refused
+    pub fn processed(&self) -> usize {
+        if self.processed {
+            self.head
+        } else {
+            self.head.saturating_sub(1)
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.log.is_empty()
+    }
+
+    pub fn log(&self) -> &[GameEvents] {
+        &self.log
+    }
+
+    //log of a loaded game, every event is considered processed
+    pub fn from_log(log: Vec<GameEvents>) -> Self {
+        Self {
+            processed: true,
+            flags: 0,
+            head: log.len(),
+            log,
+        }
+    }
+
+    //forget every event from the given log position onward (used by undo)
+    pub fn rewind(&mut self, position: usize) {
+        self.log.truncate(position);
+        self.head = position;
+        self.processed = true;
+        self.flags = 0;
+    }
+
     pub fn cancel(&mut self) -> Option<GameEvents> {
         if self.head == self.log.len() {
             self.flags = 0;
@@ -105,15 +187,22 @@ impl GameEvent {
     }
 }
 
+//answers to the selections and choices opened by the events, in order, with the player who gave
+//them. along with the event log, this is everything needed to play a game again (see
+//engine::replay::ReplayInput). selections closed without any valid card are not answers
+#[derive(Resource, Default, Debug)]
+pub struct Answers(pub Vec<(u8, ReplayInput)>);
+
 pub struct GameEventsPlugin;
 
 impl Plugin for GameEventsPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<GameEvent>()
             .init_resource::<GameEvent>()
-            .add_systems(
-                Update,
-                event_handler_dispatcher.run_if(in_state(GameStates::MainLoop)),
-            );
+            .register_type::<GameSetup>()
+            .init_resource::<GameSetup>()
+            .init_resource::<Answers>()
+            .init_resource::<EngineBoard>()
+            .add_systems(Update, event_handler_dispatcher);
     }
 }
@@ -0,0 +1,396 @@
+use crate::cards::actions::uniques::StealthNeedleCopy;
+use crate::cards::actions::Action;
+use crate::cards::actions::{CardActions, KindMask};
+use crate::cards::components::kinds::BaseLife;
+use crate::cards::transition::{CardStateSnapshot, StartTransition};
+use crate::game::events::{Answers, GameEvent, GameEvents};
+use crate::game::rng::GameRng;
+use crate::game::routines::move_to_stack::NextBuyOnDeckFlag;
+use crate::game::routines::RoutineManager;
+use crate::game::triggers::{Trigger, TriggerDuration, TriggerListener, Triggers};
+use crate::game::GameStates;
+use crate::network::NetworkSession;
+use crate::players::{
+    LocalPlayer, PlayerAttack, PlayerEconomy, PlayerLife, PlayerOwned, PlayerTurnTracker,
+};
+use crate::prelude::*;
+use crate::states::turn::TurnStates;
+use bevy::ecs::system::CommandQueue;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+//state of a card that can change during a turn
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct CardSnapshot {
+    pub id: CardId,
+    pub state: CardStateSnapshot,
+    pub actions: CardActions,
+    pub factions: CardFactions,
+    pub copy: Option<StealthNeedleCopy>,
+    pub base_life: Option<BaseLife>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct PlayerSnapshot {
+    pub player: u8,
+    pub life: i32,
+    pub attack: i32,
+    pub economy: i32,
+    pub tracker: PlayerTurnTracker,
+}
+
+//TriggerListener with the card designated by its CardId, entities are not kept between games
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct TriggerSnapshot {
+    pub card: CardId,
+    pub owner: u8,
+    pub trigger: Trigger,
+    pub duration: TriggerDuration,
+    pub action: Action,
+}
+
+//everything the game events and routines can modify, taken while no routine is running
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct BoardSnapshot {
+    pub cards: Vec<CardSnapshot>,
+    pub players: Vec<PlayerSnapshot>,
+    pub next_buy_on_deck: KindMask,
+    pub triggers: Vec<TriggerSnapshot>,
+    pub rng: GameRng,
+}
+
+impl BoardSnapshot {
+    pub fn capture(world: &mut World) -> Self {
+        let mut cards: Vec<CardSnapshot> = world
+            .query::<(
+                &CardId,
+                &CardOwners,
+                &Stacks,
+                &CardIndex,
+                &CardVisibility,
+                &CardActions,
+                &CardFactions,
+                Option<&StealthNeedleCopy>,
+                Option<&BaseLife>,
+            )>()
+            .iter(world)
+            .map(
+                |(&id, &owner, &stack, &index, &visibility, actions, factions, copy, base_life)| {
+                    CardSnapshot {
+                        id,
+                        state: CardStateSnapshot {
+                            owner,
+                            stack,
+                            index,
+                            visibility,
+                        },
+                        actions: actions.clone(),
+                        factions: factions.clone(),
+                        copy: copy.cloned(),
+                        base_life: base_life.copied(),
+                    }
+                },
+            )
+            .collect();
+        cards.sort_by_key(|c| c.id);
+        let mut players: Vec<PlayerSnapshot> = world
+            .query_filtered::<(
+                &CardOwners,
+                &PlayerLife,
+                &PlayerAttack,
+                &PlayerEconomy,
+                &PlayerTurnTracker,
+            ), With<PlayerOwned>>()
+            .iter(world)
+            .filter_map(|(owner, life, attack, economy, tracker)| match owner {
+                &CardOwners::Player(player) => Some(PlayerSnapshot {
+                    player,
+                    life: life.0,
+                    attack: attack.0,
+                    economy: economy.0,
+                    tracker: tracker.clone(),
+                }),
+                _ => None,
+            })
+            .collect();
+        players.sort_by_key(|p| p.player);
+        Self {
+            cards,
+            players,
+            next_buy_on_deck: world.resource::<NextBuyOnDeckFlag>().0,
+            triggers: world
+                .resource::<Triggers>()
+                .0
+                .clone()
+                .into_iter()
+                .filter_map(|l| {
+                    Some(TriggerSnapshot {
+                        card: *world.get::<CardId>(l.card)?,
+                        owner: l.owner,
+                        trigger: l.trigger,
+                        duration: l.duration,
+                        action: l.action,
+                    })
+                })
+                .collect(),
+            rng: *world.resource::<GameRng>(),
+        }
+    }
+
+    //cards that are not where the snapshot expects them are moved back with a transition
+    //(marked as Rewound so the listeners don't count the move as a game action)
+    pub fn restore(&self, world: &mut World) {
+        let entities: HashMap<CardId, Entity> = world
+            .query::<(Entity, &CardId)>()
+            .iter(world)
+            .map(|(e, &id)| (id, e))
+            .collect();
+        for card in &self.cards {
+            let Some(&entity) = entities.get(&card.id) else {
+                continue;
+            };
+            let mut current = world.query::<(&CardOwners, &Stacks, &CardIndex, &CardVisibility)>();
+            let moved =
+                current
+                    .get(world, entity)
+                    .map_or(true, |(&owner, &stack, &index, &visibility)| {
+                        card.state
+                            != CardStateSnapshot {
+                                owner,
+                                stack,
+                                index,
+                                visibility,
+                            }
+                    });
+            let factions = world
+                .get::<CardFactions>(entity)
+                .cloned()
+                .unwrap_or_default();
+            let mut ec = world.entity_mut(entity);
+            if moved {
+                ec.insert((
+                    StartTransition {
+                        owner: card.state.owner,
+                        stack: card.state.stack,
+                        index: card.state.index,
+                        visibility: card.state.visibility,
+                        length: 0.5,
+                    },
+                    Rewound,
+                ));
+            }
+            ec.insert((card.actions.clone(), card.factions.clone()));
+            if let Some(life) = card.base_life {
+                ec.insert(life);
+            }
+            match &card.copy {
+                Some(copy) => ec.insert(copy.clone()),
+                None => ec.remove::<StealthNeedleCopy>(),
+            };
+            //faction markers are only inserted through commands
+            let mut queue = CommandQueue::default();
+            let mut commands = Commands::new(&mut queue, world);
+            let mut ec = commands.entity(entity);
+            for faction in factions.0.difference(&card.factions.0) {
+                faction.remove(&mut ec);
+            }
+            for faction in card.factions.0.difference(&factions.0) {
+                faction.insert(&mut ec);
+            }
+            queue.apply(world);
+        }
+        for (owner, mut life, mut attack, mut economy, mut tracker) in world
+            .query_filtered::<(
+                &CardOwners,
+                &mut PlayerLife,
+                &mut PlayerAttack,
+                &mut PlayerEconomy,
+                &mut PlayerTurnTracker,
+            ), With<PlayerOwned>>()
+            .iter_mut(world)
+        {
+            if let Some(player) = self
+                .players
+                .iter()
+                .find(|p| CardOwners::Player(p.player) == *owner)
+            {
+                life.0 = player.life;
+                attack.0 = player.attack;
+                economy.0 = player.economy;
+                *tracker = player.tracker.clone();
+            }
+        }
+        world.resource_mut::<NextBuyOnDeckFlag>().0 = self.next_buy_on_deck;
+        world.resource_mut::<Triggers>().0 = self
+            .triggers
+            .iter()
+            .filter_map(|t| {
+                Some(TriggerListener {
+                    card: *entities.get(&t.card)?,
+                    owner: t.owner,
+                    trigger: t.trigger,
+                    duration: t.duration,
+                    action: t.action,
+                })
+            })
+            .collect();
+        world.insert_resource(self.rng);
+    }
+}
+
+//inserted on cards moved by an undo, removed by the card_transitioning listener
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct Rewound;
+
+//events that can be undone: they only act on the board of the current turn
+pub fn is_undoable(event: &GameEvents) -> bool {
+    matches!(
+        event,
+        GameEvents::PlayCard(_)
+            | GameEvents::BuyCard(_)
+            | GameEvents::Attack { .. }
+            | GameEvents::ActivateCard { .. }
+    )
+}
+
+//undo stack of the current turn: the board before each undoable event, with the position of
+//the event in the log and the number of answers given before it. cleared at turn boundaries and
+//as soon as a card is drawn from a player deck, since undoing would let the player act again
+//knowing that card
+#[derive(Resource, Default)]
+pub struct History {
+    undo: Vec<(usize, GameEvents, BoardSnapshot, usize)>,
+    redo: Vec<GameEvents>,
+    redoing: bool,
+}
+
+impl History {
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    //called by the dispatcher before an undoable event at the given log position is handled
+    pub fn record(&mut self, world: &mut World, position: usize, event: GameEvents) {
+        //an event rejected by its handler was removed from the log, along with its snapshot
+        self.undo.retain(|(p, ..)| *p < position);
+        if self.redoing {
+            self.redoing = false;
+        } else {
+            self.redo.clear();
+        }
+        self.undo.push((
+            position,
+            event,
+            BoardSnapshot::capture(world),
+            world.resource::<Answers>().0.len(),
+        ));
+    }
+}
+
+//an event handler might run for several frames, only the first one takes a snapshot
+pub fn record_event(world: &mut World, event: GameEvents) {
+    let position = world.resource::<GameEvent>().head() - 1;
+    world.resource_scope(|world, mut history: Mut<History>| {
+        if history
+            .undo
+            .last()
+            .is_none_or(|(p, e, ..)| *p != position || *e != event)
+        {
+            history.record(world, position, event);
+        }
+    });
+}
+
+//the board must be at rest: no routine, no pending event and no card moving
+pub fn board_at_rest(world: &mut World) -> bool {
+    world.resource::<RoutineManager>().is_empty()
+        && world.resource::<GameEvent>().get_unprocessed().is_none()
+        && world
+            .query_filtered::<(), Or<(With<CardTransition>, With<StartTransition>)>>()
+            .iter(world)
+            .next()
+            .is_none()
+}
+
+pub fn undo(world: &mut World) -> bool {
+    if !board_at_rest(world) {
+        return false;
+    }
+    let log_length = world.resource::<GameEvent>().len();
+    let mut history = world.resource_mut::<History>();
+    //drop the snapshots of events that were rejected
+    while history.undo.last().is_some_and(|(p, ..)| *p >= log_length) {
+        history.undo.pop();
+    }
+    let Some((position, event, snapshot, answers)) = history.undo.pop() else {
+        return false;
+    };
+    history.redo.push(event);
+    snapshot.restore(world);
+    world.resource_mut::<GameEvent>().rewind(position);
+    world.resource_mut::<Answers>().0.truncate(answers);
+    true
+}
+
+//the undone event is sent again, its selections and choices are asked again
+pub fn redo(world: &mut World) -> bool {
+    if !board_at_rest(world) {
+        return false;
+    }
+    let mut history = world.resource_mut::<History>();
+    let Some(event) = history.redo.pop() else {
+        return false;
+    };
+    history.redoing = true;
+    world.resource_mut::<GameEvent>().push(event);
+    true
+}
+
+//ctrl+z / ctrl+y (or ctrl+shift+z), only for the player whose turn it is, not in network games
+pub fn undo_redo_keys(world: &mut World) {
+    if world.contains_resource::<NetworkSession>() {
+        return;
+    }
+    let keys = world.resource::<ButtonInput<KeyCode>>();
+    let control = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let z = keys.just_pressed(KeyCode::KeyZ);
+    let y = keys.just_pressed(KeyCode::KeyY);
+    if !control || !(z || y) {
+        return;
+    }
+    let local = world.resource::<LocalPlayer>().0;
+    if *world.resource::<State<TurnStates>>().get() != TurnStates::PlayerTurn(local) {
+        return;
+    }
+    if z && !shift {
+        undo(world);
+    } else {
+        redo(world);
+    }
+}
+
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Rewound>()
+            .init_resource::<History>()
+            .add_systems(
+                Update,
+                undo_redo_keys
+                    .run_if(in_state(GameStates::MainLoop))
+                    .run_if(in_state(AppStates::Playing)),
+            );
+    }
+}
@@ -1,52 +1,85 @@
+use crate::cards::actions::uniques::StealthNeedleCopy;
 use crate::cards::actions::CardActions;
 use crate::cards::transition::TransitionSystemSets;
-use crate::players::{Player, PlayerTurnTracker};
+use crate::game::history::{History, Rewound};
+use crate::game::routines::RoutineManager;
+use crate::game::triggers::{leaves_play, Trigger, Triggers};
+use crate::players::PlayerTurnTracker;
 use crate::prelude::*;
+use crate::states::turn::TurnStates;
 
 pub fn card_transitioning(
+    mut commands: Commands,
     mut cards: Query<
-        (&mut CardActions, &mut CardTransition, &CardFactions, &Name),
+        (
+            Entity,
+            &mut CardActions,
+            &mut CardTransition,
+            &CardFactions,
+            Option<&StealthNeedleCopy>,
+            Option<&Rewound>,
+        ),
         Added<CardTransition>,
     >,
-    mut player_0_trackers: Query<&mut PlayerTurnTracker, (With<Player<0>>, Without<Player<1>>)>,
-    mut player_1_trackers: Query<&mut PlayerTurnTracker, (With<Player<1>>, Without<Player<0>>)>,
+    mut trackers: Query<(&mut PlayerTurnTracker, &CardOwners)>,
+    mut triggers: ResMut<Triggers>,
+    mut routines: ResMut<RoutineManager>,
+    turn: Res<State<TurnStates>>,
+    mut history: ResMut<History>,
 ) {
-    for (mut actions, mut transition, factions, name) in cards.iter_mut() {
+    for (card, mut actions, transition, factions, copy, rewound) in cards.iter_mut() {
+        if rewound.is_some() {
+            //moved back by an undo, the trackers and triggers were restored with the board
+            commands.entity(card).remove::<Rewound>();
+            continue;
+        }
         if transition.next != transition.previous {
-            if transition.next.stack == Stacks::DiscardPile
-                || transition.next.stack == Stacks::Scrapyard
+            //a card drawn from a deck reveals information, the turn can't be undone past this
+            //point. the explorers are all alike, and the card refilling the market row is only
+            //revealed by an event whose snapshot was taken before it: undoing that event puts
+            //the card back face down in the market deck
+            if transition.previous.stack == Stacks::PlayerDeck
+                && transition.next.stack != Stacks::PlayerDeck
             {
+                history.clear();
+            }
+            //scrapped explorers go back to the joker deck, to be bought again unused
+            if matches!(
+                transition.next.stack,
+                Stacks::DiscardPile | Stacks::Scrapyard | Stacks::JokerDeck
+            ) {
                 actions.reset();
             }
-            match transition.previous.owner {
-                CardOwners::Market => {}
-                CardOwners::Player(p) => {
-                    if let Ok(mut trackers) = if p == 0 {
-                        player_0_trackers.get_single_mut()
-                    } else {
-                        player_1_trackers.get_single_mut()
-                    } {
-                        trackers.card_snapshots(&transition.previous, &transition.next, factions);
-                    }
+            //both the previous and next owners (if different) see the card moving
+            for (mut trackers, owner) in trackers.iter_mut() {
+                if *owner == transition.previous.owner || *owner == transition.next.owner {
+                    trackers.card_snapshots(&transition.previous, &transition.next, factions);
+                }
+            }
+            if let TurnStates::PlayerTurn(_) = turn.get() {
+                if let Some((trigger, player)) =
+                    Trigger::from_move(&transition.previous, &transition.next)
+                {
+                    triggers.fire(trigger, player, &mut routines);
                 }
             }
-            if transition.next.owner != transition.previous.owner {
-                match transition.next.owner {
-                    CardOwners::Market => {}
-                    CardOwners::Player(p) => {
-                        if let Ok(mut trackers) = if p == 0 {
-                            player_0_trackers.get_single_mut()
-                        } else {
-                            player_1_trackers.get_single_mut()
-                        } {
-                            trackers.card_snapshots(
-                                &transition.previous,
-                                &transition.next,
-                                factions,
-                            );
-                        }
-                    }
+            if leaves_play(&transition.previous, &transition.next) {
+                triggers.card_left_play(card);
+            }
+            //a Stealth Needle leaving play loses what it copied (after the trackers saw it leave
+            //with the copied factions)
+            if let (Some(copy), Stacks::DiscardPile | Stacks::Scrapyard) =
+                (copy, transition.next.stack)
+            {
+                let mut entity = commands.entity(card);
+                for faction in factions.0.difference(&copy.factions.0) {
+                    faction.remove(&mut entity);
                 }
+                let mut original = copy.actions.clone();
+                original.reset();
+                entity
+                    .insert((original, copy.factions.clone()))
+                    .remove::<StealthNeedleCopy>();
             }
         }
     }
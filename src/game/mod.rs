@@ -1,16 +1,30 @@
 pub mod event_handlers;
 pub mod events;
+pub mod history;
 pub mod listeners;
+pub mod modes;
 mod player_interaction;
+pub mod replay;
+pub mod rng;
 pub mod routines;
+pub mod save;
+pub mod triggers;
+pub mod victory;
 
 use crate::game::routines::move_to_stack::NextBuyOnDeckFlag;
 use crate::prelude::*;
 use bevy::prelude::Plugin;
 use events::GameEventsPlugin;
+use history::HistoryPlugin;
 use listeners::ListenersPlugin;
+use modes::GameMode;
 use player_interaction::PlayerInteractionPlugin;
+use replay::ReplayPlugin;
+use rng::GameRng;
 use routines::RoutinesPlugin;
+use save::SavePlugin;
+use triggers::TriggersPlugin;
+use victory::VictoryPlugin;
 
 #[derive(States, Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Reflect)]
 #[reflect(Default)]
@@ -27,6 +41,10 @@ impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<NextBuyOnDeckFlag>()
             .init_resource::<NextBuyOnDeckFlag>()
+            .register_type::<GameRng>()
+            .init_resource::<GameRng>()
+            .register_type::<GameMode>()
+            .init_resource::<GameMode>()
             .register_type::<GameStates>()
             .register_type::<NextState<GameStates>>()
             .register_type::<State<GameStates>>()
@@ -36,6 +54,11 @@ impl Plugin for GamePlugin {
                 ListenersPlugin,
                 GameEventsPlugin,
                 PlayerInteractionPlugin,
+                VictoryPlugin,
+                TriggersPlugin,
+                HistoryPlugin,
+                SavePlugin,
+                ReplayPlugin,
             ));
     }
 }
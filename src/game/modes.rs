@@ -0,0 +1,155 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+//multiplayer formats, chosen by the setup event. every rule that depends on the format
+//(targeting, turn order, life totals, victory) goes through here, so the bevy app and the
+//headless engine share the same answers
+#[derive(
+    Resource, Reflect, Serialize, Deserialize, Copy, Clone, Debug, Default, Eq, PartialEq, Hash,
+)]
+#[reflect(Resource)]
+pub enum GameMode {
+    #[default]
+    FreeForAll, //attack anyone, last player standing wins
+    Hunter, //only the player on your left can be targeted, the first player to destroy their target wins
+    TeamEmperor, //two teams facing each other, sharing a life total per team, turns alternate between teams
+    Raid,        //player 0 is the boss, with more life, the other players team up against them
+}
+
+//the player on your left is the one playing after you
+pub fn left(player: u8, players: u8) -> u8 {
+    (player + 1) % players
+}
+
+pub fn right(player: u8, players: u8) -> u8 {
+    (player + players - 1) % players
+}
+
+impl GameMode {
+    pub fn valid_player_count(&self, players: u8) -> bool {
+        match self {
+            GameMode::FreeForAll | GameMode::Raid => players >= 2,
+            GameMode::Hunter => players >= 3,
+            GameMode::TeamEmperor => players >= 4 && players.is_multiple_of(2),
+        }
+    }
+
+    //teams are numbered from 0, in free-for-all and hunter every player is their own team
+    pub fn team(&self, player: u8, players: u8) -> u8 {
+        match self {
+            GameMode::FreeForAll | GameMode::Hunter => player,
+            //first half of the table against the second half (one row of seats each)
+            GameMode::TeamEmperor => player * 2 / players,
+            GameMode::Raid => (player != 0) as u8,
+        }
+    }
+
+    //players whose life counter moves with the life counter of this player (including themselves)
+    pub fn life_sharers(&self, player: u8, players: u8) -> Vec<u8> {
+        match self {
+            GameMode::TeamEmperor => (0..players)
+                .filter(|&p| self.team(p, players) == self.team(player, players))
+                .collect(),
+            _ => vec![player],
+        }
+    }
+
+    //base: life of a player in free-for-all, given by the rules of the set
+    pub fn starting_life(&self, player: u8, players: u8, base: i32) -> i32 {
+        match self {
+            GameMode::FreeForAll | GameMode::Hunter => base,
+            GameMode::TeamEmperor => base / 2 * (players as i32 / 2 + 1),
+            GameMode::Raid if player == 0 => base * (players as i32 - 1),
+            GameMode::Raid => base,
+        }
+    }
+
+    //used by attacks, EnemyDiscard and DestroyBase alike
+    pub fn can_target(&self, by: u8, target: u8, players: u8) -> bool {
+        if by == target || target >= players {
+            return false;
+        }
+        match self {
+            GameMode::FreeForAll => true,
+            GameMode::Hunter => target == left(by, players),
+            GameMode::TeamEmperor | GameMode::Raid => {
+                self.team(by, players) != self.team(target, players)
+            }
+        }
+    }
+
+    //valid targets still in the game, in table order starting from the left of the player
+    pub fn targets(&self, by: u8, players: u8, alive: &[bool]) -> Vec<u8> {
+        (1..players)
+            .map(|offset| (by + offset) % players)
+            .filter(|&p| alive[p as usize] && self.can_target(by, p, players))
+            .collect()
+    }
+
+    pub fn turn_order(&self, players: u8) -> Vec<u8> {
+        match self {
+            //0, half, 1, half + 1, ...: each team plays in turn
+            GameMode::TeamEmperor => {
+                let half = players / 2;
+                (0..half).flat_map(|p| [p, p + half]).collect()
+            }
+            _ => (0..players).collect(),
+        }
+    }
+
+    //next player still in the game, following the turn order of the mode
+    pub fn next_player(&self, player: u8, players: u8, alive: &[bool]) -> u8 {
+        let order = self.turn_order(players);
+        let position = order.iter().position(|&p| p == player).unwrap_or(0);
+        (1..=order.len())
+            .map(|offset| order[(position + offset) % order.len()])
+            .find(|&p| alive[p as usize])
+            .unwrap_or(player)
+    }
+
+    //returns the winner once the game is decided. for team modes, the winner is the first
+    //player of the winning team still in the game
+    pub fn winner(&self, players: u8, alive: &[bool]) -> Option<u8> {
+        match self {
+            //the hunter of the first destroyed player wins, whoever else is still standing
+            GameMode::Hunter => (0..players)
+                .find(|&p| !alive[p as usize])
+                .map(|p| right(p, players)),
+            _ => {
+                let mut teams = (0..players)
+                    .filter(|&p| alive[p as usize])
+                    .map(|p| (self.team(p, players), p));
+                let (team, winner) = teams.next()?;
+                if teams.all(|(t, _)| t == team) {
+                    Some(winner)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    //whether the player won the game won by winner: in team modes, the whole team of the
+    //winner wins, even the players who were destroyed on the way
+    pub fn won(&self, player: u8, winner: u8, players: u8) -> bool {
+        self.team(player, players) == self.team(winner, players)
+    }
+
+    //every seat winning with the given winner
+    pub fn winners(&self, winner: u8, players: u8) -> Vec<u8> {
+        (0..players)
+            .filter(|&p| self.won(p, winner, players))
+            .collect()
+    }
+}
+
+impl std::fmt::Display for GameMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameMode::FreeForAll => f.write_str("Free-for-all"),
+            GameMode::Hunter => f.write_str("Hunter"),
+            GameMode::TeamEmperor => f.write_str("Team Emperor"),
+            GameMode::Raid => f.write_str("Raid"),
+        }
+    }
+}
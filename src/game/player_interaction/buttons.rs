@@ -1,9 +1,11 @@
 use crate::game::events::{GameEvent, GameEvents};
+use crate::game::modes::GameMode;
 use crate::game::routines::card_action::{Selected, Selection};
-use crate::players::MAXIMUM_PLAYERS;
+use crate::players::{LocalPlayer, PlayerCount, PlayerLife};
 use crate::prelude::*;
 use crate::states::turn::TurnStates;
-use crate::ui::player_counters::{AttackButton, PassTurnButton};
+use crate::ui::player_counters::{AttackButton, ConcedeButton, PassTurnButton, TargetPlayerButton};
+use bevy_rapier3d::prelude::DebugRenderContext;
 
 pub fn pass_turn_button(
     mouse_buttons: Res<ButtonInput<MouseButton>>,
@@ -19,30 +21,72 @@ pub fn pass_turn_button(
     }
 }
 
+pub fn concede_button(
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    button: Query<&ConcedeButton>,
+    mut events: ResMut<GameEvent>,
+    local_player: Res<LocalPlayer>,
+) {
+    let Ok(button) = button.get_single() else {
+        return;
+    };
+    if mouse_buttons.just_released(MouseButton::Left) && button.hovered {
+        events.push(GameEvents::Concede(local_player.0));
+    }
+}
+
+pub fn target_player_button(
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    targets: Query<&TargetPlayerButton>,
+    mut attack: Query<&mut AttackButton>,
+) {
+    if mouse_buttons.just_released(MouseButton::Left) {
+        if let Some(target) = targets.iter().find(|t| t.hovered) {
+            attack.get_single_mut().unwrap().player = Some(target.player);
+        }
+    }
+}
+
 pub fn attack_button(
     mouse_buttons: Res<ButtonInput<MouseButton>>,
     button: Query<&AttackButton>,
     mut events: ResMut<GameEvent>,
     turn: Res<State<TurnStates>>,
+    player_count: Res<PlayerCount>,
+    mode: Res<GameMode>,
+    lives: Query<(&PlayerLife, &CardOwners)>,
 ) {
     if let &TurnStates::PlayerTurn(player) = turn.get() {
-        if mouse_buttons.just_released(MouseButton::Left) {
-            if button.get_single().unwrap().hovered {
-                events.push(GameEvents::Attack {
-                    player: (player + 1) % MAXIMUM_PLAYERS as u8,
-                    as_much_as_possible: false,
-                    base_index: None,
-                });
-            }
+        let button = button.get_single().unwrap();
+        let as_much_as_possible = if mouse_buttons.just_released(MouseButton::Left) {
+            false
         } else if mouse_buttons.just_released(MouseButton::Right) {
-            if button.get_single().unwrap().hovered {
-                events.push(GameEvents::Attack {
-                    player: (player + 1) % MAXIMUM_PLAYERS as u8,
-                    as_much_as_possible: true,
-                    base_index: None,
-                });
+            true
+        } else {
+            return;
+        };
+        if !button.hovered {
+            return;
+        }
+        let mut alive = vec![false; player_count.0 as usize];
+        for (life, owner) in lives.iter() {
+            if let CardOwners::Player(p) = *owner {
+                alive[p as usize] = life.0 > 0;
             }
         }
+        //the picked target if it is still valid, otherwise the first one around the table
+        let targets = mode.targets(player, player_count.0, &alive);
+        let target = button
+            .player
+            .filter(|p| targets.contains(p))
+            .or(targets.first().copied());
+        if let Some(target) = target {
+            events.push(GameEvents::Attack {
+                player: target,
+                as_much_as_possible,
+                base_index: None,
+            });
+        }
     }
 }
 
@@ -50,7 +94,12 @@ pub fn selection_validation_button(
     mut button: Query<(&Interaction, &mut BackgroundColor, &mut BorderColor), Changed<Interaction>>,
     mut selection: ResMut<Selection>,
     selected: Query<Entity, With<Selected>>,
+    local_player: Res<LocalPlayer>,
+    debug: Res<DebugRenderContext>,
 ) {
+    if selection.player != local_player.0 && !debug.enabled {
+        return;
+    }
     if !selection.finished {
         let len = selected.iter().count();
         if let Ok((interaction, mut background, mut border)) = button.get_single_mut() {
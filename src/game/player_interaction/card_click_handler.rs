@@ -2,9 +2,9 @@ use crate::cards::assets::Card;
 use crate::cards::components::Focused;
 use crate::cards::transition::{ResetFocus, StartFocus};
 use crate::game::events::{BuyFrom, CardActions, GameEvent, GameEvents};
-use crate::game::routines::card_action::{Selectable, Selected};
+use crate::game::routines::card_action::{Selectable, Selected, Selection};
 use crate::game::routines::RoutineManager;
-use crate::players::{LocalPlayer, Player, PlayerTurnTracker};
+use crate::players::{LocalPlayer, PlayerTurnTracker};
 use crate::prelude::*;
 use crate::states::turn::TurnStates;
 use crate::utils::ray_caster::RayCastHit;
@@ -116,15 +116,13 @@ pub fn card_hover(
         Or<(With<UsedCards>, With<Bases>)>,
     >,
     turn: Res<State<TurnStates>>,
-    player_0_tracker: Query<&PlayerTurnTracker, With<Player<0>>>,
-    player_1_tracker: Query<&PlayerTurnTracker, With<Player<1>>>,
+    trackers: Query<(&PlayerTurnTracker, &CardOwners)>,
 ) {
     if let TurnStates::PlayerTurn(playing) = turn.get() {
-        if let Ok(tracker) = if *playing == 0 {
-            player_0_tracker.get_single()
-        } else {
-            player_1_tracker.get_single()
-        } {
+        if let Some((tracker, _)) = trackers
+            .iter()
+            .find(|(_, o)| **o == CardOwners::Player(*playing))
+        {
             if let Ok(mut caster) = caster.get_single_mut() {
                 if let Some(RayCastHit {
                     entity,
@@ -220,7 +218,14 @@ pub fn selection_click(
     mut commands: Commands,
     selectable: Query<Entity, With<Selectable>>,
     selected: Query<Entity, With<Selected>>,
+    selection: Res<Selection>,
+    local_player: Res<LocalPlayer>,
+    debug: Res<DebugRenderContext>,
 ) {
+    //the prompt belongs to a single player, which may not be the one playing the turn
+    if selection.player != local_player.0 && !debug.enabled {
+        return;
+    }
     if buttons.just_pressed(MouseButton::Left) {
         if let Some(RayCastHit { entity, .. }) = caster.get_single().ok().and_then(|r| r.hit) {
             if selectable.contains(entity) {
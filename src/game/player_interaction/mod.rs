@@ -8,8 +8,10 @@ use crate::game::player_interaction::card_click_handler::{
     focus_card_handler, selection_click, selection_feedback,
 };
 use crate::game::GameStates;
+use crate::network::{local_input_allowed, network_connected};
+use crate::ui::hot_seat::no_handoff;
 use crate::utils::ray_caster::update_ray_cast;
-use buttons::{attack_button, pass_turn_button};
+use buttons::{attack_button, concede_button, pass_turn_button, target_player_button};
 use card_click_handler::{card_click_handler, card_hover};
 
 pub struct PlayerInteractionPlugin;
@@ -26,15 +28,22 @@ impl Plugin for PlayerInteractionPlugin {
             Update,
             (
                 focus_card_handler,
-                (card_click_handler, pass_turn_button, attack_button)
+                (
+                    (card_click_handler, pass_turn_button, attack_button)
+                        .run_if(local_input_allowed),
+                    target_player_button,
+                )
                     .run_if(in_state(GameStates::MainLoop)),
+                //a player can give up while they are asked for a selection or a choice
+                concede_button.run_if(network_connected),
                 (
                     selection_feedback,
                     selection_click,
                     selection_validation_button,
                 )
                     .run_if(in_state(GameStates::SelectionInput)),
-            ),
+            )
+                .run_if(no_handoff),
         );
     }
 }
@@ -0,0 +1,237 @@
+use crate::cards::transition::PlayBackSpeed;
+use crate::engine::replay::{Replay, ReplayError, ReplayInput};
+use crate::engine::{CardLibrary, GameState};
+use crate::game::events::{GameEvent, GameEvents};
+use crate::game::history::BoardSnapshot;
+use crate::game::routines::RoutineManager;
+use crate::game::victory::reset_board;
+use crate::prelude::*;
+use crate::states::turn::TurnStates;
+use bevy::ecs::system::RunSystemOnce;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+pub const LATEST_REPLAY: &str = "replays/latest.replay.ron";
+
+//replay opened when entering AppStates::Replay (LATEST_REPLAY if none), can be given with --replay
+#[derive(Resource, Default, Debug, Clone)]
+pub struct ReplayFile(pub Option<PathBuf>);
+
+//the app only plays the setup event (to spawn the cards), the dispatcher is then frozen and the
+//board of the engine is shown instead: stepping forward plays the next input, any other move
+//seeks from the closest keyframe
+#[derive(Resource, Default)]
+pub struct ReplayViewer {
+    pub replay: Replay,
+    library: Arc<CardLibrary>,
+    state: Option<(usize, GameState)>, //engine state, after the input it is at
+    pub turn: TurnStates,              //turn of the board shown
+    pub step: usize,                   //input to show the board after
+    shown: Option<usize>,              //board currently restored
+    pub playing: bool,
+    timer: f32,
+}
+
+impl ReplayViewer {
+    pub fn open(mut replay: Replay, library: Arc<CardLibrary>) -> Result<Self, ReplayError> {
+        if !replay.keyframes.is_empty() || !replay.hashes.is_empty() {
+            if let Err(err) = replay.verify(library.clone()) {
+                warn!("the replay does not match the current rules: {err}");
+            }
+        }
+        //keyframes are rebuilt with the current rules, a refused input ends the replay there
+        replay.keyframes = match replay.build_keyframes(library.clone()) {
+            Ok(keyframes) => keyframes,
+            Err(ReplayError::Rule { step, error }) if step > 0 => {
+                warn!("the replay stops early: input {step} was refused: {error}");
+                replay.inputs.truncate(step);
+                replay.hashes.truncate(step);
+                replay.build_keyframes(library.clone())?
+            }
+            Err(err) => return Err(err),
+        };
+        Ok(Self {
+            replay,
+            library,
+            ..Default::default()
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.replay.inputs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.replay.inputs.is_empty()
+    }
+
+    pub fn label(&self, step: usize) -> String {
+        self.replay
+            .inputs
+            .get(step)
+            .map_or(String::new(), |input| input.to_string())
+    }
+
+    //board after the given input, as seen by a spectator (every hand face up)
+    pub fn board(&mut self, step: usize) -> Result<BoardSnapshot, ReplayError> {
+        let keyframes = &self.replay.keyframes;
+        let cached = self.state.take().filter(|&(at, _)| {
+            at <= step && !keyframes.iter().any(|k| k.step > at && k.step <= step)
+        });
+        let state = match cached {
+            Some((at, mut state)) => {
+                for step in at + 1..=step {
+                    state
+                        .input(self.replay.inputs[step].clone())
+                        .map_err(|error| ReplayError::Rule { step, error })?;
+                }
+                state
+            }
+            None => self.replay.seek(self.library.clone(), step)?,
+        };
+        self.turn = state.turn();
+        let board = state.board_snapshot(true);
+        self.state = Some((step, state));
+        Ok(board)
+    }
+
+    pub fn seek(&mut self, step: usize) {
+        self.step = step.min(self.len().saturating_sub(1));
+        self.timer = 0.;
+    }
+
+    pub fn forward(&mut self) {
+        self.seek(self.step + 1);
+    }
+
+    pub fn back(&mut self) {
+        self.seek(self.step.saturating_sub(1));
+    }
+
+    pub fn toggle(&mut self) {
+        //playing from the last board starts again from the beginning
+        if !self.playing && self.step + 1 >= self.len() {
+            self.seek(0);
+        }
+        self.playing = !self.playing;
+        self.timer = 0.;
+    }
+}
+
+pub fn open_replay(world: &mut World) {
+    let path = world
+        .resource::<ReplayFile>()
+        .0
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(LATEST_REPLAY));
+    let opened = Replay::read(&path).and_then(|replay| {
+        //the set is given by the setup event starting the replay
+        let set = match replay.inputs.first() {
+            Some(ReplayInput::Event(GameEvents::Setup { set, .. })) => *set,
+            _ => 0,
+        };
+        let library = CardLibrary::load("assets", &set.to_string())?;
+        ReplayViewer::open(replay, Arc::new(library))
+    });
+    let viewer = match opened {
+        Ok(opened) => opened,
+        Err(err) => {
+            warn!("could not open the replay {}: {err}", path.display());
+            world
+                .resource_mut::<NextState<AppStates>>()
+                .set(AppStates::MainMenu);
+            return;
+        }
+    };
+    //the first input was accepted by the engine, so it is the setup event
+    if let Some(ReplayInput::Event(setup)) = viewer.replay.inputs.first() {
+        world
+            .resource_mut::<GameEvent>()
+            .push(*setup)
+            .push(GameEvents::Debug);
+    }
+    world.insert_resource(viewer);
+}
+
+pub fn close_replay(world: &mut World) {
+    world.insert_resource(ReplayViewer::default());
+    world.run_system_once(reset_board);
+}
+
+//the board is only restored once the previous restoration (or the setup) is done moving
+pub fn step_replay(world: &mut World) {
+    if world.resource::<ReplayViewer>().is_empty()
+        || *world.resource::<State<TurnStates>>().get() == TurnStates::Setup
+        || !world.resource::<RoutineManager>().is_empty()
+        || world
+            .query_filtered::<(), Or<(With<CardTransition>, With<StartTransition>)>>()
+            .iter(world)
+            .next()
+            .is_some()
+    {
+        return;
+    }
+    let delta = world.resource::<Time>().delta_seconds();
+    //transitions are scaled by the playback speed, so is the time spent on each board
+    let interval = 4. * world.resource::<PlayBackSpeed>().0;
+    let mut viewer = world.resource_mut::<ReplayViewer>();
+    if viewer.playing && viewer.shown == Some(viewer.step) {
+        viewer.timer += delta;
+        if viewer.timer >= interval {
+            if viewer.step + 1 < viewer.len() {
+                viewer.forward();
+            } else {
+                viewer.playing = false;
+            }
+        }
+    }
+    if viewer.shown == Some(viewer.step) {
+        return;
+    }
+    let step = viewer.step;
+    viewer.shown = Some(step);
+    match viewer.board(step) {
+        Ok(board) => board.restore(world),
+        Err(err) => {
+            warn!("could not show the board after input {step}: {err}");
+            viewer.playing = false;
+        }
+    }
+}
+
+//left/right: previous/next input, home/end: first/last, space: play/pause
+pub fn replay_keys(keys: Res<ButtonInput<KeyCode>>, mut viewer: ResMut<ReplayViewer>) {
+    if keys.just_pressed(KeyCode::ArrowLeft) {
+        viewer.back();
+    }
+    if keys.just_pressed(KeyCode::ArrowRight) {
+        viewer.forward();
+    }
+    if keys.just_pressed(KeyCode::Home) {
+        viewer.seek(0);
+    }
+    if keys.just_pressed(KeyCode::End) {
+        let last = viewer.len();
+        viewer.seek(last);
+    }
+    if keys.just_pressed(KeyCode::Space) {
+        viewer.toggle();
+    }
+}
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplayFile>()
+            .init_resource::<ReplayViewer>()
+            .add_systems(OnEnter(AppStates::Replay), open_replay)
+            .add_systems(OnExit(AppStates::Replay), close_replay)
+            .add_systems(
+                Update,
+                (replay_keys, step_replay)
+                    .chain()
+                    .run_if(in_state(AppStates::Replay)),
+            );
+    }
+}
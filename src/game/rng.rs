@@ -0,0 +1,37 @@
+use crate::prelude::*;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+//single source of randomness of a game, seeded by the setup event. every shuffle goes through
+//it, so replaying the same events with the same seed gives the same board
+//each shuffle draws from the stream of the game seed numbered by the shuffles so far, so the
+//whole state is two integers that can be saved and restored. ChaCha8 gives the same values on
+//every platform, unlike StdRng
+#[derive(Resource, Reflect, Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[reflect(Resource)]
+pub struct GameRng {
+    pub seed: u64,
+    pub shuffles: u64,
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::from_seed(0)
+    }
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self { seed, shuffles: 0 }
+    }
+
+    //cards must be given in a stable order (by CardId), query iteration order is not reliable
+    pub fn shuffle<T>(&mut self, cards: &mut [T]) {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        rng.set_stream(self.shuffles);
+        self.shuffles += 1;
+        cards.shuffle(&mut rng);
+    }
+}
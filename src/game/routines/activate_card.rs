@@ -1,12 +1,19 @@
+use crate::engine::replay::ReplayInput;
+use crate::game::events::Answers;
 use crate::game::routines::{RoutineManager, Routines};
 use crate::game::GameStates;
+use crate::players::{player_counter_mut, PlayerTurnTracker};
 use crate::prelude::{ActionSet, DespawnRecursiveExt, Entity};
 use crate::ui::choice_ui::{spawn_choices, ChoiceButton, ChoiceButtonNone, ChoiceRoot};
 use bevy::ecs::system::{Command, CommandQueue};
 use bevy::prelude::{Commands, NextState, World};
 
+//resolve one node of an action set: leaves are queued as CardAction, sequences are split into
+//nested ActivateCard routines and choices wait for the choice ui before queueing the picked sets
 pub fn activate_card(world: &mut World) {
     let mut finished = false;
+    let mut nested = Vec::new();
+    let mut source = None;
     if let Some(Routines::ActivateCard {
         card,
         owner,
@@ -15,6 +22,7 @@ pub fn activate_card(world: &mut World) {
         running,
     }) = world.resource::<RoutineManager>().routine()
     {
+        source = Some((owner, card, index));
         if let Ok((root, root_state)) = world.query::<(Entity, &ChoiceRoot)>().get_single(world) {
             if root_state.finished {
                 let mut choices = Vec::new();
@@ -32,50 +40,19 @@ pub fn activate_card(world: &mut World) {
                         }
                     }
                 }
+                choices.sort();
+                world
+                    .resource_mut::<Answers>()
+                    .0
+                    .push((owner, ReplayInput::Choose(choices.clone())));
                 //despawn choice root
                 let mut command_queue = CommandQueue::default();
                 let mut commands = Commands::new(&mut command_queue, world);
                 commands.entity(root).despawn_recursive();
                 command_queue.apply(world);
-                //apply choices as immediate actions
-                if !choices.is_empty() {
-                    match &set {
-                        ActionSet::Optional(action) => {
-                            world
-                                .resource_mut::<RoutineManager>()
-                                .action(owner, card, index, 0, *action);
-                        }
-                        ActionSet::OneAndOptional(_, action) => {
-                            world
-                                .resource_mut::<RoutineManager>()
-                                .action(owner, card, index, 1, *action);
-                        }
-                        ActionSet::AnyOf2(first, second) => {
-                            for index in &choices {
-                                if *index == 0 {
-                                    world
-                                        .resource_mut::<RoutineManager>()
-                                        .action(owner, card, *index, 0, *first);
-                                } else if *index == 1 {
-                                    world
-                                        .resource_mut::<RoutineManager>()
-                                        .action(owner, card, *index, 1, *second);
-                                }
-                            }
-                        }
-                        ActionSet::OneOf2(first, second) => {
-                            if choices[0] == 0 {
-                                world
-                                    .resource_mut::<RoutineManager>()
-                                    .action(owner, card, index, 0, *first);
-                            } else if choices[0] == 1 {
-                                world
-                                    .resource_mut::<RoutineManager>()
-                                    .action(owner, card, index, 1, *second);
-                            }
-                        }
-                        _ => {}
-                    }
+                //the picked sets are resolved next
+                if let Some(picked) = set.resolve_choices(&choices) {
+                    nested = picked;
                 }
                 //remove wait for finish
                 finished = true;
@@ -92,79 +69,18 @@ pub fn activate_card(world: &mut World) {
                         .resource_mut::<RoutineManager>()
                         .action(owner, card, index, 0, *action);
                 }
-                ActionSet::Optional(_) => {
-                    world
-                        .resource_mut::<NextState<GameStates>>()
-                        .set(GameStates::ChoiceInput);
-                    let mut command_queue = CommandQueue::default();
-                    let mut commands = Commands::new(&mut command_queue, world);
-                    spawn_choices(&mut commands, set);
-                    command_queue.apply(world);
-                    finished = false;
+                ActionSet::All(sets) => nested = sets.clone(),
+                ActionSet::If { .. } => {
+                    nested = set
+                        .checked(&player_counter_mut::<PlayerTurnTracker>(world, owner).unwrap());
                 }
-                ActionSet::OneAndOptional(action, _) => {
-                    world
-                        .resource_mut::<RoutineManager>()
-                        .action(owner, card, index, 0, *action);
+                ActionSet::Optional(_) | ActionSet::Choose { .. } => {
                     world
                         .resource_mut::<NextState<GameStates>>()
                         .set(GameStates::ChoiceInput);
                     let mut command_queue = CommandQueue::default();
                     let mut commands = Commands::new(&mut command_queue, world);
-                    spawn_choices(&mut commands, set);
-                    command_queue.apply(world);
-                    finished = false;
-                }
-                ActionSet::Two(first, second) => {
-                    world
-                        .resource_mut::<RoutineManager>()
-                        .action(owner, card, index, 0, *first);
-                    world
-                        .resource_mut::<RoutineManager>()
-                        .action(owner, card, index, 1, *second);
-                }
-                ActionSet::Three(first, second, third) => {
-                    world
-                        .resource_mut::<RoutineManager>()
-                        .action(owner, card, index, 0, *first);
-                    world
-                        .resource_mut::<RoutineManager>()
-                        .action(owner, card, index, 1, *second);
-                    world
-                        .resource_mut::<RoutineManager>()
-                        .action(owner, card, index, 2, *third);
-                }
-                ActionSet::Four(first, second, third, fourth) => {
-                    world
-                        .resource_mut::<RoutineManager>()
-                        .action(owner, card, index, 0, *first);
-                    world
-                        .resource_mut::<RoutineManager>()
-                        .action(owner, card, index, 1, *second);
-                    world
-                        .resource_mut::<RoutineManager>()
-                        .action(owner, card, index, 2, *third);
-                    world
-                        .resource_mut::<RoutineManager>()
-                        .action(owner, card, index, 3, *fourth);
-                }
-                ActionSet::AnyOf2(_, _) => {
-                    world
-                        .resource_mut::<NextState<GameStates>>()
-                        .set(GameStates::ChoiceInput);
-                    let mut command_queue = CommandQueue::default();
-                    let mut commands = Commands::new(&mut command_queue, world);
-                    spawn_choices(&mut commands, set);
-                    command_queue.apply(world);
-                    finished = false;
-                }
-                ActionSet::OneOf2(_, _) => {
-                    world
-                        .resource_mut::<NextState<GameStates>>()
-                        .set(GameStates::ChoiceInput);
-                    let mut command_queue = CommandQueue::default();
-                    let mut commands = Commands::new(&mut command_queue, world);
-                    spawn_choices(&mut commands, set);
+                    spawn_choices(&mut commands, owner, &set);
                     command_queue.apply(world);
                     finished = false;
                 }
@@ -176,7 +92,11 @@ pub fn activate_card(world: &mut World) {
         }
     }
     if finished {
-        world.resource_mut::<RoutineManager>().finish();
+        let mut routines = world.resource_mut::<RoutineManager>();
+        routines.finish();
+        if let Some((owner, card, index)) = source {
+            routines.activate_nested(owner, card, index, nested);
+        }
     } else if let Some(Routines::ActivateCard {
         card,
         owner,
@@ -1,7 +1,11 @@
 use crate::cards::actions::{Action, KindMask};
+use crate::game::modes::GameMode;
 use crate::game::routines::move_to_stack::NextBuyOnDeckFlag;
 use crate::game::routines::{RoutineManager, Routines, SelectionFilter};
-use crate::players::{Player, PlayerAttack, PlayerEconomy, PlayerLife, PlayerTurnTracker};
+use crate::players::{
+    alive_players, change_life, player_counter_mut, PlayerAttack, PlayerCount, PlayerEconomy,
+    PlayerTurnTracker,
+};
 use crate::prelude::*;
 use bevy::utils::HashMap;
 
@@ -16,6 +20,7 @@ pub struct Selection {
     pub finished: bool,
     pub min: usize,
     pub max: usize,
+    pub player: u8, //only this player can pick cards and validate
 }
 
 #[derive(Component)]
@@ -25,6 +30,20 @@ pub struct Selectable;
 pub struct Selected;
 
 pub fn card_action(world: &mut World) {
+    if !matches!(
+        world.resource::<RoutineManager>().0.front(),
+        Some(Routines::CardAction { .. })
+    ) {
+        return;
+    }
+    //what the action queues (moves, selections, draws) resolves before the routines already queued
+    //after it, as in the rules engine
+    let rest = world.resource_mut::<RoutineManager>().0.split_off(1);
+    resolve_action(world);
+    world.resource_mut::<RoutineManager>().0.extend(rest);
+}
+
+fn resolve_action(world: &mut World) {
     if let Some(Routines::CardAction {
         card,
         owner,
@@ -35,60 +54,19 @@ pub fn card_action(world: &mut World) {
     {
         match action {
             Action::Eco(qty) => {
-                if owner == 0 {
-                    if let Ok(mut eco) = world
-                        .query_filtered::<&mut PlayerEconomy, With<Player<0>>>()
-                        .get_single_mut(world)
-                    {
-                        eco.0 += qty as i32;
-                    }
-                }
-                if owner == 1 {
-                    if let Ok(mut eco) = world
-                        .query_filtered::<&mut PlayerEconomy, With<Player<1>>>()
-                        .get_single_mut(world)
-                    {
-                        eco.0 += qty as i32;
-                    }
+                if let Some(mut eco) = player_counter_mut::<PlayerEconomy>(world, owner) {
+                    eco.0 += qty as i32;
                 }
                 world.resource_mut::<RoutineManager>().finish();
             }
             Action::Atk(qty) => {
-                if owner == 0 {
-                    if let Ok(mut atk) = world
-                        .query_filtered::<&mut PlayerAttack, With<Player<0>>>()
-                        .get_single_mut(world)
-                    {
-                        atk.0 += qty as i32;
-                    }
-                }
-                if owner == 1 {
-                    if let Ok(mut atk) = world
-                        .query_filtered::<&mut PlayerAttack, With<Player<1>>>()
-                        .get_single_mut(world)
-                    {
-                        atk.0 += qty as i32;
-                    }
+                if let Some(mut atk) = player_counter_mut::<PlayerAttack>(world, owner) {
+                    atk.0 += qty as i32;
                 }
                 world.resource_mut::<RoutineManager>().finish();
             }
             Action::Life(qty) => {
-                if owner == 0 {
-                    if let Ok(mut life) = world
-                        .query_filtered::<&mut PlayerLife, With<Player<0>>>()
-                        .get_single_mut(world)
-                    {
-                        life.0 += qty as i32;
-                    }
-                }
-                if owner == 1 {
-                    if let Ok(mut life) = world
-                        .query_filtered::<&mut PlayerLife, With<Player<1>>>()
-                        .get_single_mut(world)
-                    {
-                        life.0 += qty as i32;
-                    }
-                }
+                change_life(world, owner, qty as i32);
                 world.resource_mut::<RoutineManager>().finish();
             }
             Action::Draw(qty) => {
@@ -98,6 +76,15 @@ pub fn card_action(world: &mut World) {
                     routines.draw(owner, true);
                 }
             }
+            Action::DrawPer(counter) => {
+                let qty =
+                    counter.value(&player_counter_mut::<PlayerTurnTracker>(world, owner).unwrap());
+                let mut routines = world.resource_mut::<RoutineManager>();
+                routines.finish();
+                for _ in 0..qty {
+                    routines.draw(owner, true);
+                }
+            }
             Action::Discard => {
                 let mut selection = world.resource_mut::<Selection>();
                 if selection.finished {
@@ -218,95 +205,83 @@ pub fn card_action(world: &mut World) {
             Action::ScrapSelf => {
                 let mut routines = world.resource_mut::<RoutineManager>();
                 routines.finish();
-                routines.discard(owner, card);
+                routines.scrap(card);
             }
             Action::DestroyBase => {
                 let mut selection = world.resource_mut::<Selection>();
                 if selection.finished {
                     selection.finished = false;
                     let test: Vec<Entity> = selection.cards.drain(..).collect();
+                    let owners: Vec<Option<CardOwners>> = test
+                        .iter()
+                        .map(|&card| world.get::<CardOwners>(card).copied())
+                        .collect();
                     let mut routines = world.resource_mut::<RoutineManager>();
                     routines.finish();
-                    for &card in test.iter() {
-                        routines.discard(if owner == 0 { 1 } else { 0 }, card);
+                    for (&card, owner) in test.iter().zip(owners) {
+                        if let Some(CardOwners::Player(owner)) = owner {
+                            routines.discard(owner, card);
+                        }
                     }
                 } else {
-                    //complex filter ahead
-                    if owner == 0 {
-                        if world.query_filtered::<Entity, (With<Bases>, With<Outpost>, With<Player<1>>)>().iter(world).count() != 0 {
-                            //first, test for outposts
-                            world.resource_mut::<RoutineManager>().selection(
-                                1,
-                                1,
-                                Stacks::Bases,
-                                CardOwners::Player(1),
-                                KindMask::Outposts,
-                                0,
-                                255,
-                            );
-                        } else if world.query_filtered::<Entity, (With<Bases>, With<Outpost>, With<Player<1>>)>().iter(world).count() != 0 {
-                            //then test for bases
-                            world.resource_mut::<RoutineManager>().selection(
-                                1,
-                                1,
-                                Stacks::Bases,
-                                CardOwners::Player(1),
-                                KindMask::Bases,
-                                0,
-                                255,
-                            );
-                        } else {
-                            //then if no base, early return
-                            world.resource_mut::<RoutineManager>().finish();
-                        }
+                    //outposts of a player must be destroyed before their other bases
+                    let bases: Vec<(CardOwners, bool)> = world
+                        .query_filtered::<(&CardOwners, Option<&Outpost>), With<Bases>>()
+                        .iter(world)
+                        .map(|(o, outpost)| (*o, outpost.is_some()))
+                        .collect();
+                    let filters: Vec<SelectionFilter> = targets(world, owner)
+                        .into_iter()
+                        .map(CardOwners::Player)
+                        .filter(|&target| bases.iter().any(|&(o, _)| o == target))
+                        .map(|target| SelectionFilter {
+                            stacks: vec![Stacks::Bases],
+                            owners: vec![target],
+                            kinds: if bases.iter().any(|&(o, outpost)| o == target && outpost) {
+                                KindMask::Outposts
+                            } else {
+                                KindMask::Bases
+                            },
+                            min_cost: 0,
+                            max_cost: 255,
+                        })
+                        .collect();
+                    if filters.is_empty() {
+                        //no base, early return
+                        world.resource_mut::<RoutineManager>().finish();
                     } else {
-                        //and do this twice, once for each player
-                        if world.query_filtered::<Entity, (With<Bases>, With<Outpost>, With<Player<0>>)>().iter(world).count() != 0 {
-                            //first, test for outposts
-                            world.resource_mut::<RoutineManager>().selection(
-                                1,
-                                1,
-                                Stacks::Bases,
-                                CardOwners::Player(0),
-                                KindMask::Outposts,
-                                0,
-                                255,
-                            );
-                        } else if world.query_filtered::<Entity, (With<Bases>, With<Outpost>, With<Player<0>>)>().iter(world).count() != 0 {
-                            //then test for bases
-                            world.resource_mut::<RoutineManager>().selection(
-                                1,
-                                1,
-                                Stacks::Bases,
-                                CardOwners::Player(0),
-                                KindMask::Bases,
-                                0,
-                                255,
-                            );
-                        } else {
-                            //then if no base, early return
-                            world.resource_mut::<RoutineManager>().finish();
-                        }
+                        world
+                            .resource_mut::<RoutineManager>()
+                            .extended_selection(1, 1, filters);
                     }
                 }
             }
             Action::EnemyDiscard => {
+                //discards are stored and only applied at the start of that players turn
+                //with several possible targets, the player chooses one of them
+                let targets = targets(world, owner);
+                let mut routines = world.resource_mut::<RoutineManager>();
+                routines.finish();
+                if targets.len() > 1 {
+                    routines.activate_nested(
+                        owner,
+                        card,
+                        ability_index,
+                        vec![ActionSet::discard_choice(targets)],
+                    );
+                } else if let Some(&target) = targets.first() {
+                    if let Some(mut tracker) =
+                        player_counter_mut::<PlayerTurnTracker>(world, target)
+                    {
+                        tracker.cards_to_discard += 1;
+                    }
+                }
+            }
+            Action::PlayerDiscard(target) => {
                 world.resource_mut::<RoutineManager>().finish();
-                if owner == 0 {
-                    world
-                        .query_filtered::<&mut PlayerTurnTracker, With<Player<1>>>()
-                        .get_single_mut(world)
-                        .unwrap()
-                        .cards_to_discard += 1;
-                } else {
-                    world
-                        .query_filtered::<&mut PlayerTurnTracker, With<Player<0>>>()
-                        .get_single_mut(world)
-                        .unwrap()
-                        .cards_to_discard += 1;
+                if let Some(mut tracker) = player_counter_mut::<PlayerTurnTracker>(world, target) {
+                    tracker.cards_to_discard += 1;
                 }
-                //spawn a prompt to select an enemy player?
-                //discards are stored and only applied at the start of that players turn
             }
             Action::FreeBuy {
                 min_cost,
@@ -349,3 +324,10 @@ pub fn card_action(world: &mut World) {
         }
     }
 }
+
+//players the owner of a card can target with DestroyBase/EnemyDiscard, depending on the mode
+fn targets(world: &mut World, owner: u8) -> Vec<u8> {
+    let alive = alive_players(world);
+    let players = world.resource::<PlayerCount>().0;
+    world.resource::<GameMode>().targets(owner, players, &alive)
+}
@@ -1,5 +1,5 @@
 use crate::game::routines::{RoutineManager, Routines};
-use crate::players::{LocalPlayer, Player};
+use crate::players::LocalPlayer;
 use crate::prelude::*;
 use crate::prelude::{
     CardIndex, CardOwners, Commands, DiscardPile, Entity, Hand, PlayerDeck, Query, Stacks,
@@ -7,42 +7,44 @@ use crate::prelude::{
 };
 use bevy_rapier3d::prelude::DebugRenderContext;
 
-pub fn draw_routine<const PLAYER: u8>(
+type OutOfDeck<T> = (With<T>, Without<PlayerDeck>);
+
+pub fn draw_routine(
     mut commands: Commands,
     mut manager: ResMut<RoutineManager>,
-    hand: Query<&CardIndex, (With<Hand>, With<Player<PLAYER>>, Without<PlayerDeck>)>,
-    discard_pile: Query<Entity, (With<DiscardPile>, With<Player<PLAYER>>, Without<PlayerDeck>)>,
-    deck: Query<(Entity, &CardIndex), (With<PlayerDeck>, With<Player<PLAYER>>)>,
+    hand: Query<(&CardIndex, &CardOwners), OutOfDeck<Hand>>,
+    discard_pile: Query<(Entity, &CardOwners), OutOfDeck<DiscardPile>>,
+    deck: Query<(Entity, &CardIndex, &CardOwners), With<PlayerDeck>>,
     local_player: Res<LocalPlayer>,
     debug: Res<DebugRenderContext>,
 ) {
     let mut finished = false;
-    let mut send_shuffle = false;
+    let mut send_shuffle = None;
     if let Some(Routines::Draw {
         player,
         drawn,
         discard_to_deck,
     }) = manager.routine_mut()
     {
-        if *player != PLAYER {
-            return;
-        }
+        let owner = CardOwners::Player(*player);
+        let mut deck = deck.iter().filter(|(.., o)| **o == owner).peekable();
         if let Some(drawn) = drawn {
             if hand.contains(*drawn) {
                 finished = true;
             }
         } else if *discard_to_deck {
-            if deck.is_empty() {
+            if deck.peek().is_none() {
                 return;
             }
-            send_shuffle = true;
+            send_shuffle = Some(owner);
             *discard_to_deck = false;
         } else {
-            if deck.is_empty() {
-                if !discard_pile.is_empty() {
-                    for card in discard_pile.iter() {
+            if deck.peek().is_none() {
+                let mut discard_pile = discard_pile.iter().filter(|(_, o)| **o == owner).peekable();
+                if discard_pile.peek().is_some() {
+                    for (card, _) in discard_pile {
                         commands.entity(card).insert(StartTransition {
-                            owner: CardOwners::Player(PLAYER),
+                            owner,
                             stack: Stacks::PlayerDeck,
                             index: CardIndex(0), //will be shuffled anyway
                             visibility: CardVisibility::Hidden,
@@ -59,16 +61,17 @@ pub fn draw_routine<const PLAYER: u8>(
                 //search empty slot (mask trick to allow random iteration)
                 let empty_slot = hand
                     .iter()
-                    .fold(0u64, |m, v| m | 1u64 << v.0)
+                    .filter(|(_, o)| **o == owner)
+                    .fold(0u64, |m, (v, _)| m | 1u64 << v.0)
                     .trailing_ones();
-                for (card, index) in deck.iter() {
+                for (card, index, _) in deck {
                     if index.0 == 0 {
                         *drawn = Some(card);
                         commands.entity(card).insert(StartTransition {
-                            owner: CardOwners::Player(PLAYER),
+                            owner,
                             stack: Stacks::Hand,
                             index: CardIndex(empty_slot as usize),
-                            visibility: if local_player.0 == PLAYER || debug.enabled {
+                            visibility: if local_player.0 == *player || debug.enabled {
                                 CardVisibility::Visible
                             } else {
                                 CardVisibility::Hidden
@@ -77,7 +80,7 @@ pub fn draw_routine<const PLAYER: u8>(
                         });
                     } else {
                         commands.entity(card).insert(StartTransition {
-                            owner: CardOwners::Player(PLAYER),
+                            owner,
                             stack: Stacks::PlayerDeck,
                             index: CardIndex(index.0 - 1),
                             visibility: CardVisibility::Hidden,
@@ -90,7 +93,7 @@ pub fn draw_routine<const PLAYER: u8>(
     }
     if finished {
         manager.finish();
-    } else if send_shuffle {
-        manager.shuffle(CardOwners::Player(PLAYER), Stacks::PlayerDeck, true);
+    } else if let Some(owner) = send_shuffle {
+        manager.shuffle(owner, Stacks::PlayerDeck, true);
     }
 }
@@ -0,0 +1,64 @@
+use crate::cards::actions::KindMask;
+use crate::game::routines::card_action::Selection;
+use crate::game::routines::{RoutineManager, Routines, SelectionFilter};
+use crate::players::{player_counter_mut, PlayerTurnTracker};
+use crate::prelude::*;
+use crate::states::turn::TurnStates;
+
+//discards stored by EnemyDiscard are applied at the start of the turn of the targeted player
+pub fn queue_forced_discards(
+    turn: Res<State<TurnStates>>,
+    trackers: Query<(&PlayerTurnTracker, &CardOwners)>,
+    mut routines: ResMut<RoutineManager>,
+) {
+    if let &TurnStates::PlayerTurn(player) = turn.get() {
+        let owner = CardOwners::Player(player);
+        if trackers
+            .iter()
+            .any(|(t, o)| *o == owner && t.cards_to_discard > 0)
+        {
+            routines.0.push_back(Routines::ForcedDiscard { player });
+        }
+    }
+}
+
+pub fn forced_discard(world: &mut World) {
+    if let Some(Routines::ForcedDiscard { player }) = world.resource::<RoutineManager>().routine() {
+        let mut selection = world.resource_mut::<Selection>();
+        if selection.finished {
+            selection.finished = false;
+            let test: Vec<Entity> = selection.cards.drain(..).collect();
+            let mut routines = world.resource_mut::<RoutineManager>();
+            routines.finish();
+            for &card in test.iter() {
+                routines.discard(player, card);
+            }
+            if let Some(mut tracker) = player_counter_mut::<PlayerTurnTracker>(world, player) {
+                tracker.cards_to_discard = 0;
+            }
+        } else {
+            //mandatory: exactly as many cards as required (or the whole hand if it is too small)
+            let owner = CardOwners::Player(player);
+            let hand = world
+                .query_filtered::<&CardOwners, With<Hand>>()
+                .iter(world)
+                .filter(|o| **o == owner)
+                .count();
+            let count = player_counter_mut::<PlayerTurnTracker>(world, player)
+                .map_or(0, |t| t.cards_to_discard)
+                .min(hand);
+            world.resource_mut::<RoutineManager>().player_selection(
+                player,
+                count,
+                count,
+                vec![SelectionFilter {
+                    stacks: vec![Stacks::Hand],
+                    owners: vec![owner],
+                    kinds: KindMask::Any,
+                    min_cost: 0,
+                    max_cost: 255,
+                }],
+            );
+        }
+    }
+}
@@ -1,15 +1,18 @@
 mod activate_card;
 pub mod card_action;
 mod draw;
+mod forced_discard;
 pub mod move_to_stack;
 mod reload_market;
-mod selection;
+pub mod selection;
 mod shuffle;
+mod turn_finished;
 
 use crate::cards::actions::{Action, ActionCondition, KindMask};
 use crate::game::events::CardActions;
 use crate::game::routines::card_action::Selection;
 use crate::prelude::*;
+use crate::states::turn::TurnStates;
 use bevy::utils::HashSet;
 use std::collections::VecDeque;
 
@@ -64,11 +67,19 @@ pub enum Routines {
         action: Action,
     },
     Selection {
+        player: Option<u8>, //player answering the selection, None -> the player whose turn it is
         filters: Vec<SelectionFilter>,
+        exclude: Option<Entity>, //card matching the filters that can't be selected
         min: usize,
         max: usize,
         running: bool,
     },
+    ForcedDiscard {
+        player: u8,
+    },
+    TurnFinished {
+        player: u8,
+    },
 }
 
 #[derive(Resource, Reflect, Debug, Default)]
@@ -87,6 +98,7 @@ impl RoutineManager {
         max_cost: u8,
     ) {
         self.0.push_front(Routines::Selection {
+            player: None,
             filters: vec![SelectionFilter {
                 stacks: vec![stack],
                 owners: vec![owner],
@@ -94,6 +106,7 @@ impl RoutineManager {
                 min_cost,
                 max_cost,
             }],
+            exclude: None,
             min,
             max,
             running: false,
@@ -102,7 +115,45 @@ impl RoutineManager {
 
     pub fn extended_selection(&mut self, min: usize, max: usize, filters: Vec<SelectionFilter>) {
         self.0.push_front(Routines::Selection {
+            player: None,
+            filters,
+            exclude: None,
+            min,
+            max,
+            running: false,
+        });
+    }
+
+    //selection in which a card can't be picked, like the Stealth Needle that can't copy itself
+    pub fn selection_without(
+        &mut self,
+        card: Entity,
+        min: usize,
+        max: usize,
+        filters: Vec<SelectionFilter>,
+    ) {
+        self.0.push_front(Routines::Selection {
+            player: None,
+            filters,
+            exclude: Some(card),
+            min,
+            max,
+            running: false,
+        });
+    }
+
+    //selection answered by a specific player, even outside of their turn
+    pub fn player_selection(
+        &mut self,
+        player: u8,
+        min: usize,
+        max: usize,
+        filters: Vec<SelectionFilter>,
+    ) {
+        self.0.push_front(Routines::Selection {
+            player: Some(player),
             filters,
+            exclude: None,
             min,
             max,
             running: false,
@@ -212,6 +263,19 @@ impl RoutineManager {
         });
     }
 
+    //queue the parts of a set at the front, so they resolve before anything queued after the set
+    pub fn activate_nested(&mut self, owner: u8, card: Entity, index: u8, sets: Vec<ActionSet>) {
+        for set in sets.into_iter().rev() {
+            self.0.push_front(Routines::ActivateCard {
+                card,
+                owner,
+                index,
+                set,
+                running: false,
+            });
+        }
+    }
+
     pub fn action(
         &mut self,
         owner: u8,
@@ -242,14 +306,16 @@ impl Plugin for RoutinesPlugin {
             .add_systems(
                 Update,
                 (
-                    draw::draw_routine::<0>,
-                    draw::draw_routine::<1>,
+                    draw::draw_routine,
                     shuffle::shuffle,
                     reload_market::reload_market,
                     move_to_stack::move_to_stack,
                     activate_card::activate_card,
                     card_action::card_action,
                     selection::selection,
+                    forced_discard::forced_discard,
+                    turn_finished::turn_finished,
+                    forced_discard::queue_forced_discards.run_if(state_changed::<TurnStates>),
                 ),
             );
     }
@@ -5,7 +5,7 @@ use crate::prelude::{CardIndex, Commands, Entity, Query};
 
 #[derive(Resource, Reflect, Debug)]
 #[reflect(Resource)]
-pub struct NextBuyOnDeckFlag(pub KindMask); //FIXME: player counter reset on turn end?
+pub struct NextBuyOnDeckFlag(pub KindMask); //reset on turn end
 
 impl Default for NextBuyOnDeckFlag {
     fn default() -> Self {
@@ -64,6 +64,7 @@ pub fn move_to_stack(
                 {
                     next_buy_on_deck_flag.0 = KindMask::None;
                     *target_stack = Stacks::PlayerDeck;
+                    *target_visibility = CardVisibility::Hidden;
                 }
                 if target_index.is_none() {
                     *target_index = Some(
@@ -1,7 +1,10 @@
+use crate::engine::replay::ReplayInput;
+use crate::game::events::Answers;
 use crate::game::routines::card_action::{Selectable, Selected, Selection};
 use crate::game::routines::{RoutineManager, Routines, SelectionFilter};
 use crate::game::GameStates;
 use crate::prelude::*;
+use crate::states::turn::TurnStates;
 use crate::ui::SelectionValidationButton;
 use bevy_rapier3d::rapier::crossbeam::channel::at;
 
@@ -14,9 +17,14 @@ pub fn selection(
     mut game_state: ResMut<NextState<GameStates>>,
     all_cards: Query<(Entity, &CardOwners, &Stacks, &CardCost, &CardKinds)>,
     mut validation_button: Query<&mut Visibility, With<SelectionValidationButton>>,
+    turn: Res<State<TurnStates>>,
+    ids: Query<&CardId>,
+    mut answers: ResMut<Answers>,
 ) {
     if let Some(Routines::Selection {
+        player,
         filters,
+        exclude,
         min,
         max,
         running,
@@ -28,9 +36,17 @@ pub fn selection(
             selection.cards = Vec::new();
             selection.min = *min;
             selection.max = *max;
+            selection.player = match (*player, turn.get()) {
+                (Some(player), _) => player,
+                (None, &TurnStates::PlayerTurn(player)) => player,
+                _ => 0,
+            };
             let mut at_least_one_choice = false;
             //get all valid cards and insert the Selectable component
-            for (card, &owner, &stack, &CardCost(cost), &kind) in all_cards.iter() {
+            for (card, &owner, &stack, &CardCost(cost), &kind) in all_cards
+                .iter()
+                .filter(|(card, ..)| Some(*card) != *exclude)
+            {
                 for SelectionFilter {
                     stacks,
                     owners,
@@ -70,9 +86,42 @@ pub fn selection(
                 selection.cards.push(card);
                 commands.entity(card).remove::<Selected>();
             }
+            //resolved in CardId order, as the rules engine does
+            selection
+                .cards
+                .sort_by_key(|&card| ids.get(card).map_or(usize::MAX, |id| id.0));
+            if !selectable.is_empty() {
+                let cards = ids.iter_many(&selection.cards).map(|id| id.0).collect();
+                answers
+                    .0
+                    .push((selection.player, ReplayInput::Select(cards)));
+            }
             game_state.set(GameStates::MainLoop);
             *validation_button.get_single_mut().unwrap() = Visibility::Hidden;
             manager.finish();
         }
     }
 }
+
+//the state only goes back to MainLoop on the next frame, the selection is open while its routine runs
+pub fn selection_open(world: &World) -> bool {
+    matches!(
+        world.resource::<RoutineManager>().0.front(),
+        Some(Routines::Selection { running: true, .. })
+    ) && !world.resource::<Selection>().finished
+}
+
+//answer the open selection for a player that does not use the mouse (network, bots): the cards
+//are given by their CardId, those that can not be selected are ignored
+pub fn answer_selection(world: &mut World, cards: &[usize]) {
+    let entities: Vec<Entity> = world
+        .query_filtered::<(Entity, &CardId), With<Selectable>>()
+        .iter(world)
+        .filter(|(_, id)| cards.contains(&id.0))
+        .map(|(entity, _)| entity)
+        .collect();
+    for entity in entities {
+        world.entity_mut(entity).insert(Selected);
+    }
+    world.resource_mut::<Selection>().finished = true;
+}
@@ -1,18 +1,13 @@
+use crate::game::rng::GameRng;
 use crate::game::routines::{RoutineManager, Routines};
-use crate::players::Player;
 use crate::prelude::*;
-use crate::prelude::{
-    CardIndex, CardOwners, Commands, Entity, PlayerDeck, Query, Stacks, StartTransition, With,
-};
-use rand::prelude::SliceRandom;
-use rand::thread_rng;
+use crate::prelude::{CardIndex, CardOwners, Commands, Entity, Query, Stacks, StartTransition};
 
 pub fn shuffle(
     mut commands: Commands,
     mut manager: ResMut<RoutineManager>,
-    player_0_deck: Query<Entity, (With<Player<0>>, With<PlayerDeck>)>,
-    player_1_deck: Query<Entity, (With<Player<1>>, With<PlayerDeck>)>,
-    market_deck: Query<Entity, With<MarketDeck>>,
+    mut rng: ResMut<GameRng>,
+    cards: Query<(Entity, &CardId, &CardOwners, &Stacks)>,
 ) {
     let mut finished = false;
     if let Some(Routines::Shuffle {
@@ -21,83 +16,37 @@ pub fn shuffle(
         running,
     }) = manager.routine_mut()
     {
-        match (*owner, *stack) {
-            (CardOwners::Player(0), Stacks::PlayerDeck) => {
-                if *running {
-                    if player_0_deck.is_empty() {
-                        return;
-                    }
-                    finished = true;
-                } else {
-                    let mut v: Vec<Entity> = player_0_deck.iter().collect();
-                    if !v.is_empty() {
-                        v.shuffle(&mut thread_rng());
-                        for (i, e) in v.iter().enumerate() {
-                            commands.entity(*e).insert(StartTransition {
-                                owner: *owner,
-                                stack: *stack,
-                                index: CardIndex(i),
-                                visibility: CardVisibility::Hidden,
-                                length: 0.5,
-                            });
-                        }
-                        *running = true;
-                    } else {
-                        finished = true;
-                    }
-                }
-            }
-            (CardOwners::Player(1), Stacks::PlayerDeck) => {
-                if *running {
-                    if player_1_deck.is_empty() {
-                        return;
-                    }
-                    finished = true;
-                } else {
-                    let mut v: Vec<Entity> = player_1_deck.iter().collect();
-                    if !v.is_empty() {
-                        v.shuffle(&mut thread_rng());
-                        for (i, e) in v.iter().enumerate() {
-                            commands.entity(*e).insert(StartTransition {
-                                owner: *owner,
-                                stack: *stack,
-                                index: CardIndex(i),
-                                visibility: CardVisibility::Hidden,
-                                length: 0.5,
-                            });
-                        }
-                        *running = true;
-                    } else {
-                        finished = true;
-                    }
-                }
+        if !matches!(
+            (*owner, *stack),
+            (CardOwners::Player(_), Stacks::PlayerDeck) | (CardOwners::Market, Stacks::MarketDeck)
+        ) {
+            return;
+        }
+        let mut v: Vec<(Entity, &CardId)> = cards
+            .iter()
+            .filter(|(_, _, o, s)| *o == owner && *s == stack)
+            .map(|(e, id, ..)| (e, id))
+            .collect();
+        if *running {
+            if v.is_empty() {
+                return;
             }
-            (CardOwners::Market, Stacks::MarketDeck) => {
-                if *running {
-                    if market_deck.is_empty() {
-                        return;
-                    }
-                    finished = true;
-                } else {
-                    let mut v: Vec<Entity> = market_deck.iter().collect();
-                    if !v.is_empty() {
-                        v.shuffle(&mut thread_rng());
-                        for (i, e) in v.iter().enumerate() {
-                            commands.entity(*e).insert(StartTransition {
-                                owner: *owner,
-                                stack: *stack,
-                                index: CardIndex(i),
-                                visibility: CardVisibility::Hidden,
-                                length: 0.5,
-                            });
-                        }
-                        *running = true;
-                    } else {
-                        finished = true;
-                    }
-                }
+            finished = true;
+        } else if !v.is_empty() {
+            v.sort_by_key(|(_, id)| **id);
+            rng.shuffle(&mut v);
+            for (i, (e, _)) in v.iter().enumerate() {
+                commands.entity(*e).insert(StartTransition {
+                    owner: *owner,
+                    stack: *stack,
+                    index: CardIndex(i),
+                    visibility: CardVisibility::Hidden,
+                    length: 0.5,
+                });
             }
-            _ => {}
+            *running = true;
+        } else {
+            finished = true;
         }
     }
     if finished {
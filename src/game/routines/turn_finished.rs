@@ -0,0 +1,14 @@
+use crate::game::routines::{RoutineManager, Routines};
+use crate::players::{player_counter_mut, PlayerTurnTracker};
+use crate::prelude::*;
+
+//queued by pass_turn after the end of turn discards and draws, so the trackers see the cards
+//move before being reset (and by setup after the first hands)
+pub fn turn_finished(world: &mut World) {
+    if let Some(Routines::TurnFinished { player }) = world.resource::<RoutineManager>().routine() {
+        world.resource_mut::<RoutineManager>().finish();
+        if let Some(mut tracker) = player_counter_mut::<PlayerTurnTracker>(world, player) {
+            tracker.turn_finished();
+        }
+    }
+}
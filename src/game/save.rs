@@ -0,0 +1,168 @@
+use crate::game::events::{GameEvent, GameEvents, GameSetup};
+use crate::game::history::{board_at_rest, BoardSnapshot, History};
+use crate::game::victory::reset_board;
+use crate::game::GameStates;
+use crate::network::NetworkSession;
+use crate::prelude::*;
+use crate::states::turn::TurnStates;
+use bevy::ecs::system::RunSystemOnce;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use thiserror::Error;
+
+pub const QUICKSAVE: &str = "saves/quicksave.game.ron";
+
+//selections and choices are not part of the event log, so replaying the log alone would not
+//give back the same board: the board itself is saved, the log is kept for the record
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SavedGame {
+    pub log: Vec<GameEvents>, //starts with the setup event
+    pub turn: TurnStates,
+    pub board: BoardSnapshot,
+}
+
+#[derive(Debug, Error)]
+pub enum SaveError {
+    #[error("no game in progress")]
+    NoGame,
+    #[error("the board is busy, wait for the current actions to finish")]
+    Busy,
+    #[error("the save does not start with a setup event")]
+    MissingSetup,
+    #[error("could not access the save file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not write RON: {0}")]
+    Ron(#[from] ron::Error),
+    #[error("could not parse RON: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+}
+
+impl SavedGame {
+    //games can only be saved between two events of a player turn
+    pub fn capture(world: &mut World) -> Result<Self, SaveError> {
+        let turn = *world.resource::<State<TurnStates>>().get();
+        if !matches!(turn, TurnStates::PlayerTurn(_)) {
+            return Err(SaveError::NoGame);
+        }
+        if *world.resource::<State<GameStates>>().get() != GameStates::MainLoop
+            || !board_at_rest(world)
+        {
+            return Err(SaveError::Busy);
+        }
+        Ok(Self {
+            log: world.resource::<GameEvent>().log().to_vec(),
+            turn,
+            board: BoardSnapshot::capture(world),
+        })
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), SaveError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    //setup of the game to load the board into
+    pub fn setup(&self) -> Result<GameSetup, SaveError> {
+        let Some(&GameEvents::Setup {
+            seed,
+            set,
+            players,
+            mode,
+            ..
+        }) = self.log.first()
+        else {
+            return Err(SaveError::MissingSetup);
+        };
+        let TurnStates::PlayerTurn(current) = self.turn else {
+            return Err(SaveError::NoGame);
+        };
+        //starting on the saved turn avoids a turn change (and its start of turn effects) after the load
+        Ok(GameSetup {
+            seed,
+            set,
+            players,
+            starting_player: current,
+            mode,
+        })
+    }
+
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, SaveError> {
+        let saved: Self = ron::from_str(&std::fs::read_to_string(path)?)?;
+        if !matches!(saved.log.first(), Some(GameEvents::Setup { .. })) {
+            return Err(SaveError::MissingSetup);
+        }
+        Ok(saved)
+    }
+}
+
+pub fn save_game(world: &mut World, path: impl AsRef<Path>) -> Result<(), SaveError> {
+    SavedGame::capture(world)?.write(path)
+}
+
+//a loaded game is set up again with the same seed, set and mode (so the same cards are spawned
+//with the same ids), then the saved board replaces the fresh one once the setup is done
+#[derive(Resource, Default)]
+pub struct PendingLoad(pub Option<SavedGame>);
+
+pub fn load_game(world: &mut World, saved: SavedGame) -> Result<(), SaveError> {
+    let setup = saved.setup()?;
+    world.run_system_once(reset_board);
+    world.resource_mut::<GameEvent>().push(setup.event());
+    world.resource_mut::<PendingLoad>().0 = Some(saved);
+    Ok(())
+}
+
+pub fn finish_load(world: &mut World) {
+    if world.resource::<PendingLoad>().0.is_none()
+        || !matches!(
+            world.resource::<State<TurnStates>>().get(),
+            TurnStates::PlayerTurn(_)
+        )
+        || !board_at_rest(world)
+    {
+        return;
+    }
+    let Some(saved) = world.resource_mut::<PendingLoad>().0.take() else {
+        return;
+    };
+    saved.board.restore(world);
+    world.insert_resource(GameEvent::from_log(saved.log));
+    world.resource_mut::<History>().clear();
+}
+
+//F5 saves to the quicksave file, F9 loads it (not in network games)
+pub fn quicksave_keys(world: &mut World) {
+    if world.contains_resource::<NetworkSession>() {
+        return;
+    }
+    let keys = world.resource::<ButtonInput<KeyCode>>();
+    let (save, load) = (
+        keys.just_pressed(KeyCode::F5),
+        keys.just_pressed(KeyCode::F9),
+    );
+    if save {
+        if let Err(err) = save_game(world, QUICKSAVE) {
+            warn!("could not save the game: {err}");
+        }
+    } else if load {
+        if let Err(err) = SavedGame::read(QUICKSAVE).and_then(|saved| load_game(world, saved)) {
+            warn!("could not load the game: {err}");
+        }
+    }
+}
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingLoad>().add_systems(
+            Update,
+            (quicksave_keys, finish_load).run_if(in_state(AppStates::Playing)),
+        );
+    }
+}
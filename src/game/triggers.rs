@@ -0,0 +1,90 @@
+use crate::cards::actions::Action;
+use crate::cards::transition::CardStateSnapshot;
+use crate::game::routines::RoutineManager;
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+//game events a card can react to, always from the point of view of the player that caused them
+#[derive(Serialize, Deserialize, Debug, Reflect, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Trigger {
+    ShipPlayed, //a ship went from the hand of the player to their used cards
+}
+
+#[derive(Serialize, Deserialize, Debug, Reflect, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum TriggerDuration {
+    ThisTurn,    //removed at the end of the turn of the owner
+    WhileInPlay, //removed when the card leaves play
+}
+
+impl Trigger {
+    //trigger (and the player it concerns) fired by a card moving, if any
+    pub fn from_move(
+        previous: &CardStateSnapshot,
+        next: &CardStateSnapshot,
+    ) -> Option<(Trigger, u8)> {
+        match (previous.owner, previous.stack, next.owner, next.stack) {
+            (CardOwners::Player(_), Stacks::Hand, CardOwners::Player(p), Stacks::UsedCards) => {
+                Some((Trigger::ShipPlayed, p))
+            }
+            _ => None,
+        }
+    }
+}
+
+//true if the move takes the card out of play (bases and used cards)
+pub fn leaves_play(previous: &CardStateSnapshot, next: &CardStateSnapshot) -> bool {
+    let in_play = |stack: Stacks| stack == Stacks::Bases || stack == Stacks::UsedCards;
+    in_play(previous.stack) && !in_play(next.stack)
+}
+
+#[derive(Reflect, Debug, Clone)]
+pub struct TriggerListener {
+    pub card: Entity,
+    pub owner: u8,
+    pub trigger: Trigger,
+    pub duration: TriggerDuration,
+    pub action: Action,
+}
+
+//abilities waiting for a game event, registered by cards (see Uniques::FleetHQ)
+//firing a trigger only queues the action in the RoutineManager, like any other card action
+#[derive(Resource, Reflect, Debug, Default, Clone)]
+#[reflect(Resource)]
+pub struct Triggers(pub Vec<TriggerListener>);
+
+impl Triggers {
+    pub fn register(&mut self, listener: TriggerListener) {
+        self.0.push(listener);
+    }
+
+    pub fn fire(&self, trigger: Trigger, player: u8, routines: &mut RoutineManager) {
+        for listener in self
+            .0
+            .iter()
+            .filter(|l| l.trigger == trigger && l.owner == player)
+        {
+            routines.action(listener.owner, listener.card, 0, 0, listener.action);
+        }
+    }
+
+    pub fn turn_ended(&mut self, player: u8) {
+        self.0
+            .retain(|l| l.owner != player || l.duration != TriggerDuration::ThisTurn);
+    }
+
+    pub fn card_left_play(&mut self, card: Entity) {
+        self.0.retain(|l| l.card != card);
+    }
+}
+
+pub struct TriggersPlugin;
+
+impl Plugin for TriggersPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Trigger>()
+            .register_type::<TriggerDuration>()
+            .register_type::<TriggerListener>()
+            .register_type::<Triggers>()
+            .init_resource::<Triggers>();
+    }
+}
@@ -0,0 +1,91 @@
+use crate::game::event_handlers::event_handler_dispatcher;
+use crate::game::events::{Answers, GameEvent};
+use crate::game::history::History;
+use crate::game::modes::GameMode;
+use crate::game::rng::GameRng;
+use crate::game::routines::card_action::Selection;
+use crate::game::routines::move_to_stack::NextBuyOnDeckFlag;
+use crate::game::routines::RoutineManager;
+use crate::game::triggers::Triggers;
+use crate::game::GameStates;
+use crate::players::{PlayerCount, PlayerLife};
+use crate::prelude::*;
+use crate::states::turn::{GameOverReason, TurnStates};
+use crate::ui::choice_ui::ChoiceRoot;
+
+//the game ends as soon as the mode has a winner, whatever made the others lose their life
+pub fn check_victory(
+    turn: Res<State<TurnStates>>,
+    mut next: ResMut<NextState<TurnStates>>,
+    lives: Query<(&PlayerLife, &CardOwners)>,
+    player_count: Res<PlayerCount>,
+    mode: Res<GameMode>,
+) {
+    //a transition is already queued (concession for example), don't override it
+    if next.0.is_some() {
+        return;
+    }
+    if let TurnStates::PlayerTurn(_) | TurnStates::PlayerCleanup(_) = turn.get() {
+        let mut alive = vec![false; player_count.0 as usize];
+        for (life, owner) in lives.iter() {
+            if let CardOwners::Player(p) = *owner {
+                alive[p as usize] = life.0 > 0;
+            }
+        }
+        if let Some(winner) = mode.winner(player_count.0, &alive) {
+            next.set(TurnStates::GameOver {
+                winner,
+                reason: GameOverReason::LifeDepleted,
+            });
+        }
+    }
+}
+
+pub fn enter_game_over(turn: Res<State<TurnStates>>, mut app: ResMut<NextState<AppStates>>) {
+    if let TurnStates::GameOver { .. } = turn.get() {
+        app.set(AppStates::GameOver);
+    }
+}
+
+//clear everything a game left behind, used when leaving the results screen (rematch or menu)
+pub fn reset_board(
+    mut commands: Commands,
+    cards: Query<Entity, With<CardId>>,
+    choices: Query<Entity, With<ChoiceRoot>>,
+    counters: Query<Entity, With<PlayerLife>>,
+    mut turn: ResMut<NextState<TurnStates>>,
+    mut game_state: ResMut<NextState<GameStates>>,
+) {
+    //player counters are spawned again by the next setup event
+    for entity in cards.iter().chain(choices.iter()).chain(counters.iter()) {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.insert_resource(GameEvent::default());
+    commands.insert_resource(Answers::default());
+    commands.insert_resource(RoutineManager::default());
+    commands.insert_resource(Selection::default());
+    commands.insert_resource(NextBuyOnDeckFlag::default());
+    commands.insert_resource(GameRng::default());
+    commands.insert_resource(Triggers::default());
+    commands.insert_resource(History::default());
+    turn.set(TurnStates::Setup);
+    game_state.set(GameStates::MainLoop);
+}
+
+pub struct VictoryPlugin;
+
+impl Plugin for VictoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                check_victory
+                    .after(event_handler_dispatcher)
+                    .run_if(in_state(AppStates::Playing)),
+                enter_game_over.run_if(state_changed::<TurnStates>),
+            )
+                .chain(),
+        )
+        .add_systems(OnExit(AppStates::GameOver), reset_board);
+    }
+}
@@ -0,0 +1,19 @@
+pub mod bots;
+pub mod cards;
+pub mod engine;
+pub mod game;
+pub mod network;
+pub mod players;
+pub mod stacks;
+pub mod states;
+pub mod ui;
+pub mod utils;
+
+pub mod prelude {
+    pub use super::cards::prelude::*;
+    pub use super::stacks::*;
+    pub use super::states::app::AppStates;
+    pub use super::utils::filter_enum::FilterEnumInserter;
+    pub use super::utils::ray_caster::RayCaster;
+    pub use bevy::prelude::*;
+}
@@ -1,28 +1,20 @@
-use crate::game::events::{GameEvent, GameEvents, GameEventsPlugin};
-use crate::game::GamePlugin;
-use crate::prelude::*;
-use crate::utils::ray_caster::RayCasterPlugin;
-use bevy::ecs::system::RunSystemOnce;
+use card_game_4::bots::mcts::Budget;
+use card_game_4::bots::{BotKind, BotPlugin, Bots};
+use card_game_4::cards::assets::manifest::SetCatalog;
+use card_game_4::game::events::{GameEvent, GameSetup};
+use card_game_4::game::replay::ReplayFile;
+use card_game_4::game::GamePlugin;
+use card_game_4::network::client::ServerGame;
+use card_game_4::network::protocol::DEFAULT_PORT;
+use card_game_4::network::{NetworkRole, NetworkSession};
+use card_game_4::prelude::*;
+use card_game_4::ui::hot_seat::HotSeat;
+use card_game_4::ui::main_menu::{GameConfig, PlayerKind};
+use card_game_4::utils::ray_caster::RayCasterPlugin;
+use card_game_4::{cards, network, players, states, ui, utils};
 // use bevy_mod_billboard::prelude::BillboardPlugin;
-use crate::utils::font3d::Font3DPlugin;
 use bevy_rapier3d::prelude::{NoUserData, RapierPhysicsPlugin};
-
-mod cards;
-mod game;
-mod players;
-mod stacks;
-mod states;
-mod ui;
-mod utils;
-
-pub mod prelude {
-    pub use super::cards::prelude::*;
-    pub use super::stacks::*;
-    pub use super::states::app::AppStates;
-    pub use super::utils::filter_enum::FilterEnumInserter;
-    pub use super::utils::ray_caster::RayCaster;
-    pub use bevy::prelude::*;
-}
+use card_game_4::utils::font3d::Font3DPlugin;
 
 pub fn spawn_camera(mut commands: Commands) {
     commands.spawn((
@@ -51,32 +43,143 @@ pub fn spawn_light(mut commands: Commands) {
 }
 
 fn main() {
-    App::new()
-        .add_plugins((
-            DefaultPlugins,
-            // BillboardPlugin,
-            RapierPhysicsPlugin::<NoUserData>::default(),
-            RayCasterPlugin,
-            cards::CardsPlugin,
-            players::PlayerPlugin,
-            StacksPlugin,
-            states::StatesPlugin,
-            GamePlugin,
-            utils::debug::DebugPlugin,
-            ui::UIPlugin,
-            Font3DPlugin,
-        ))
-        .add_systems(Startup, (spawn_camera, spawn_light))
-        .add_systems(
-            OnEnter(AppStates::Playing),
-            |mut events: ResMut<GameEvent>| {
-                events.push(GameEvents::Setup {
-                    seed: 0,
-                    set: 0,
-                    players: 2,
-                    starting_player: 0,
-                });
-            },
-        )
-        .run();
+    //--replay <path> opens a recorded game instead of starting a new one
+    //--host [address] waits for a second player, --join <address> joins them
+    //--server [address] plays a seat of a game run by the server binary (src/bin/server.rs)
+    //--bot <player> lets the program play this seat, --no-bot leaves every seat to humans
+    //--mcts <milliseconds> makes the bot search that long before each input instead of following rules
+    //--hot-seat passes the device between the humans at each turn (add --bot after it to keep one)
+    //--set <id|name> plays with another set of cards (see assets/sets)
+    let mut args = std::env::args().skip(1);
+    let mut replay = ReplayFile::default();
+    let mut session = None;
+    let mut server = None;
+    let mut setup = GameSetup::default();
+    let mut bots = Bots::default();
+    let mut hot_seat = HotSeat::default();
+    let mut bot_kind = BotKind::Heuristic;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--replay" => replay.0 = args.next().map(Into::into),
+            "--host" => {
+                let addr = args
+                    .next()
+                    .unwrap_or_else(|| format!("0.0.0.0:{DEFAULT_PORT}"));
+                setup.seed = rand::random();
+                session = Some(NetworkSession::host(addr));
+            }
+            "--join" => {
+                let addr = args
+                    .next()
+                    .unwrap_or_else(|| format!("127.0.0.1:{DEFAULT_PORT}"));
+                session = Some(NetworkSession::join(addr));
+            }
+            "--server" => {
+                let addr = args
+                    .next()
+                    .unwrap_or_else(|| format!("127.0.0.1:{DEFAULT_PORT}"));
+                server = Some(ServerGame::join(addr));
+            }
+            "--bot" => {
+                bots.seats = args
+                    .next()
+                    .and_then(|p| p.parse().ok())
+                    .into_iter()
+                    .collect()
+            }
+            "--no-bot" => bots.seats.clear(),
+            "--hot-seat" => {
+                hot_seat.enabled = true;
+                bots.seats.clear();
+            }
+            "--set" => {
+                let key = args.next().unwrap_or_default();
+                match SetCatalog::discover("assets").find(&key) {
+                    Some(set) => setup.set = set.manifest.id,
+                    None => eprintln!("unknown set: {key}"),
+                }
+            }
+            "--mcts" => {
+                let time = args.next().and_then(|t| t.parse().ok()).unwrap_or(1000);
+                bot_kind = BotKind::Mcts(Budget::Time(std::time::Duration::from_millis(time)));
+                bots.set_bot(bot_kind.build(rand::random()));
+            }
+            _ => {}
+        }
+    }
+    //network games are played by humans, each on their own device
+    if session.is_some() || server.is_some() {
+        bots.seats.clear();
+        hot_seat.enabled = false;
+    }
+    //the options above are the defaults of the main menu, network games skip it
+    let mut config = GameConfig::new(
+        (0..setup.players)
+            .map(|p| {
+                if bots.plays(p) {
+                    PlayerKind::Bot
+                } else {
+                    PlayerKind::Human
+                }
+            })
+            .collect(),
+        bot_kind,
+    );
+    config.set = setup.set;
+    let mut app = App::new();
+    match session {
+        Some(Ok(session)) => {
+            app.insert_resource(session);
+        }
+        Some(Err(err)) => {
+            eprintln!("could not start the network game: {err}");
+            return;
+        }
+        None => {}
+    }
+    match server {
+        Some(Ok(server)) => {
+            app.insert_resource(server);
+        }
+        Some(Err(err)) => {
+            eprintln!("could not join the server: {err}");
+            return;
+        }
+        None => {}
+    }
+    app.add_plugins((
+        DefaultPlugins,
+        // BillboardPlugin,
+        RapierPhysicsPlugin::<NoUserData>::default(),
+        RayCasterPlugin,
+        cards::CardsPlugin,
+        players::PlayerPlugin,
+        StacksPlugin,
+        states::StatesPlugin,
+        GamePlugin,
+        utils::debug::DebugPlugin,
+        ui::UIPlugin,
+        Font3DPlugin,
+        network::NetworkPlugin,
+        BotPlugin,
+    ))
+    .insert_resource(replay)
+    .insert_resource(setup)
+    .insert_resource(bots)
+    .insert_resource(hot_seat)
+    .insert_resource(config)
+    .add_systems(Startup, (spawn_camera, spawn_light))
+    .add_systems(
+        OnEnter(AppStates::Playing),
+        |mut events: ResMut<GameEvent>,
+         setup: Res<GameSetup>,
+         session: Option<Res<NetworkSession>>,
+         server: Option<Res<ServerGame>>| {
+            //a guest waits for the setup of the host, a server client for the first view
+            if session.is_none_or(|s| s.role == NetworkRole::Host) && server.is_none() {
+                events.push(setup.event());
+            }
+        },
+    )
+    .run();
 }
@@ -0,0 +1,327 @@
+use crate::cards::components::kinds::BaseLife;
+use crate::cards::transition::CardStateSnapshot;
+use crate::engine::replay::ReplayInput;
+use crate::engine::view::PlayerView;
+use crate::engine::Decision;
+use crate::game::events::{GameEvent, GameSetup};
+use crate::game::history::{board_at_rest, BoardSnapshot};
+use crate::game::routines::card_action::{Selectable, Selected, Selection};
+use crate::game::GameStates;
+use crate::network::protocol::NetworkError;
+use crate::network::server::ServerClient;
+use crate::players::LocalPlayer;
+use crate::prelude::*;
+use crate::states::turn::TurnStates;
+use crate::ui::choice_ui::{spawn_choices, ChoiceButton, ChoiceButtonNone, ChoiceRoot};
+use crate::ui::SelectionValidationButton;
+use bevy::ecs::system::CommandQueue;
+use std::net::ToSocketAddrs;
+
+//the app playing against an authoritative server: it never runs the rules itself. the events of
+//the local player are sent to the server as intents (see event_handler_dispatcher) and the board
+//is moved to each view the server sends back
+#[derive(Resource)]
+pub struct ServerGame {
+    client: Option<ServerClient>, //None once the connection is lost
+    view: Option<PlayerView>,     //last view received, kept once the server said goodbye
+    set_up: bool,                 //the setup event spawning the cards was pushed
+    shown: bool,                  //the board shows the last view received
+    asked: Option<Decision>,      //decision of the local player open in the ui
+}
+
+impl ServerGame {
+    pub fn join(addr: impl ToSocketAddrs) -> Result<Self, NetworkError> {
+        Ok(Self {
+            client: Some(ServerClient::join(addr)?),
+            view: None,
+            set_up: false,
+            shown: false,
+            asked: None,
+        })
+    }
+
+    pub fn connected(&self) -> bool {
+        self.client.is_some()
+    }
+
+    //last view received, None before the game starts
+    pub fn view(&self) -> Option<&PlayerView> {
+        self.view.as_ref()
+    }
+
+    pub fn submit(&mut self, input: ReplayInput) {
+        let Some(client) = &mut self.client else {
+            return;
+        };
+        if let Err(err) = client.submit(input) {
+            warn!("server game interrupted: {err}");
+            self.client = None;
+        }
+    }
+}
+
+pub fn sync_server(world: &mut World) {
+    let Some(mut game) = world.remove_resource::<ServerGame>() else {
+        return;
+    };
+    if let Err(err) = receive_views(world, &mut game) {
+        warn!("server game interrupted: {err}");
+        game.client = None;
+    }
+    send_answer(world, &mut game);
+    show_view(world, &mut game);
+    world.insert_resource(game);
+}
+
+fn receive_views(world: &mut World, game: &mut ServerGame) -> Result<(), NetworkError> {
+    let Some(client) = &mut game.client else {
+        return Ok(());
+    };
+    loop {
+        match client.poll() {
+            Ok(true) => {
+                game.view = Some(client.view.clone());
+                game.shown = false;
+            }
+            Ok(false) => break,
+            //the board did not move, the decision that was answered is asked again
+            Err(NetworkError::Refused { input, reason }) => {
+                warn!("the server refused {input}: {reason}");
+                game.shown = false;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    if client.greeted() && world.resource::<LocalPlayer>().0 != client.player {
+        world.resource_mut::<LocalPlayer>().0 = client.player;
+    }
+    Ok(())
+}
+
+//the first view sets up a game of the same set, mode and players: the cards are spawned in the
+//same order, so they get the ids the server uses. the seed only places them until the view does
+fn show_view(world: &mut World, game: &mut ServerGame) {
+    let Some(view) = game.view().cloned() else {
+        return;
+    };
+    if !game.set_up {
+        let setup = GameSetup {
+            seed: 0,
+            set: view.set,
+            players: view.players.len() as u8,
+            starting_player: match view.turn {
+                TurnStates::PlayerTurn(player) => player,
+                _ => 0,
+            },
+            mode: view.mode,
+        };
+        world.resource_mut::<GameEvent>().push(setup.event());
+        game.set_up = true;
+        return;
+    }
+    if game.shown
+        || *world.resource::<State<TurnStates>>().get() == TurnStates::Setup
+        || !board_at_rest(world)
+    {
+        return;
+    }
+    close_decision(world, game);
+    view_board(world, &view).restore(world);
+    if *world.resource::<State<TurnStates>>().get() != view.turn {
+        world.resource_mut::<NextState<TurnStates>>().set(view.turn);
+    }
+    open_decision(world, game, view.pending);
+    game.shown = true;
+}
+
+//the board of the app moved to the view: the cards seen go where the view shows them, the others
+//fill the hidden stacks (staying in their stack when it is still one of them) face down
+fn view_board(world: &mut World, view: &PlayerView) -> BoardSnapshot {
+    let mut board = BoardSnapshot::capture(world);
+    let mut slots: Vec<(CardOwners, Stacks, Vec<usize>)> = view
+        .hidden
+        .iter()
+        .map(|hidden| {
+            let seen: Vec<usize> = view
+                .stack(hidden.owner, hidden.stack)
+                .iter()
+                .map(|card| card.index)
+                .collect();
+            let free = (0..)
+                .filter(|index| !seen.contains(index))
+                .take(hidden.count)
+                .collect();
+            (hidden.owner, hidden.stack, free)
+        })
+        .collect();
+    let mut unseen = Vec::new();
+    for (position, card) in board.cards.iter_mut().enumerate() {
+        let Some(seen) = view.card(card.id.0) else {
+            unseen.push(position);
+            continue;
+        };
+        card.state = CardStateSnapshot {
+            owner: seen.owner,
+            stack: seen.stack,
+            index: CardIndex(seen.index),
+            visibility: CardVisibility::Visible,
+        };
+        if let Some(life) = seen.life {
+            card.base_life = Some(BaseLife(life));
+        }
+    }
+    let mut take = |owner: Option<(CardOwners, Stacks)>| {
+        let (owner, stack, free) = slots
+            .iter_mut()
+            .find(|(o, s, free)| owner.is_none_or(|k| k == (*o, *s)) && !free.is_empty())?;
+        Some((*owner, *stack, free.remove(0)))
+    };
+    let mut left = Vec::new();
+    for position in unseen {
+        let state = board.cards[position].state;
+        match take(Some((state.owner, state.stack))) {
+            Some(slot) => hide(&mut board.cards[position].state, slot),
+            None => left.push(position),
+        }
+    }
+    for position in left {
+        if let Some(slot) = take(None) {
+            hide(&mut board.cards[position].state, slot);
+        }
+    }
+    for player in board.players.iter_mut() {
+        if let Some(counters) = view.players.get(player.player as usize) {
+            player.life = counters.life;
+            player.attack = counters.attack;
+            player.economy = counters.economy;
+        }
+    }
+    board
+}
+
+fn hide(state: &mut CardStateSnapshot, (owner, stack, index): (CardOwners, Stacks, usize)) {
+    *state = CardStateSnapshot {
+        owner,
+        stack,
+        index: CardIndex(index),
+        visibility: CardVisibility::Hidden,
+    };
+}
+
+//the selection or the choice is shown as if a routine asked for it
+fn open_decision(world: &mut World, game: &mut ServerGame, decision: Option<Decision>) {
+    match &decision {
+        Some(Decision::Selection {
+            player,
+            min,
+            max,
+            cards,
+        }) => {
+            let mut selection = world.resource_mut::<Selection>();
+            selection.finished = false;
+            selection.cards = Vec::new();
+            selection.min = *min;
+            selection.max = *max;
+            selection.player = *player;
+            let selectable: Vec<Entity> = world
+                .query::<(Entity, &CardId)>()
+                .iter(world)
+                .filter(|(_, id)| cards.contains(&id.0))
+                .map(|(entity, _)| entity)
+                .collect();
+            for entity in selectable {
+                world.entity_mut(entity).insert(Selectable);
+            }
+            for mut visibility in world
+                .query_filtered::<&mut Visibility, With<SelectionValidationButton>>()
+                .iter_mut(world)
+            {
+                *visibility = Visibility::Visible;
+            }
+            world
+                .resource_mut::<NextState<GameStates>>()
+                .set(GameStates::SelectionInput);
+        }
+        Some(Decision::Choice { player, set }) => {
+            let mut queue = CommandQueue::default();
+            let mut commands = Commands::new(&mut queue, world);
+            spawn_choices(&mut commands, *player, set);
+            queue.apply(world);
+            world
+                .resource_mut::<NextState<GameStates>>()
+                .set(GameStates::ChoiceInput);
+        }
+        None => {}
+    }
+    game.asked = decision;
+}
+
+fn close_decision(world: &mut World, game: &mut ServerGame) {
+    if game.asked.take().is_none() {
+        return;
+    }
+    let marked: Vec<Entity> = world
+        .query_filtered::<Entity, Or<(With<Selectable>, With<Selected>)>>()
+        .iter(world)
+        .collect();
+    for entity in marked {
+        world
+            .entity_mut(entity)
+            .remove::<Selectable>()
+            .remove::<Selected>();
+    }
+    for mut visibility in world
+        .query_filtered::<&mut Visibility, With<SelectionValidationButton>>()
+        .iter_mut(world)
+    {
+        *visibility = Visibility::Hidden;
+    }
+    let roots: Vec<Entity> = world
+        .query_filtered::<Entity, With<ChoiceRoot>>()
+        .iter(world)
+        .collect();
+    for root in roots {
+        world.entity_mut(root).despawn_recursive();
+    }
+    world
+        .resource_mut::<NextState<GameStates>>()
+        .set(GameStates::MainLoop);
+}
+
+//once the local player validated the selection or the choice, it is sent to the server
+fn send_answer(world: &mut World, game: &mut ServerGame) {
+    let answer = match &game.asked {
+        Some(Decision::Selection { .. }) if world.resource::<Selection>().finished => {
+            let mut cards: Vec<usize> = world
+                .query_filtered::<&CardId, With<Selected>>()
+                .iter(world)
+                .map(|id| id.0)
+                .collect();
+            cards.sort();
+            ReplayInput::Select(cards)
+        }
+        Some(Decision::Choice { .. }) => {
+            let Ok(root) = world.query::<&ChoiceRoot>().get_single(world) else {
+                return;
+            };
+            if !root.finished {
+                return;
+            }
+            let nothing = world
+                .query::<&ChoiceButtonNone>()
+                .get_single(world)
+                .is_ok_and(|button| button.selected);
+            let mut choices: Vec<u8> = world
+                .query::<&ChoiceButton>()
+                .iter(world)
+                .filter(|button| button.selected && !nothing)
+                .map(|button| button.index)
+                .collect();
+            choices.sort();
+            ReplayInput::Choose(choices)
+        }
+        _ => return,
+    };
+    close_decision(world, game);
+    game.submit(answer);
+}
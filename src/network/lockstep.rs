@@ -0,0 +1,174 @@
+use crate::engine::hash::CanonicalBoard;
+use crate::engine::replay::{Replay, ReplayInput};
+use crate::engine::{CardLibrary, Effect, GameState};
+use crate::game::events::GameEvents;
+use crate::network::protocol::{
+    check_hello, Connection, NetMessage, NetworkError, CLOSE_TIMEOUT, PROTOCOL_VERSION,
+};
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+//headless two player client: each side runs the engine and only sends its own inputs, the
+//inputs of the other side are validated by the local engine before being applied. each input is
+//followed by the hash of the board it led to, so both sides know they still agree
+pub struct LockstepClient {
+    pub state: GameState,
+    pub player: u8,
+    pub replay: Replay, //every input played, from both sides
+    library: Arc<CardLibrary>,
+    connection: Connection,
+}
+
+impl LockstepClient {
+    //wait for a guest, then send the setup (the host is player 0)
+    pub fn host(
+        listener: &TcpListener,
+        library: Arc<CardLibrary>,
+        setup: GameEvents,
+        timeout: Duration,
+    ) -> Result<Self, NetworkError> {
+        let (stream, _) = listener.accept()?;
+        let mut connection = Connection::new(stream)?;
+        connection.send(&NetMessage::Hello {
+            version: PROTOCOL_VERSION,
+            player: Some(1),
+        })?;
+        check_hello(connection.wait(timeout)?)?;
+        let mut client = Self {
+            state: GameState::new(library.clone()),
+            player: 0,
+            library,
+            replay: Replay::default(),
+            connection,
+        };
+        client.apply(ReplayInput::Event(setup))?;
+        client.send(ReplayInput::Event(setup))?;
+        Ok(client)
+    }
+
+    //connect to a host and wait for the setup
+    pub fn join(
+        addr: impl std::net::ToSocketAddrs,
+        library: Arc<CardLibrary>,
+        timeout: Duration,
+    ) -> Result<Self, NetworkError> {
+        let mut connection = Connection::connect(addr)?;
+        connection.send(&NetMessage::Hello {
+            version: PROTOCOL_VERSION,
+            player: None,
+        })?;
+        let player = check_hello(connection.wait(timeout)?)?.unwrap_or(1);
+        let mut client = Self {
+            state: GameState::new(library.clone()),
+            player,
+            library,
+            replay: Replay::default(),
+            connection,
+        };
+        match client.connection.wait(timeout)? {
+            NetMessage::Input(input @ ReplayInput::Event(GameEvents::Setup { .. })) => {
+                client.apply(input)?;
+            }
+            message => return Err(NetworkError::Unexpected(message)),
+        }
+        Ok(client)
+    }
+
+    //true if the next input (other than a concession) is ours to give
+    pub fn our_turn(&self) -> bool {
+        let player = match self.state.pending() {
+            Some(decision) => Some(decision.player()),
+            None => self.state.current_player(),
+        };
+        player == Some(self.player)
+    }
+
+    //play one of our inputs and send it, inputs refused by the rules are not sent
+    pub fn play(&mut self, input: ReplayInput) -> Result<Vec<Effect>, NetworkError> {
+        if self.state.input_player(&input) != Some(self.player) {
+            return Err(NetworkError::NotYourTurn(self.player));
+        }
+        let effects = self.replay.record(&mut self.state, input.clone())?;
+        self.send(input)?;
+        Ok(effects)
+    }
+
+    //apply the next input of the other side if one was received
+    pub fn poll(&mut self) -> Result<Option<Vec<Effect>>, NetworkError> {
+        while let Some(message) = self.connection.poll()? {
+            if let Some(effects) = self.receive(message)? {
+                return Ok(Some(effects));
+            }
+        }
+        Ok(None)
+    }
+
+    //wait for the next input of the other side
+    pub fn wait(&mut self, timeout: Duration) -> Result<Vec<Effect>, NetworkError> {
+        let start = Instant::now();
+        loop {
+            let message = self
+                .connection
+                .wait(timeout.saturating_sub(start.elapsed()))?;
+            if let Some(effects) = self.receive(message)? {
+                return Ok(effects);
+            }
+        }
+    }
+
+    pub fn leave(mut self) -> Result<(), NetworkError> {
+        self.connection.send(&NetMessage::Bye)?;
+        self.connection.close(CLOSE_TIMEOUT)
+    }
+
+    //send one of our inputs, with the hash of the board after it
+    fn send(&mut self, input: ReplayInput) -> Result<(), NetworkError> {
+        self.connection.send(&NetMessage::Input(input))?;
+        self.connection.send(&NetMessage::Hash {
+            step: self.replay.inputs.len() - 1,
+            hash: self.state.hash(),
+        })
+    }
+
+    //the effects of the input of the other side, None for the messages checking the boards
+    fn receive(&mut self, message: NetMessage) -> Result<Option<Vec<Effect>>, NetworkError> {
+        match message {
+            NetMessage::Input(input) => {
+                if self.state.input_player(&input) == Some(self.player) {
+                    return Err(NetworkError::Desync(input));
+                }
+                self.apply(input).map(Some)
+            }
+            //on a mismatch our board goes back to the other side, which can tell what differs
+            NetMessage::Hash { step, hash } => {
+                if self.replay.hashes.get(step) == Some(&hash) {
+                    return Ok(None);
+                }
+                let board = self.board(step)?;
+                self.connection.send(&NetMessage::Board { step, board })?;
+                self.connection.close(CLOSE_TIMEOUT)?;
+                Err(NetworkError::HashMismatch(step))
+            }
+            NetMessage::Board { step, board } => Err(NetworkError::Diverged {
+                step,
+                diff: self.board(step)?.diff(&board),
+            }),
+            NetMessage::Bye => Err(NetworkError::Closed),
+            message => Err(NetworkError::Unexpected(message)),
+        }
+    }
+
+    fn board(&self, step: usize) -> Result<CanonicalBoard, NetworkError> {
+        match self.replay.seek(self.library.clone(), step) {
+            Ok(state) => Ok(state.canonical()),
+            Err(_) => Err(NetworkError::HashMismatch(step)),
+        }
+    }
+
+    fn apply(&mut self, input: ReplayInput) -> Result<Vec<Effect>, NetworkError> {
+        self.replay
+            .record(&mut self.state, input.clone())
+            .map_err(|_| NetworkError::Desync(input))
+    }
+}
@@ -0,0 +1,402 @@
+pub mod client;
+pub mod lockstep;
+pub mod protocol;
+pub mod server;
+
+use self::client::{sync_server, ServerGame};
+use crate::cards::transition::{CardTransition, StartTransition};
+use crate::engine::hash::{BoardDiff, CanonicalBoard};
+use crate::engine::replay::ReplayInput;
+use crate::game::events::{Answers, GameEvent, GameEvents};
+use crate::game::history::BoardSnapshot;
+use crate::game::routines::card_action::Selection;
+use crate::game::routines::selection::{answer_selection, selection_open};
+use crate::game::routines::RoutineManager;
+use crate::game::GameStates;
+use crate::players::LocalPlayer;
+use crate::prelude::*;
+use crate::states::turn::TurnStates;
+use crate::ui::choice_ui::{answer_choice, ChoiceRoot};
+use protocol::{
+    check_hello, Connection, NetMessage, NetworkError, CLOSE_TIMEOUT, PROTOCOL_VERSION,
+};
+use std::collections::VecDeque;
+use std::net::{SocketAddr, TcpListener, ToSocketAddrs};
+
+#[derive(Debug, Reflect, Copy, Clone, Eq, PartialEq)]
+pub enum NetworkRole {
+    Host, //player 0, sends the setup
+    Guest,
+}
+
+impl NetworkRole {
+    //seat played by this side
+    pub fn player(&self) -> u8 {
+        match self {
+            NetworkRole::Host => 0,
+            NetworkRole::Guest => 1,
+        }
+    }
+
+    pub fn other(&self) -> Self {
+        match self {
+            NetworkRole::Host => NetworkRole::Guest,
+            NetworkRole::Guest => NetworkRole::Host,
+        }
+    }
+}
+
+//two player game in lockstep: both apps run every event and routine, each one only sends what its
+//local player did (events, selections and choices) and replays what the other one sent
+#[derive(Resource)]
+pub struct NetworkSession {
+    pub role: NetworkRole,
+    listener: Option<TcpListener>,
+    connection: Option<Connection>,
+    greeted: bool,
+    events: usize,  //entries of the event log already sent or received
+    answers: usize, //entries of Answers already sent or received
+    incoming: VecDeque<ReplayInput>,
+    hashed: usize, //inputs played when the board was last hashed
+    boards: VecDeque<(usize, CanonicalBoard)>, //our last hashed boards, by step
+    theirs: VecDeque<(usize, u64)>, //hashes of the other side not compared yet
+    pub desync: Option<usize>, //step after which the two boards differed
+    pub diff: Option<BoardDiff>, //what differed, once both boards were exchanged
+}
+
+//boards kept to compare with hashes of the other side arriving late
+const KEPT_BOARDS: usize = 64;
+
+impl NetworkSession {
+    fn new(
+        role: NetworkRole,
+        listener: Option<TcpListener>,
+        connection: Option<Connection>,
+    ) -> Self {
+        Self {
+            role,
+            listener,
+            connection,
+            greeted: false,
+            events: 0,
+            answers: 0,
+            incoming: VecDeque::new(),
+            hashed: 0,
+            boards: VecDeque::new(),
+            theirs: VecDeque::new(),
+            desync: None,
+            diff: None,
+        }
+    }
+
+    pub fn host(addr: impl ToSocketAddrs) -> Result<Self, NetworkError> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self::new(NetworkRole::Host, Some(listener), None))
+    }
+
+    pub fn join(addr: impl ToSocketAddrs) -> Result<Self, NetworkError> {
+        let mut connection = Connection::connect(addr)?;
+        connection.send(&NetMessage::Hello {
+            version: PROTOCOL_VERSION,
+            player: None,
+        })?;
+        Ok(Self::new(NetworkRole::Guest, None, Some(connection)))
+    }
+
+    //address the host listens on (to find the port it was given when binding port 0)
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.listener.as_ref()?.local_addr().ok()
+    }
+
+    //the game stops as soon as the boards differ, while they are exchanged
+    pub fn connected(&self) -> bool {
+        self.connection.is_some() && self.greeted && self.desync.is_none()
+    }
+
+    fn send(&mut self, message: &NetMessage) -> Result<(), NetworkError> {
+        match &mut self.connection {
+            Some(connection) => connection.send(message),
+            None => Err(NetworkError::Closed),
+        }
+    }
+    fn board(&self, step: usize) -> Option<&CanonicalBoard> {
+        self.boards
+            .iter()
+            .find(|(s, _)| *s == step)
+            .map(|(_, board)| board)
+    }
+}
+
+//without a network session the local player can always act, as before
+pub fn local_input_allowed(
+    session: Option<Res<NetworkSession>>,
+    turn: Res<State<TurnStates>>,
+    local_player: Res<LocalPlayer>,
+) -> bool {
+    session.is_none_or(|s| s.connected() && *turn.get() == TurnStates::PlayerTurn(local_player.0))
+}
+
+pub fn network_connected(session: Option<Res<NetworkSession>>) -> bool {
+    session.is_none_or(|s| s.connected())
+}
+
+fn receive(world: &mut World, session: &mut NetworkSession) -> Result<(), NetworkError> {
+    if let Some(listener) = &session.listener {
+        if session.connection.is_none() {
+            if let Ok((stream, _)) = listener.accept() {
+                let mut connection = Connection::new(stream)?;
+                connection.send(&NetMessage::Hello {
+                    version: PROTOCOL_VERSION,
+                    player: Some(NetworkRole::Guest.player()),
+                })?;
+                session.connection = Some(connection);
+            }
+        }
+    }
+    let Some(connection) = &mut session.connection else {
+        return Ok(());
+    };
+    while let Some(message) = connection.poll()? {
+        match message {
+            hello @ NetMessage::Hello { .. } if !session.greeted => {
+                if let Some(player) = check_hello(hello)? {
+                    world.resource_mut::<LocalPlayer>().0 = player;
+                }
+                session.greeted = true;
+            }
+            NetMessage::Input(input) if session.greeted => session.incoming.push_back(input),
+            NetMessage::Hash { step, hash } if session.greeted => {
+                session.theirs.push_back((step, hash))
+            }
+            //the boards differ and the other side tells what it has. the side that found it
+            //first gets our board in return, so both can show what differs
+            NetMessage::Board { step, board } if session.greeted => {
+                let reply = session.desync.is_none();
+                session.desync = Some(step);
+                let Some(ours) = session.board(step).cloned() else {
+                    return Err(NetworkError::HashMismatch(step));
+                };
+                let diff = ours.diff(&board);
+                session.diff = Some(diff.clone());
+                if reply {
+                    session.send(&NetMessage::Board { step, board: ours })?;
+                    if let Some(connection) = &mut session.connection {
+                        connection.close(CLOSE_TIMEOUT)?;
+                    }
+                }
+                return Err(NetworkError::Diverged { step, diff });
+            }
+            NetMessage::Bye => return Err(NetworkError::Closed),
+            message => return Err(NetworkError::Unexpected(message)),
+        }
+    }
+    Ok(())
+}
+
+//send what the local player added to the event log and the answers since the last frame. events
+//are only sent once their handler accepted them, the refused ones are removed from the log
+fn send_local(world: &mut World, session: &mut NetworkSession) -> Result<(), NetworkError> {
+    if !session.greeted {
+        return Ok(());
+    }
+    let log = world.resource::<GameEvent>();
+    let processed = log.processed();
+    session.events = session.events.min(log.len());
+    let events: Vec<_> = log.log()[session.events.min(processed)..processed].to_vec();
+    session.events = session.events.max(processed);
+    for event in events {
+        session.send(&NetMessage::Input(ReplayInput::Event(event)))?;
+    }
+    let local = world.resource::<LocalPlayer>().0;
+    let answers = world.resource::<Answers>();
+    session.answers = session.answers.min(answers.0.len());
+    let local_answers: Vec<_> = answers.0[session.answers..]
+        .iter()
+        .filter(|(player, _)| *player == local)
+        .map(|(_, input)| input.clone())
+        .collect();
+    session.answers = answers.0.len();
+    for input in local_answers {
+        session.send(&NetMessage::Input(input))?;
+    }
+    Ok(())
+}
+
+//the board is hashed each time it settles after new inputs (steps are counted as in a replay) and
+//compared with the hash the other side sent for the same step. inputs played before the board
+//settled are not compared on their own. on a mismatch our board is sent to tell what differs
+fn check_hashes(world: &mut World, session: &mut NetworkSession) -> Result<(), NetworkError> {
+    if !session.greeted {
+        return Ok(());
+    }
+    let played = world.resource::<GameEvent>().len() + world.resource::<Answers>().0.len();
+    if played > session.hashed && board_settled(world) {
+        let board = CanonicalBoard::from(&BoardSnapshot::capture(world));
+        session.hashed = played;
+        session.send(&NetMessage::Hash {
+            step: played - 1,
+            hash: board.hash(),
+        })?;
+        session.boards.push_back((played - 1, board));
+        if session.boards.len() > KEPT_BOARDS {
+            session.boards.pop_front();
+        }
+    }
+    while let Some(&(step, hash)) = session.theirs.front() {
+        if step >= session.hashed {
+            break;
+        }
+        session.theirs.pop_front();
+        let Some(ours) = session.board(step) else {
+            continue;
+        };
+        //our board is sent, the connection stays open for the board of the other side
+        if ours.hash() != hash {
+            let board = ours.clone();
+            session.desync = Some(step);
+            session.send(&NetMessage::Board { step, board })?;
+            warn!(
+                "network game interrupted: {}",
+                NetworkError::HashMismatch(step)
+            );
+            return Ok(());
+        }
+    }
+    Ok(())
+}
+
+//the board the inputs led to: no card moves and the game waits for an event or an answer
+fn board_settled(world: &mut World) -> bool {
+    let waiting = match world.resource::<State<GameStates>>().get() {
+        GameStates::MainLoop => world.resource::<RoutineManager>().is_empty(),
+        GameStates::SelectionInput => selection_open(world),
+        GameStates::ChoiceInput => world
+            .query::<&ChoiceRoot>()
+            .get_single(world)
+            .is_ok_and(|root| !root.finished),
+    };
+    waiting
+        && world.resource::<GameEvent>().get_unprocessed().is_none()
+        && world.resource::<NextState<TurnStates>>().0.is_none()
+        && world.resource::<NextState<GameStates>>().0.is_none()
+        && world
+            .query_filtered::<(), Or<(With<CardTransition>, With<StartTransition>)>>()
+            .iter(world)
+            .next()
+            .is_none()
+}
+
+//inputs of the other player are given back in order, each one waiting for the board to ask for it.
+//the other player only gives the inputs of its own seat, anything else means the boards differ
+fn apply_remote(world: &mut World, session: &mut NetworkSession) -> Result<(), NetworkError> {
+    let remote = session.role.other().player();
+    while let Some(input) = session.incoming.front() {
+        match input {
+            ReplayInput::Event(event) => {
+                let allowed = match event {
+                    GameEvents::Concede(player) => *player == remote,
+                    GameEvents::Setup { .. } => {
+                        session.role == NetworkRole::Guest
+                            && *world.resource::<State<TurnStates>>().get() == TurnStates::Setup
+                    }
+                    //the turn of the other player may not have started here yet
+                    _ if !turn_settled(world) => return Ok(()),
+                    _ => {
+                        *world.resource::<State<TurnStates>>().get()
+                            == TurnStates::PlayerTurn(remote)
+                    }
+                };
+                if !allowed {
+                    return Err(NetworkError::Desync(input.clone()));
+                }
+                let mut events = world.resource_mut::<GameEvent>();
+                events.push(*event);
+                session.events = events.len();
+            }
+            ReplayInput::Select(cards) => {
+                if !selection_open(world) {
+                    return Ok(());
+                }
+                if world.resource::<Selection>().player != remote {
+                    return Err(NetworkError::Desync(input.clone()));
+                }
+                answer_selection(world, cards);
+            }
+            ReplayInput::Choose(choices) => {
+                let Ok(root) = world.query::<&ChoiceRoot>().get_single(world) else {
+                    return Ok(());
+                };
+                if root.finished {
+                    return Ok(());
+                }
+                if root.player != remote {
+                    return Err(NetworkError::Desync(input.clone()));
+                }
+                answer_choice(world, choices);
+            }
+        }
+        session.incoming.pop_front();
+    }
+    Ok(())
+}
+
+//every event already received was played and the turn that follows them has started
+fn turn_settled(world: &World) -> bool {
+    world.resource::<RoutineManager>().is_empty()
+        && world.resource::<GameEvent>().get_unprocessed().is_none()
+        && world.resource::<NextState<TurnStates>>().0.is_none()
+        && !matches!(
+            world.resource::<State<TurnStates>>().get(),
+            TurnStates::PlayerCleanup(_)
+        )
+}
+
+pub fn sync_network(world: &mut World) {
+    let Some(mut session) = world.remove_resource::<NetworkSession>() else {
+        return;
+    };
+    //once the boards differ, only the board of the other side is waited for
+    let result = receive(world, &mut session).and_then(|_| match session.desync {
+        Some(_) => Ok(()),
+        None => send_local(world, &mut session)
+            .and_then(|_| check_hashes(world, &mut session))
+            .and_then(|_| apply_remote(world, &mut session)),
+    });
+    if let Err(err) = result {
+        warn!("network game interrupted: {err}");
+        session.connection = None;
+        session.listener = None;
+        session.incoming.clear();
+    }
+    world.insert_resource(session);
+}
+
+//write what the connection could not send at once, without waiting for the other side
+pub fn flush_network(mut session: ResMut<NetworkSession>) {
+    let Some(connection) = &mut session.connection else {
+        return;
+    };
+    if let Err(err) = connection.flush() {
+        warn!("network game interrupted: {err}");
+        session.connection = None;
+        session.listener = None;
+        session.incoming.clear();
+    }
+}
+
+pub struct NetworkPlugin;
+
+impl Plugin for NetworkPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<NetworkRole>().add_systems(
+            PostUpdate,
+            (
+                (sync_network, flush_network)
+                    .chain()
+                    .run_if(resource_exists::<NetworkSession>),
+                sync_server.run_if(resource_exists::<ServerGame>),
+            ),
+        );
+    }
+}
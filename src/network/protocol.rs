@@ -0,0 +1,207 @@
+use crate::engine::hash::{BoardDiff, CanonicalBoard};
+use crate::engine::replay::ReplayInput;
+use crate::engine::view::PlayerView;
+use crate::engine::RuleError;
+use crate::game::events::GameEvents;
+use serde::{Deserialize, Serialize};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+//bumped whenever the messages or the rules change in a way that would desync two clients
+pub const PROTOCOL_VERSION: u32 = 5;
+
+pub const DEFAULT_PORT: u16 = 7878;
+
+//longest line accepted, the other side is dropped when it sends more without ending the line
+pub const MAX_LINE_LENGTH: usize = 1 << 20;
+
+//messages are sent as one line of RON each
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub enum NetMessage {
+    //first message of both sides, the host also gives the seat of the guest
+    Hello { version: u32, player: Option<u8> },
+    //an event (the first one being the setup), or the answer to a selection or a choice
+    //(sent to the authoritative server, they are intents it validates before playing them)
+    Input(ReplayInput),
+    //GameState::hash after the input of this index, sent after each input by lockstep clients
+    Hash { step: usize, hash: u64 },
+    //board after the input of this index, sent back when its hash did not match
+    Board { step: usize, board: CanonicalBoard },
+    //sent by the authoritative server after each input it played, redacted for each player
+    View(Box<PlayerView>),
+    //an input sent to the authoritative server was refused by the rules
+    Refused { input: ReplayInput, reason: String },
+    Bye,
+}
+
+#[derive(Debug, Error)]
+pub enum NetworkError {
+    #[error("connection error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not write RON: {0}")]
+    Ron(#[from] ron::Error),
+    #[error("could not parse RON: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("the connection was closed")]
+    Closed,
+    #[error("the other side sent a line longer than {MAX_LINE_LENGTH} bytes")]
+    LineTooLong,
+    #[error("timed out waiting for the other side")]
+    Timeout,
+    #[error("protocol version mismatch: {ours} here, {theirs} on the other side")]
+    Version { ours: u32, theirs: u32 },
+    #[error("unexpected message: {0:?}")]
+    Unexpected(NetMessage),
+    #[error("input {0} was refused, the clients are out of sync")]
+    Desync(ReplayInput),
+    #[error("input refused: {0}")]
+    Rule(#[from] RuleError),
+    #[error("the boards differ after input {0}")]
+    HashMismatch(usize),
+    #[error("the boards differ after input {step}:\n{diff}")]
+    Diverged { step: usize, diff: BoardDiff },
+    #[error("input {input} was refused by the server: {reason}")]
+    Refused { input: ReplayInput, reason: String },
+    #[error("player {0} can not give this input")]
+    NotYourTurn(u8),
+    #[error("event {0:?} is not legal now")]
+    Illegal(GameEvents),
+}
+
+//non blocking TCP stream split in messages, what could not be written yet is kept until the next
+//flush (the app flushes once per frame, the headless clients whenever they poll)
+pub struct Connection {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+    outgoing: Vec<u8>,
+}
+
+//how long a closing connection waits for what it still has to send
+pub const CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> Result<Self, NetworkError> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            stream,
+            buffer: Vec::new(),
+            outgoing: Vec::new(),
+        })
+    }
+
+    pub fn connect(addr: impl ToSocketAddrs) -> Result<Self, NetworkError> {
+        Self::new(TcpStream::connect(addr)?)
+    }
+
+    pub fn send(&mut self, message: &NetMessage) -> Result<(), NetworkError> {
+        self.outgoing
+            .extend_from_slice(ron::to_string(message)?.as_bytes());
+        self.outgoing.push(b'\n');
+        self.flush().map(|_| ())
+    }
+
+    //write what the socket accepts without blocking, true once nothing is left to send
+    pub fn flush(&mut self) -> Result<bool, NetworkError> {
+        let mut written = 0;
+        let result = loop {
+            if written == self.outgoing.len() {
+                break Ok(true);
+            }
+            match self.stream.write(&self.outgoing[written..]) {
+                Ok(0) => break Err(NetworkError::Closed),
+                Ok(n) => written += n,
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break Ok(false),
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => break Err(err.into()),
+            }
+        };
+        self.outgoing.drain(..written);
+        result
+    }
+
+    //block until everything was sent, before closing the connection
+    pub fn close(&mut self, timeout: Duration) -> Result<(), NetworkError> {
+        let start = Instant::now();
+        while !self.flush()? {
+            if start.elapsed() > timeout {
+                return Err(NetworkError::Timeout);
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        Ok(())
+    }
+
+    //next message if one was fully received, without blocking
+    pub fn poll(&mut self) -> Result<Option<NetMessage>, NetworkError> {
+        self.flush()?;
+        if let Some(message) = self.pop()? {
+            return Ok(Some(message));
+        }
+        //reading stops at the first complete line, so only the line being received is buffered
+        let mut chunk = [0; 4096];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return Err(NetworkError::Closed),
+                Ok(n) => {
+                    self.buffer.extend_from_slice(&chunk[..n]);
+                    if chunk[..n].contains(&b'\n') {
+                        break;
+                    }
+                    if self.buffer.len() > MAX_LINE_LENGTH {
+                        return Err(self.too_long());
+                    }
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        self.pop()
+    }
+
+    //block until the next message
+    pub fn wait(&mut self, timeout: Duration) -> Result<NetMessage, NetworkError> {
+        let start = Instant::now();
+        loop {
+            if let Some(message) = self.poll()? {
+                return Ok(message);
+            }
+            if start.elapsed() > timeout {
+                return Err(NetworkError::Timeout);
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    fn pop(&mut self) -> Result<Option<NetMessage>, NetworkError> {
+        let Some(end) = self.buffer.iter().position(|&b| b == b'\n') else {
+            return Ok(None);
+        };
+        if end > MAX_LINE_LENGTH {
+            return Err(self.too_long());
+        }
+        let line: Vec<u8> = self.buffer.drain(..=end).collect();
+        Ok(Some(ron::de::from_bytes(&line[..end])?))
+    }
+
+    fn too_long(&mut self) -> NetworkError {
+        self.buffer.clear();
+        let _ = self.stream.shutdown(Shutdown::Both);
+        NetworkError::LineTooLong
+    }
+}
+
+//both sides start with a hello, the version must match
+pub fn check_hello(message: NetMessage) -> Result<Option<u8>, NetworkError> {
+    match message {
+        NetMessage::Hello { version, player } if version == PROTOCOL_VERSION => Ok(player),
+        NetMessage::Hello { version, .. } => Err(NetworkError::Version {
+            ours: PROTOCOL_VERSION,
+            theirs: version,
+        }),
+        message => Err(NetworkError::Unexpected(message)),
+    }
+}
@@ -0,0 +1,270 @@
+use crate::engine::replay::{Replay, ReplayInput};
+use crate::engine::view::PlayerView;
+use crate::engine::{CardLibrary, GameState};
+use crate::game::events::GameEvents;
+use crate::network::protocol::{
+    check_hello, Connection, NetMessage, NetworkError, CLOSE_TIMEOUT, PROTOCOL_VERSION,
+};
+use std::io::ErrorKind;
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+//authoritative server: it owns the rng and the whole board, players only receive their own view
+//(see GameState::view) and send intents, which are played only if the rules engine accepts them
+pub struct Server {
+    pub state: GameState,
+    pub replay: Replay,
+    clients: Vec<Option<Connection>>, //by seat, None once the player left
+}
+
+impl Server {
+    //wait for one client per seat (in order of connection), then play the setup. a connection
+    //failing or not saying hello in time is dropped and the seat goes to the next one, the whole
+    //wait is given up after the deadline
+    pub fn start(
+        listener: &TcpListener,
+        library: Arc<CardLibrary>,
+        setup: GameEvents,
+        deadline: Duration,
+    ) -> Result<Self, NetworkError> {
+        let mut server = Self {
+            state: GameState::new(library),
+            replay: Replay::default(),
+            clients: Vec::new(),
+        };
+        server
+            .replay
+            .record(&mut server.state, ReplayInput::Event(setup))?;
+        let end = Instant::now() + deadline;
+        let seats = server.state.players().len();
+        listener.set_nonblocking(true)?;
+        while server.clients.len() < seats {
+            let remaining = end.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(NetworkError::Timeout);
+            }
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    //the seat stays free for the next connection if this one fails
+                    let seat = server.clients.len() as u8;
+                    if let Ok(connection) = greet(stream, seat, remaining.min(HELLO_TIMEOUT)) {
+                        server.clients.push(Some(connection));
+                    }
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                    std::thread::sleep(Duration::from_millis(1))
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+        listener.set_nonblocking(false)?;
+        server.broadcast();
+        Ok(server)
+    }
+
+    pub fn finished(&self) -> bool {
+        self.state.current_player().is_none() || self.clients.iter().all(Option::is_none)
+    }
+
+    //play the intents received since the last call, returns false once the game is over
+    pub fn poll(&mut self) -> bool {
+        for seat in 0..self.clients.len() {
+            while let Some(connection) = &mut self.clients[seat] {
+                match connection.poll() {
+                    Ok(Some(NetMessage::Input(input))) => self.submit(seat as u8, input),
+                    Ok(None) => break,
+                    //anything else ends the connection
+                    Ok(Some(_)) | Err(_) => self.leave(seat as u8),
+                }
+            }
+        }
+        if self.finished() {
+            for connection in self.clients.iter_mut().flatten() {
+                let _ = connection
+                    .send(&NetMessage::Bye)
+                    .and_then(|_| connection.close(CLOSE_TIMEOUT));
+            }
+            return false;
+        }
+        true
+    }
+
+    //poll until the end of the game
+    pub fn run(&mut self) {
+        while self.poll() {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    //events go through the check of the event dispatcher of the app (GameState::is_legal),
+    //selections and choices are checked by the rules engine when they are played
+    fn submit(&mut self, seat: u8, input: ReplayInput) {
+        let result = match input {
+            _ if self.state.input_player(&input) != Some(seat) => {
+                Err(NetworkError::NotYourTurn(seat))
+            }
+            ReplayInput::Event(event) if !self.state.is_legal(seat, event) => {
+                Err(NetworkError::Illegal(event))
+            }
+            _ => self
+                .replay
+                .record(&mut self.state, input.clone())
+                .map_err(NetworkError::from),
+        };
+        match result {
+            Ok(_) => self.broadcast(),
+            Err(err) => {
+                let refused = NetMessage::Refused {
+                    input,
+                    reason: err.to_string(),
+                };
+                if let Some(Err(_)) = self.clients[seat as usize]
+                    .as_mut()
+                    .map(|c| c.send(&refused))
+                {
+                    self.leave(seat);
+                }
+            }
+        }
+    }
+
+    //a player leaving the game concedes it
+    fn leave(&mut self, seat: u8) {
+        if self.clients[seat as usize].take().is_none() {
+            return;
+        }
+        if self
+            .replay
+            .record(
+                &mut self.state,
+                ReplayInput::Event(GameEvents::Concede(seat)),
+            )
+            .is_ok()
+        {
+            self.broadcast();
+        }
+    }
+
+    fn broadcast(&mut self) {
+        let mut left = Vec::new();
+        for (seat, client) in self.clients.iter_mut().enumerate() {
+            if let Some(connection) = client {
+                let view = Box::new(self.state.view(seat as u8));
+                if connection.send(&NetMessage::View(view)).is_err() {
+                    left.push(seat as u8);
+                }
+            }
+        }
+        for seat in left {
+            self.leave(seat);
+        }
+    }
+}
+
+//how long a new connection has to say hello before its seat goes to someone else
+pub const HELLO_TIMEOUT: Duration = Duration::from_secs(5);
+
+//give a seat to a new connection, once it answered the hello of the server
+fn greet(stream: TcpStream, seat: u8, timeout: Duration) -> Result<Connection, NetworkError> {
+    let mut connection = Connection::new(stream)?;
+    connection.send(&NetMessage::Hello {
+        version: PROTOCOL_VERSION,
+        player: Some(seat),
+    })?;
+    check_hello(connection.wait(timeout)?)?;
+    Ok(connection)
+}
+
+//player connected to an authoritative server, it only knows its view of the board
+pub struct ServerClient {
+    pub player: u8,
+    pub view: PlayerView,
+    connection: Connection,
+    greeted: bool,
+}
+
+impl ServerClient {
+    //connect without waiting, the seat is known once the hello of the server is received
+    pub fn join(addr: impl ToSocketAddrs) -> Result<Self, NetworkError> {
+        let mut connection = Connection::connect(addr)?;
+        connection.send(&NetMessage::Hello {
+            version: PROTOCOL_VERSION,
+            player: None,
+        })?;
+        Ok(Self {
+            player: 0,
+            view: PlayerView::default(),
+            connection,
+            greeted: false,
+        })
+    }
+
+    //connect and wait for the first view (sent once every seat is taken)
+    pub fn connect(addr: impl ToSocketAddrs, timeout: Duration) -> Result<Self, NetworkError> {
+        let mut client = Self::join(addr)?;
+        while !client.started() {
+            client.wait(timeout)?;
+        }
+        Ok(client)
+    }
+
+    //the server gave a seat
+    pub fn greeted(&self) -> bool {
+        self.greeted
+    }
+
+    //the first view was received, the game is set up
+    pub fn started(&self) -> bool {
+        !self.view.players.is_empty()
+    }
+
+    //true if the server waits for an input of this player (other than a concession)
+    pub fn our_turn(&self) -> bool {
+        self.view.waiting_for == Some(self.player)
+    }
+
+    //send an intent, the server answers with a new view or a refusal
+    pub fn submit(&mut self, input: ReplayInput) -> Result<(), NetworkError> {
+        self.connection.send(&NetMessage::Input(input))
+    }
+
+    //true if a new view was received
+    pub fn poll(&mut self) -> Result<bool, NetworkError> {
+        match self.connection.poll()? {
+            Some(message) => {
+                let view = matches!(message, NetMessage::View(_));
+                self.receive(message).map(|_| view)
+            }
+            None => Ok(false),
+        }
+    }
+
+    //wait for the next message (a view, once greeted)
+    pub fn wait(&mut self, timeout: Duration) -> Result<(), NetworkError> {
+        let message = self.connection.wait(timeout)?;
+        self.receive(message)
+    }
+
+    pub fn leave(mut self) -> Result<(), NetworkError> {
+        self.connection.send(&NetMessage::Bye)?;
+        self.connection.close(CLOSE_TIMEOUT)
+    }
+
+    fn receive(&mut self, message: NetMessage) -> Result<(), NetworkError> {
+        match message {
+            hello @ NetMessage::Hello { .. } if !self.greeted => {
+                self.player = check_hello(hello)?.unwrap_or(0);
+                self.greeted = true;
+                Ok(())
+            }
+            NetMessage::View(view) if self.greeted => {
+                self.view = *view;
+                Ok(())
+            }
+            NetMessage::Refused { input, reason } => Err(NetworkError::Refused { input, reason }),
+            NetMessage::Bye => Err(NetworkError::Closed),
+            message => Err(NetworkError::Unexpected(message)),
+        }
+    }
+}
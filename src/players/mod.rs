@@ -1,18 +1,32 @@
 use crate::cards::components::factions::CardFaction;
 use crate::cards::transition::CardStateSnapshot;
+use crate::game::modes::GameMode;
 use crate::prelude::{CardFactions, CardOwners, FilterEnumInserter, Stacks};
 use bevy::prelude::*;
 use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
 
-pub const MAXIMUM_PLAYERS: usize = 2;
+pub const MAXIMUM_PLAYERS: usize = 6;
 
 #[derive(Resource, Reflect, Default, Debug)]
 #[reflect(Resource)]
 pub struct LocalPlayer(pub u8);
 
+//number of players of the current game, set by the setup event
+#[derive(Resource, Reflect, Debug, Copy, Clone)]
+#[reflect(Resource)]
+pub struct PlayerCount(pub u8);
+
+impl Default for PlayerCount {
+    fn default() -> Self {
+        Self(2)
+    }
+}
+
+//marker inserted alongside CardOwners::Player, the id itself is read from CardOwners
 #[derive(Component, Reflect, Default, Debug)]
 #[reflect(Component)]
-pub struct Player<const ID: u8>;
+pub struct PlayerOwned;
 
 pub trait PlayerCounter {
     fn get_value(&self) -> i32;
@@ -48,7 +62,7 @@ impl PlayerCounter for PlayerEconomy {
     }
 }
 
-#[derive(Reflect, Default, Debug)]
+#[derive(Reflect, Serialize, Deserialize, Default, Debug, Clone, Eq, PartialEq)]
 pub struct FactionTurnTracker {
     pub bases_in_play: usize,
     pub bases_played: usize,
@@ -72,7 +86,7 @@ impl FactionTurnTracker {
     }
 }
 
-#[derive(Component, Reflect, Debug)]
+#[derive(Component, Reflect, Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 #[reflect(Component)]
 pub struct PlayerTurnTracker {
     pub common: FactionTurnTracker,
@@ -113,6 +127,24 @@ impl PlayerTurnTracker {
         }
     }
 
+    pub fn life_changed(&mut self, delta: i32) {
+        if delta < 0 {
+            self.life_lost += delta.unsigned_abs() as usize;
+        } else {
+            self.life_gained += delta as usize;
+        }
+    }
+
+    //factions gained by a ship already in play (Stealth Needle copying another ship)
+    pub fn ship_gained_factions(&mut self, factions: &[CardFaction]) {
+        for (faction, counters) in self.faction_counters.iter_mut() {
+            if factions.contains(faction) {
+                counters.ships_in_play += 1;
+                counters.ships_played += 1;
+            }
+        }
+    }
+
     pub fn card_snapshots(
         &mut self,
         previous: &CardStateSnapshot,
@@ -208,21 +240,53 @@ impl PlayerTurnTracker {
 #[reflect(Component)]
 pub struct PlayerActionTracker {}
 
-pub fn spawn_counters(mut commands: Commands) {
-    CardOwners::Player(0).insert(&mut commands.spawn((
-        Name::new(format!("Player 0 counters")),
-        PlayerLife(50),
-        PlayerAttack(0),
-        PlayerEconomy(0),
-        PlayerTurnTracker::default(),
-    )));
-    CardOwners::Player(1).insert(&mut commands.spawn((
-        Name::new(format!("Player 1 counters")),
-        PlayerLife(50),
-        PlayerAttack(0),
-        PlayerEconomy(0),
-        PlayerTurnTracker::default(),
-    )));
+pub fn spawn_counters(commands: &mut Commands, players: u8, mode: GameMode, life: i32) {
+    for player in 0..players {
+        CardOwners::Player(player).insert(&mut commands.spawn((
+            Name::new(format!("Player {player} counters")),
+            PlayerLife(mode.starting_life(player, players, life)),
+            PlayerAttack(0),
+            PlayerEconomy(0),
+            PlayerTurnTracker::default(),
+        )));
+    }
+}
+
+//component of the entity holding the counters of a player (life, attack, economy, tracker)
+pub fn player_counter_mut<C: Component>(world: &mut World, player: u8) -> Option<Mut<'_, C>> {
+    let entity = world
+        .query_filtered::<(Entity, &CardOwners), With<PlayerLife>>()
+        .iter(world)
+        .find(|(_, o)| **o == CardOwners::Player(player))?
+        .0;
+    world.get_mut::<C>(entity)
+}
+
+//indexed by player id, a player is out of the game once their life reaches 0
+pub fn alive_players(world: &mut World) -> Vec<bool> {
+    let players = world.resource::<PlayerCount>().0;
+    let mut alive = vec![false; players as usize];
+    for (life, owner) in world.query::<(&PlayerLife, &CardOwners)>().iter(world) {
+        if let CardOwners::Player(p) = *owner {
+            if let Some(alive) = alive.get_mut(p as usize) {
+                *alive = life.0 > 0;
+            }
+        }
+    }
+    alive
+}
+
+//apply a life change to a player and to everyone sharing their life total
+pub fn change_life(world: &mut World, player: u8, delta: i32) {
+    let players = world.resource::<PlayerCount>().0;
+    for p in world.resource::<GameMode>().life_sharers(player, players) {
+        if let Some(mut life) = player_counter_mut::<PlayerLife>(world, p) {
+            life.0 += delta;
+        }
+        if let Some(mut tracker) = player_counter_mut::<PlayerTurnTracker>(world, p) {
+            tracker.life_changed(delta);
+        }
+    }
 }
 
 pub struct PlayerPlugin;
@@ -231,14 +295,14 @@ impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<LocalPlayer>()
             .init_resource::<LocalPlayer>()
+            .register_type::<PlayerCount>()
+            .init_resource::<PlayerCount>()
             .register_type::<PlayerLife>()
             .register_type::<PlayerAttack>()
             .register_type::<PlayerEconomy>()
             .register_type::<PlayerActionTracker>()
             .register_type::<FactionTurnTracker>()
             .register_type::<PlayerTurnTracker>()
-            .register_type::<Player<0>>()
-            .register_type::<Player<1>>()
-            .add_systems(Startup, spawn_counters);
+            .register_type::<PlayerOwned>();
     }
 }
@@ -7,4 +7,6 @@ pub enum AppStates {
     Loading,
     MainMenu,
     Playing,
+    GameOver, //board is frozen and the results screen is shown
+    Replay,   //a recorded game is shown, see game::replay
 }
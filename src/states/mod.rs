@@ -10,6 +10,7 @@ impl Plugin for StatesPlugin {
         app.register_type::<State<app::AppStates>>()
             .register_type::<NextState<app::AppStates>>()
             .init_state::<app::AppStates>()
+            .register_type::<turn::GameOverReason>()
             .register_type::<State<turn::TurnStates>>()
             .register_type::<NextState<turn::TurnStates>>()
             .init_state::<turn::TurnStates>();
@@ -1,10 +1,24 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(States, Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Reflect)]
+#[derive(
+    States, Serialize, Deserialize, Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Reflect,
+)]
 #[reflect(Default)]
 pub enum TurnStates {
     #[default]
     Setup,
     PlayerTurn(u8),
     PlayerCleanup(u8),
+    GameOver {
+        winner: u8,
+        reason: GameOverReason,
+    }, //no event is processed anymore, the board is kept as is until a rematch or a return to the menu
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Reflect)]
+pub enum GameOverReason {
+    #[default]
+    LifeDepleted,
+    Conceded,
 }
@@ -1,10 +1,16 @@
 use crate::game::GameStates;
+use crate::players::LocalPlayer;
 use crate::prelude::*;
 use bevy::ecs::system::EntityCommands;
+use bevy_rapier3d::prelude::DebugRenderContext;
 
 #[derive(Component, Debug, Reflect)]
 pub struct ChoiceRoot {
     pub finished: bool,
+    pub player: u8, //only this player can pick
+    pub min: u8,    //bounds on the number of buttons selected when validating
+    pub max: u8,
+    pub set: ActionSet, //node the buttons are the options of
 }
 
 #[derive(Component, Debug, Reflect)]
@@ -31,7 +37,10 @@ impl Plugin for ChoiceUIPlugin {
             .register_type::<ChoiceButtonNone>()
             .add_systems(
                 Update,
-                (handle_choice_clicks, handle_choice_hover)
+                (
+                    handle_choice_clicks.run_if(local_choice),
+                    handle_choice_hover,
+                )
                     .run_if(in_state(GameStates::ChoiceInput)),
             );
     }
@@ -59,6 +68,15 @@ pub fn handle_choice_hover(
     }
 }
 
+//the choice of another player is given by them (or by the network)
+pub fn local_choice(
+    root: Query<&ChoiceRoot>,
+    local_player: Res<LocalPlayer>,
+    debug: Res<DebugRenderContext>,
+) -> bool {
+    debug.enabled || root.get_single().is_ok_and(|r| r.player == local_player.0)
+}
+
 pub fn handle_choice_clicks(
     mouse_buttons: Res<ButtonInput<MouseButton>>,
     mut root: Query<&mut ChoiceRoot>,
@@ -83,7 +101,8 @@ pub fn handle_choice_clicks(
     if mouse_buttons.just_released(MouseButton::Left) {
         if let Ok(mut root) = root.get_single_mut() {
             let multi = if let Ok(validate) = validate_button.get_single() {
-                if *validate != Interaction::None {
+                let count = buttons.iter().filter(|(b, ..)| b.selected).count() as u8;
+                if *validate != Interaction::None && count >= root.min && count <= root.max {
                     root.finished = true;
                 }
                 true
@@ -143,10 +162,38 @@ pub fn handle_choice_clicks(
     }
 }
 
-pub fn spawn_choices(commands: &mut Commands, set: ActionSet) {
-    fn spawn_ui<'w, 's, 'c>(commands: &'c mut Commands<'w, 's>) -> EntityCommands<'c> {
+//answer the open choice for a player that does not use the mouse (network, bots)
+pub fn answer_choice(world: &mut World, choices: &[u8]) {
+    for mut button in world.query::<&mut ChoiceButton>().iter_mut(world) {
+        button.selected = choices.contains(&button.index);
+    }
+    for mut button in world.query::<&mut ChoiceButtonNone>().iter_mut(world) {
+        button.selected = choices.is_empty();
+    }
+    for mut root in world.query::<&mut ChoiceRoot>().iter_mut(world) {
+        root.finished = true;
+    }
+    world
+        .resource_mut::<NextState<GameStates>>()
+        .set(GameStates::MainLoop);
+}
+
+pub fn spawn_choices(commands: &mut Commands, player: u8, set: &ActionSet) {
+    fn spawn_ui<'w, 's, 'c>(
+        commands: &'c mut Commands<'w, 's>,
+        player: u8,
+        min: u8,
+        max: u8,
+        set: ActionSet,
+    ) -> EntityCommands<'c> {
         commands.spawn((
-            ChoiceRoot { finished: false },
+            ChoiceRoot {
+                finished: false,
+                player,
+                min,
+                max,
+                set,
+            },
             NodeBundle {
                 background_color: BackgroundColor(Color::BLACK.with_a(0.8)),
                 style: Style {
@@ -226,70 +273,31 @@ pub fn spawn_choices(commands: &mut Commands, set: ActionSet) {
             });
         });
     };
-    match set {
-        ActionSet::Optional(option) => {
-            let mut ec = spawn_ui(commands);
-            ec.with_children(|root| {
-                let mut button = root.spawn(ChoiceButton {
-                    selected: false,
-                    index: 0,
-                });
-                insert_button(&mut button, format!("{option}"));
-                spawn_separator(root, "or");
-                let mut button = root.spawn(ChoiceButtonNone { selected: false });
-                insert_button(&mut button, "nothing".to_string());
+    //one button per option, a "nothing" button if no option is mandatory and a validation
+    //button if more than one option can be picked
+    let (min, max) = set.choice_bounds();
+    let options = set.options();
+    let separator = if max > 1 { "and/or" } else { "or" };
+    let mut ec = spawn_ui(commands, player, min, max, set.clone());
+    ec.with_children(|root| {
+        for (index, option) in options.iter().enumerate() {
+            if index > 0 {
+                spawn_separator(root, separator);
+            }
+            let mut button = root.spawn(ChoiceButton {
+                selected: false,
+                index: index as u8,
             });
+            insert_button(&mut button, option.label());
         }
-        ActionSet::OneAndOptional(_, option) => {
-            let mut ec = spawn_ui(commands);
-            ec.with_children(|root| {
-                let mut button = root.spawn(ChoiceButton {
-                    selected: false,
-                    index: 0,
-                });
-                insert_button(&mut button, format!("{option}"));
-                spawn_separator(root, "or");
-                let mut button = root.spawn(ChoiceButtonNone { selected: false });
-                insert_button(&mut button, "nothing".to_string());
-            });
+        if min == 0 {
+            spawn_separator(root, if max > 1 { "OR" } else { "or" });
+            let mut button = root.spawn(ChoiceButtonNone { selected: false });
+            insert_button(&mut button, "nothing".to_string());
         }
-        ActionSet::AnyOf2(first, second) => {
-            let mut ec = spawn_ui(commands);
-            ec.with_children(|root| {
-                let mut button = root.spawn(ChoiceButton {
-                    selected: false,
-                    index: 0,
-                });
-                insert_button(&mut button, format!("{}", first));
-                spawn_separator(root, "and/or");
-                let mut button = root.spawn(ChoiceButton {
-                    selected: false,
-                    index: 1,
-                });
-                insert_button(&mut button, format!("{}", second));
-                spawn_separator(root, "OR");
-                let mut button = root.spawn(ChoiceButtonNone { selected: false });
-                insert_button(&mut button, "nothing".to_string());
-                let mut button = root.spawn(ValidateButton);
-                insert_button(&mut button, "Validate".to_string());
-            });
+        if max > 1 {
+            let mut button = root.spawn(ValidateButton);
+            insert_button(&mut button, "Validate".to_string());
         }
-        ActionSet::OneOf2(first, second) => {
-            let mut ec = spawn_ui(commands);
-            ec.with_children(|root| {
-                let mut button = root.spawn(ChoiceButton {
-                    selected: false,
-                    index: 0,
-                });
-                insert_button(&mut button, format!("{first}"));
-                spawn_separator(root, "or");
-                let mut button = root.spawn(ChoiceButton {
-                    selected: false,
-                    index: 1,
-                });
-                insert_button(&mut button, format!("{second}"));
-            });
-        }
-        _ => {}
-    }
+    });
 }
@@ -0,0 +1,77 @@
+use crate::network::NetworkSession;
+use crate::prelude::*;
+
+#[derive(Component, Debug, Reflect)]
+pub struct DesyncUI;
+
+//the network game stopped because the two boards differ: the input after which they differ, and
+//what differs once the boards were exchanged
+pub fn desync_text(session: &NetworkSession) -> Option<String> {
+    let step = session.desync?;
+    let details = match &session.diff {
+        Some(diff) => format!("here / on the other side:\n{diff}"),
+        None => "waiting for the board of the other player".to_string(),
+    };
+    Some(format!(
+        "The boards differ after input {step}, the game is stopped\n{details}"
+    ))
+}
+
+pub fn show_desync(
+    mut commands: Commands,
+    session: Res<NetworkSession>,
+    mut ui: Query<(Entity, &mut Text), With<DesyncUI>>,
+) {
+    if !session.is_changed() {
+        return;
+    }
+    let Some(value) = desync_text(&session) else {
+        return;
+    };
+    if let Ok((_, mut text)) = ui.get_single_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+        return;
+    }
+    commands.spawn((
+        DesyncUI,
+        TextBundle {
+            background_color: BackgroundColor(Color::BLACK.with_a(0.8)),
+            style: Style {
+                position_type: PositionType::Absolute,
+                justify_self: JustifySelf::Center,
+                top: Val::Px(20.),
+                padding: UiRect::all(Val::Px(10.)),
+                ..Default::default()
+            },
+            ..TextBundle::from_section(
+                value,
+                TextStyle {
+                    font_size: 18.,
+                    color: Color::ORANGE_RED,
+                    ..Default::default()
+                },
+            )
+        },
+    ));
+}
+
+pub fn despawn_desync(mut commands: Commands, ui: Query<Entity, With<DesyncUI>>) {
+    for entity in ui.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub struct DesyncUIPlugin;
+
+impl Plugin for DesyncUIPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<DesyncUI>()
+            .add_systems(
+                Update,
+                show_desync.run_if(resource_exists::<NetworkSession>),
+            )
+            .add_systems(OnExit(AppStates::Playing), despawn_desync);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::game::events::GameSetup;
+use crate::game::modes::GameMode;
+use crate::players::{LocalPlayer, PlayerCount};
+use crate::prelude::*;
+use crate::states::turn::{GameOverReason, TurnStates};
+
+#[derive(Component, Debug, Reflect)]
+pub struct GameOverUI;
+
+#[derive(Component, Debug, Reflect, Copy, Clone, Eq, PartialEq)]
+pub enum GameOverButton {
+    Rematch,
+    MainMenu,
+}
+
+pub fn spawn_results(
+    mut commands: Commands,
+    turn: Res<State<TurnStates>>,
+    local_player: Res<LocalPlayer>,
+    player_count: Res<PlayerCount>,
+    mode: Res<GameMode>,
+) {
+    let TurnStates::GameOver { winner, reason } = *turn.get() else {
+        return;
+    };
+    let title = if mode.won(local_player.0, winner, player_count.0) {
+        "Victory"
+    } else {
+        "Defeat"
+    };
+    let winners = match *mode {
+        GameMode::TeamEmperor | GameMode::Raid => format!("The team of player {winner}"),
+        GameMode::FreeForAll | GameMode::Hunter => format!("Player {winner}"),
+    };
+    let details = match (reason, *mode) {
+        (GameOverReason::LifeDepleted, GameMode::Hunter) => {
+            format!("{winners} wins, their target was destroyed")
+        }
+        (GameOverReason::LifeDepleted, _) => {
+            format!("{winners} wins, all opponents were destroyed")
+        }
+        (GameOverReason::Conceded, _) => format!("{winners} wins by concession"),
+    };
+    let details = format!("{}: {details}", *mode);
+    let text = |value: String, font_size: f32| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font_size,
+                ..Default::default()
+            },
+        )
+    };
+    let button = || ButtonBundle {
+        background_color: BackgroundColor(Color::BLACK.with_a(0.9)),
+        style: Style {
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            padding: UiRect::all(Val::Px(5.)),
+            margin: UiRect::all(Val::Px(5.)),
+            border: UiRect::all(Val::Px(5.)),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    commands
+        .spawn((
+            GameOverUI,
+            NodeBundle {
+                background_color: BackgroundColor(Color::BLACK.with_a(0.8)),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    justify_self: JustifySelf::Center,
+                    align_self: AlignSelf::Center,
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::all(Val::Px(20.)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        ))
+        .with_children(|root| {
+            root.spawn(text(title.to_string(), 60.));
+            root.spawn(text(details, 25.));
+            root.spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    margin: UiRect::top(Val::Px(15.)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .with_children(|buttons| {
+                buttons
+                    .spawn((GameOverButton::Rematch, button()))
+                    .with_children(|button| {
+                        button.spawn(text("Rematch".to_string(), 20.));
+                    });
+                buttons
+                    .spawn((GameOverButton::MainMenu, button()))
+                    .with_children(|button| {
+                        button.spawn(text("Main menu".to_string(), 20.));
+                    });
+            });
+        });
+}
+
+pub fn despawn_results(mut commands: Commands, ui: Query<Entity, With<GameOverUI>>) {
+    for entity in ui.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub fn handle_results_buttons(
+    mut buttons: Query<(&Interaction, &GameOverButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut setup: ResMut<GameSetup>,
+    mut app: ResMut<NextState<AppStates>>,
+) {
+    for (interaction, button, mut background) in buttons.iter_mut() {
+        match interaction {
+            Interaction::Pressed => match button {
+                GameOverButton::Rematch => {
+                    //same settings, new deal
+                    setup.seed = setup.seed.wrapping_add(1);
+                    app.set(AppStates::Playing);
+                }
+                GameOverButton::MainMenu => app.set(AppStates::MainMenu),
+            },
+            Interaction::Hovered => *background = BackgroundColor(Color::GRAY),
+            Interaction::None => *background = BackgroundColor(Color::BLACK.with_a(0.9)),
+        }
+    }
+}
+
+pub struct GameOverUIPlugin;
+
+impl Plugin for GameOverUIPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GameOverUI>()
+            .register_type::<GameOverButton>()
+            .add_systems(OnEnter(AppStates::GameOver), spawn_results)
+            .add_systems(OnExit(AppStates::GameOver), despawn_results)
+            .add_systems(
+                Update,
+                handle_results_buttons.run_if(in_state(AppStates::GameOver)),
+            );
+    }
+}
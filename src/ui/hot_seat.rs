@@ -0,0 +1,213 @@
+use crate::bots::Bots;
+use crate::game::routines::card_action::Selection;
+use crate::game::GameStates;
+use crate::players::{LocalPlayer, PlayerCount};
+use crate::prelude::*;
+use crate::states::turn::TurnStates;
+use crate::ui::choice_ui::ChoiceRoot;
+use bevy::ui::FocusPolicy;
+use bevy_rapier3d::prelude::DebugRenderContext;
+
+//several humans sharing the device: the local player follows whoever has to act, and the hands
+//stay hidden behind a curtain until the next player takes the device
+#[derive(Resource, Reflect, Default, Debug)]
+#[reflect(Resource)]
+pub struct HotSeat {
+    pub enabled: bool,
+    pub handoff: Option<u8>, //player the device is passed to, waiting for them to confirm
+}
+
+#[derive(Component, Debug, Reflect)]
+pub struct HandoffCurtain;
+
+#[derive(Component, Debug, Reflect)]
+pub struct HandoffButton;
+
+//no input is taken from the board while the device changes hands
+pub fn no_handoff(hot_seat: Res<HotSeat>) -> bool {
+    hot_seat.handoff.is_none()
+}
+
+//move every card of the players again, so their stacks follow the seats of TransitionTransforms,
+//the hands are shown or hidden on the way and the other cards keep their visibility
+fn place_player_cards(world: &mut World, hand_visible: impl Fn(CardOwners) -> bool) {
+    let cards: Vec<(Entity, CardOwners, Stacks, CardIndex, CardVisibility)> = world
+        .query::<(Entity, &CardOwners, &Stacks, &CardIndex, &CardVisibility)>()
+        .iter(world)
+        .filter(|(_, owner, ..)| matches!(owner, CardOwners::Player(_)))
+        .map(|(card, owner, stack, index, visibility)| (card, *owner, *stack, *index, *visibility))
+        .collect();
+    for (card, owner, stack, index, visibility) in cards {
+        let visibility = match stack {
+            Stacks::Hand if hand_visible(owner) => CardVisibility::Visible,
+            Stacks::Hand => CardVisibility::Hidden,
+            _ => visibility,
+        };
+        world.entity_mut(card).insert(StartTransition {
+            owner,
+            stack,
+            index,
+            visibility,
+            length: 0.0,
+        });
+    }
+}
+
+fn spawn_curtain(world: &mut World, player: u8) {
+    world
+        .spawn((
+            HandoffCurtain,
+            NodeBundle {
+                background_color: BackgroundColor(Color::BLACK),
+                //the buttons of the game under it can not be pressed either
+                focus_policy: FocusPolicy::Block,
+                z_index: ZIndex::Global(i32::MAX - 1),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        ))
+        .with_children(|root| {
+            root.spawn(TextBundle::from_section(
+                format!("Pass the device to player {player}"),
+                TextStyle {
+                    font_size: 40.,
+                    ..Default::default()
+                },
+            ));
+            root.spawn((
+                HandoffButton,
+                ButtonBundle {
+                    background_color: BackgroundColor(Color::DARK_GRAY),
+                    style: Style {
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        padding: UiRect::all(Val::Px(5.)),
+                        margin: UiRect::all(Val::Px(20.)),
+                        border: UiRect::all(Val::Px(5.)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            ))
+            .with_children(|button| {
+                button.spawn(TextBundle::from_section(
+                    format!("I am player {player}"),
+                    TextStyle {
+                        font_size: 20.,
+                        ..Default::default()
+                    },
+                ));
+            });
+        });
+}
+
+//once the board is at rest, hand the device to the human who has to act next: the one prompted by
+//a selection or a choice, otherwise the one playing the turn
+pub fn follow_acting_player(world: &mut World) {
+    let hot_seat = world.resource::<HotSeat>();
+    if !hot_seat.enabled
+        || hot_seat.handoff.is_some()
+        || world
+            .query_filtered::<(), Or<(With<CardTransition>, With<StartTransition>)>>()
+            .iter(world)
+            .next()
+            .is_some()
+    {
+        return;
+    }
+    let acting = match (
+        world.resource::<State<GameStates>>().get(),
+        world.resource::<State<TurnStates>>().get(),
+    ) {
+        (GameStates::SelectionInput, _) => Some(world.resource::<Selection>().player),
+        (GameStates::ChoiceInput, _) => world
+            .query::<&ChoiceRoot>()
+            .get_single(world)
+            .ok()
+            .map(|c| c.player),
+        (_, TurnStates::PlayerTurn(player)) => Some(*player),
+        _ => None,
+    };
+    let Some(player) = acting else {
+        return;
+    };
+    let players = world.resource::<PlayerCount>().0;
+    if player == world.resource::<LocalPlayer>().0
+        || player >= players
+        || world
+            .get_resource::<Bots>()
+            .is_some_and(|b| b.plays(player))
+    {
+        return;
+    }
+    world.resource_mut::<LocalPlayer>().0 = player;
+    world.resource_mut::<HotSeat>().handoff = Some(player);
+    world.insert_resource(TransitionTransforms::with_seats(players, player));
+    let debug = world.resource::<DebugRenderContext>().enabled;
+    //the table turns, every stack of the players moves to its new seat
+    place_player_cards(world, |_| debug);
+    spawn_curtain(world, player);
+}
+
+pub fn handle_handoff_button(world: &mut World) {
+    let mut pressed = false;
+    for (interaction, _, mut background) in world
+        .query_filtered::<(&Interaction, &HandoffButton, &mut BackgroundColor), Changed<Interaction>>()
+        .iter_mut(world)
+    {
+        match interaction {
+            Interaction::Pressed => pressed = true,
+            Interaction::Hovered => *background = BackgroundColor(Color::GRAY),
+            Interaction::None => *background = BackgroundColor(Color::DARK_GRAY),
+        }
+    }
+    if !pressed {
+        return;
+    }
+    if let Some(player) = world.resource_mut::<HotSeat>().handoff.take() {
+        let debug = world.resource::<DebugRenderContext>().enabled;
+        place_player_cards(world, |owner| debug || owner == CardOwners::Player(player));
+    }
+    let curtain: Vec<Entity> = world
+        .query_filtered::<Entity, With<HandoffCurtain>>()
+        .iter(world)
+        .collect();
+    for entity in curtain {
+        world.entity_mut(entity).despawn_recursive();
+    }
+}
+
+pub fn remove_curtain(
+    mut commands: Commands,
+    mut hot_seat: ResMut<HotSeat>,
+    curtain: Query<Entity, With<HandoffCurtain>>,
+) {
+    hot_seat.handoff = None;
+    for entity in curtain.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub struct HotSeatPlugin;
+
+impl Plugin for HotSeatPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<HotSeat>()
+            .init_resource::<HotSeat>()
+            .register_type::<HandoffCurtain>()
+            .register_type::<HandoffButton>()
+            .add_systems(OnExit(AppStates::Playing), remove_curtain)
+            .add_systems(
+                Update,
+                (follow_acting_player, handle_handoff_button).run_if(in_state(AppStates::Playing)),
+            );
+    }
+}
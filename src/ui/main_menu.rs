@@ -0,0 +1,524 @@
+use crate::bots::mcts::Budget;
+use crate::bots::{BotKind, Bots};
+use crate::cards::assets::manifest::SetCatalog;
+use crate::game::events::GameSetup;
+use crate::game::modes::GameMode;
+use crate::game::save::{PendingLoad, SavedGame, QUICKSAVE};
+use crate::network::protocol::DEFAULT_PORT;
+use crate::network::NetworkSession;
+use crate::players::{LocalPlayer, MAXIMUM_PLAYERS};
+use crate::prelude::*;
+use crate::ui::hot_seat::HotSeat;
+use std::time::Duration;
+
+#[derive(Reflect, Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum PlayerKind {
+    #[default]
+    Human,
+    Bot,
+    Remote, //a guest joining over the network (2 players only, the guest plays seat 1)
+}
+
+//settings of the main menu that can be changed with the arrows
+#[derive(Reflect, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MenuOption {
+    Set,
+    Mode,
+    Players,
+    Player(u8),
+    Bots,
+    Seed,
+    StartingPlayer,
+}
+
+const MODES: [GameMode; 4] = [
+    GameMode::FreeForAll,
+    GameMode::Hunter,
+    GameMode::TeamEmperor,
+    GameMode::Raid,
+];
+
+const BOTS: [BotKind; 3] = [
+    BotKind::Heuristic,
+    BotKind::Mcts(Budget::Time(Duration::from_millis(500))),
+    BotKind::Mcts(Budget::Time(Duration::from_millis(2000))),
+];
+
+//what the main menu sets up, turned into the GameSetup, the bots and the network session of the
+//next game when it starts
+#[derive(Resource, Clone, Debug)]
+pub struct GameConfig {
+    pub sets: Vec<(u8, String)>, //ids and names of the sets found in the assets
+    pub set: u8,                 //id of the set, given to the setup event
+    pub mode: GameMode,
+    pub players: Vec<PlayerKind>,
+    pub bot: BotKind,
+    pub seed: Option<u64>,           //None: random
+    pub starting_player: Option<u8>, //None: random
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self::new(vec![PlayerKind::Human, PlayerKind::Bot], BotKind::Heuristic)
+    }
+}
+
+impl GameConfig {
+    pub fn new(players: Vec<PlayerKind>, bot: BotKind) -> Self {
+        let sets: Vec<(u8, String)> = SetCatalog::discover("assets")
+            .0
+            .into_iter()
+            .map(|set| (set.manifest.id, set.manifest.name))
+            .collect();
+        Self {
+            set: sets
+                .iter()
+                .find(|(id, _)| *id == 0)
+                .or(sets.first())
+                .map_or(0, |(id, _)| *id),
+            sets,
+            mode: GameMode::FreeForAll,
+            players,
+            bot,
+            seed: None,
+            starting_player: None,
+        }
+    }
+
+    pub fn seats(&self, kind: PlayerKind) -> Vec<u8> {
+        (0..self.players.len() as u8)
+            .filter(|&p| self.players[p as usize] == kind)
+            .collect()
+    }
+
+    //the random settings are drawn now
+    pub fn setup(&self) -> GameSetup {
+        let players = self.players.len() as u8;
+        GameSetup {
+            seed: self.seed.unwrap_or_else(rand::random),
+            set: self.set,
+            players,
+            starting_player: self
+                .starting_player
+                .unwrap_or_else(|| rand::random::<u8>() % players),
+            mode: self.mode,
+        }
+    }
+
+    pub fn value(&self, option: MenuOption) -> String {
+        match option {
+            MenuOption::Set => self
+                .sets
+                .iter()
+                .find(|(id, _)| *id == self.set)
+                .map_or_else(|| self.set.to_string(), |(_, name)| name.clone()),
+            MenuOption::Mode => self.mode.to_string(),
+            MenuOption::Players => self.players.len().to_string(),
+            MenuOption::Player(seat) => match self.players.get(seat as usize) {
+                Some(kind) => format!("{kind:?}"),
+                None => String::new(),
+            },
+            MenuOption::Bots => match self.bot {
+                BotKind::Heuristic => "Heuristic".to_string(),
+                BotKind::Mcts(Budget::Time(time)) => format!("Search {}ms", time.as_millis()),
+                BotKind::Mcts(Budget::Iterations(i)) => format!("Search {i} iterations"),
+            },
+            MenuOption::Seed => match self.seed {
+                Some(seed) => seed.to_string(),
+                None => "Random".to_string(),
+            },
+            MenuOption::StartingPlayer => match self.starting_player {
+                Some(player) => format!("Player {player}"),
+                None => "Random".to_string(),
+            },
+        }
+    }
+
+    //next (or previous) value of a setting, keeping the others compatible with it
+    pub fn change(&mut self, option: MenuOption, forward: bool) {
+        let step = |value: usize, len: usize| {
+            if forward {
+                (value + 1) % len
+            } else {
+                (value + len - 1) % len
+            }
+        };
+        match option {
+            MenuOption::Set => {
+                if let Some(index) = self.sets.iter().position(|(id, _)| *id == self.set) {
+                    self.set = self.sets[step(index, self.sets.len())].0;
+                }
+            }
+            MenuOption::Mode => {
+                let index = MODES.iter().position(|m| *m == self.mode).unwrap_or(0);
+                self.mode = MODES[step(index, MODES.len())];
+                let mut players = self.players.len() as u8;
+                while !self.mode.valid_player_count(players) && (players as usize) < MAXIMUM_PLAYERS
+                {
+                    players += 1;
+                }
+                self.set_players(players);
+            }
+            MenuOption::Players => {
+                let mut players = self.players.len();
+                loop {
+                    players = step(players, MAXIMUM_PLAYERS + 1);
+                    if self.mode.valid_player_count(players as u8) {
+                        break;
+                    }
+                }
+                self.set_players(players as u8);
+            }
+            MenuOption::Player(seat) => {
+                let remote = seat == 1 && self.players.len() == 2;
+                if let Some(kind) = self.players.get_mut(seat as usize) {
+                    *kind = match (*kind, forward) {
+                        (PlayerKind::Human, true) | (PlayerKind::Remote, false) => PlayerKind::Bot,
+                        (PlayerKind::Bot, true) | (PlayerKind::Human, false) if remote => {
+                            PlayerKind::Remote
+                        }
+                        (PlayerKind::Human, false) => PlayerKind::Bot,
+                        _ => PlayerKind::Human,
+                    };
+                }
+            }
+            MenuOption::Bots => {
+                let index = BOTS.iter().position(|b| *b == self.bot).unwrap_or(0);
+                self.bot = BOTS[step(index, BOTS.len())];
+            }
+            MenuOption::Seed => {
+                self.seed = match (self.seed, forward) {
+                    (None, true) => Some(0),
+                    (Some(0), false) | (None, false) => None,
+                    (Some(seed), true) => Some(seed.saturating_add(1)),
+                    (Some(seed), false) => Some(seed - 1),
+                };
+            }
+            MenuOption::StartingPlayer => {
+                let players = self.players.len();
+                let index = self.starting_player.map_or(players, |p| p as usize);
+                let index = step(index, players + 1);
+                self.starting_player = (index < players).then_some(index as u8);
+            }
+        }
+    }
+
+    fn set_players(&mut self, players: u8) {
+        self.players.resize(players as usize, PlayerKind::Human);
+        //only a 2 players game can be hosted
+        if players != 2 {
+            for kind in self.players.iter_mut() {
+                if *kind == PlayerKind::Remote {
+                    *kind = PlayerKind::Human;
+                }
+            }
+        }
+        if self.starting_player.is_some_and(|p| p >= players) {
+            self.starting_player = None;
+        }
+    }
+
+    //the bots, the hot seat, the local player and the network session of the next game
+    pub fn apply(&self, world: &mut World) -> bool {
+        let remote = !self.seats(PlayerKind::Remote).is_empty();
+        //the session of a previous game is closed, freeing its port
+        world.remove_resource::<NetworkSession>();
+        if remote {
+            match NetworkSession::host(format!("0.0.0.0:{DEFAULT_PORT}")) {
+                Ok(session) => world.insert_resource(session),
+                Err(err) => {
+                    warn!("could not host the network game: {err}");
+                    return false;
+                }
+            }
+        }
+        let humans = self.seats(PlayerKind::Human);
+        let mut bots = world.resource_mut::<Bots>();
+        //network games are played by two humans
+        bots.seats = if remote {
+            Vec::new()
+        } else {
+            self.seats(PlayerKind::Bot)
+        };
+        bots.set_bot(self.bot.build(rand::random()));
+        world.resource_mut::<HotSeat>().enabled = humans.len() > 1 && !remote;
+        world.resource_mut::<LocalPlayer>().0 = humans.first().copied().unwrap_or(0);
+        true
+    }
+}
+
+#[derive(Component, Debug, Reflect)]
+pub struct MainMenuUI;
+
+#[derive(Component, Debug, Reflect, Copy, Clone, Eq, PartialEq)]
+pub enum MainMenuButton {
+    NewGame,
+    ResumeGame,  //loads the quicksave
+    WatchReplay, //opens ReplayFile, or the latest replay
+    Previous(MenuOption),
+    Next(MenuOption),
+}
+
+//text showing the value of a setting
+#[derive(Component, Debug, Reflect)]
+pub struct MenuValue(pub MenuOption);
+
+//line of a setting, hidden when the setting does not apply (seats past the number of players)
+#[derive(Component, Debug, Reflect)]
+pub struct MenuRow(pub MenuOption);
+
+pub fn spawn_main_menu(mut commands: Commands, config: Res<GameConfig>) {
+    let text = |value: String, font_size: f32| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font_size,
+                ..Default::default()
+            },
+        )
+    };
+    let button = |padding: f32| ButtonBundle {
+        background_color: BackgroundColor(Color::BLACK.with_a(0.9)),
+        style: Style {
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            padding: UiRect::all(Val::Px(padding)),
+            margin: UiRect::all(Val::Px(5.)),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut options = vec![
+        (MenuOption::Set, "Card set".to_string()),
+        (MenuOption::Mode, "Mode".to_string()),
+        (MenuOption::Players, "Players".to_string()),
+    ];
+    options
+        .extend((0..MAXIMUM_PLAYERS as u8).map(|p| (MenuOption::Player(p), format!("Player {p}"))));
+    options.extend([
+        (MenuOption::Bots, "Bots".to_string()),
+        (MenuOption::Seed, "Seed (type digits)".to_string()),
+        (MenuOption::StartingPlayer, "First player".to_string()),
+    ]);
+    commands
+        .spawn((
+            MainMenuUI,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        ))
+        .with_children(|root| {
+            for (option, label) in options {
+                root.spawn((
+                    MenuRow(option),
+                    NodeBundle {
+                        background_color: BackgroundColor(Color::BLACK.with_a(0.5)),
+                        style: Style {
+                            display: row_display(&config, option),
+                            flex_direction: FlexDirection::Row,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                ))
+                .with_children(|row| {
+                    row.spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(220.),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+                    .with_children(|label_box| {
+                        label_box.spawn(text(label, 20.));
+                    });
+                    row.spawn((MainMenuButton::Previous(option), button(5.)))
+                        .with_children(|button| {
+                            button.spawn(text("<".to_string(), 20.));
+                        });
+                    row.spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(200.),
+                            justify_content: JustifyContent::Center,
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+                    .with_children(|value_box| {
+                        value_box.spawn((MenuValue(option), text(config.value(option), 20.)));
+                    });
+                    row.spawn((MainMenuButton::Next(option), button(5.)))
+                        .with_children(|button| {
+                            button.spawn(text(">".to_string(), 20.));
+                        });
+                });
+            }
+            root.spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    margin: UiRect::top(Val::Px(15.)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .with_children(|buttons| {
+                for (kind, label) in [
+                    (MainMenuButton::NewGame, "New game"),
+                    (MainMenuButton::ResumeGame, "Resume saved game"),
+                    (MainMenuButton::WatchReplay, "Watch replay"),
+                ] {
+                    buttons.spawn((kind, button(10.))).with_children(|button| {
+                        button.spawn(text(label.to_string(), 30.));
+                    });
+                }
+            });
+        });
+}
+
+fn row_display(config: &GameConfig, option: MenuOption) -> Display {
+    match option {
+        MenuOption::Player(seat) if seat as usize >= config.players.len() => Display::None,
+        MenuOption::Bots if config.seats(PlayerKind::Bot).is_empty() => Display::None,
+        _ => Display::Flex,
+    }
+}
+
+pub fn update_main_menu(
+    config: Res<GameConfig>,
+    mut values: Query<(&MenuValue, &mut Text)>,
+    mut rows: Query<(&MenuRow, &mut Style)>,
+) {
+    for (value, mut text) in values.iter_mut() {
+        text.sections[0].value = config.value(value.0);
+    }
+    for (row, mut style) in rows.iter_mut() {
+        style.display = row_display(&config, row.0);
+    }
+}
+
+pub fn despawn_main_menu(mut commands: Commands, ui: Query<Entity, With<MainMenuUI>>) {
+    for entity in ui.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+//digits typed in the menu make up a fixed seed, backspace removes the last one
+pub fn type_seed(keys: Res<ButtonInput<KeyCode>>, mut config: ResMut<GameConfig>) {
+    const DIGITS: [KeyCode; 10] = [
+        KeyCode::Digit0,
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ];
+    for (digit, key) in DIGITS.iter().enumerate() {
+        if keys.just_pressed(*key) {
+            let seed = config.seed.unwrap_or(0);
+            config.seed = Some(seed.saturating_mul(10).saturating_add(digit as u64));
+        }
+    }
+    if keys.just_pressed(KeyCode::Backspace) {
+        config.seed = config.seed.map(|s| s / 10).filter(|s| *s != 0);
+    }
+}
+
+pub fn handle_main_menu_buttons(world: &mut World) {
+    let mut pressed = None;
+    for (interaction, button, mut background) in world
+        .query_filtered::<(&Interaction, &MainMenuButton, &mut BackgroundColor), Changed<Interaction>>()
+        .iter_mut(world)
+    {
+        match interaction {
+            Interaction::Pressed => pressed = Some(*button),
+            Interaction::Hovered => *background = BackgroundColor(Color::GRAY),
+            Interaction::None => *background = BackgroundColor(Color::BLACK.with_a(0.9)),
+        }
+    }
+    let Some(button) = pressed else {
+        return;
+    };
+    let next = match button {
+        MainMenuButton::Previous(option) => {
+            world.resource_mut::<GameConfig>().change(option, false);
+            return;
+        }
+        MainMenuButton::Next(option) => {
+            world.resource_mut::<GameConfig>().change(option, true);
+            return;
+        }
+        MainMenuButton::NewGame => {
+            let config = world.resource::<GameConfig>().clone();
+            if !config.apply(world) {
+                return;
+            }
+            world.insert_resource(config.setup());
+            AppStates::Playing
+        }
+        MainMenuButton::ResumeGame => {
+            let saved = match SavedGame::read(QUICKSAVE) {
+                Ok(saved) => saved,
+                Err(err) => {
+                    warn!("could not load the game: {err}");
+                    return;
+                }
+            };
+            let Ok(setup) = saved.setup() else {
+                return;
+            };
+            //the kinds of player are taken from the menu, the rest from the save
+            let mut config = world.resource::<GameConfig>().clone();
+            config
+                .players
+                .resize(setup.players as usize, PlayerKind::Human);
+            if !config.apply(world) {
+                return;
+            }
+            world.insert_resource(setup);
+            world.resource_mut::<PendingLoad>().0 = Some(saved);
+            AppStates::Playing
+        }
+        MainMenuButton::WatchReplay => AppStates::Replay,
+    };
+    world.resource_mut::<NextState<AppStates>>().set(next);
+}
+
+pub struct MainMenuPlugin;
+
+impl Plugin for MainMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<MainMenuUI>()
+            .register_type::<MainMenuButton>()
+            .register_type::<MenuOption>()
+            .register_type::<MenuValue>()
+            .register_type::<MenuRow>()
+            .register_type::<PlayerKind>()
+            .init_resource::<GameConfig>()
+            .add_systems(OnEnter(AppStates::MainMenu), spawn_main_menu)
+            .add_systems(OnExit(AppStates::MainMenu), despawn_main_menu)
+            .add_systems(
+                Update,
+                (
+                    handle_main_menu_buttons,
+                    type_seed,
+                    update_main_menu.run_if(resource_changed::<GameConfig>),
+                )
+                    .chain()
+                    .run_if(in_state(AppStates::MainMenu)),
+            );
+    }
+}
@@ -2,7 +2,12 @@ use bevy::prelude::*;
 
 pub mod billboards;
 pub mod choice_ui;
+pub mod desync;
+pub mod game_over;
+pub mod hot_seat;
+pub mod main_menu;
 pub mod player_counters;
+pub mod replay;
 
 #[derive(Component, Debug, Reflect)]
 pub struct UIRoot;
@@ -66,7 +71,15 @@ pub struct UIPlugin;
 impl Plugin for UIPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(PreStartup, ui_setup)
-            .add_plugins((player_counters::CountersUIPlugin, choice_ui::ChoiceUIPlugin))
+            .add_plugins((
+                player_counters::CountersUIPlugin,
+                choice_ui::ChoiceUIPlugin,
+                desync::DesyncUIPlugin,
+                game_over::GameOverUIPlugin,
+                hot_seat::HotSeatPlugin,
+                main_menu::MainMenuPlugin,
+                replay::ReplayUIPlugin,
+            ))
             // .add_systems(
             //     Update,
             //     (
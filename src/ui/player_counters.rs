@@ -1,5 +1,7 @@
 use crate::game::events::{GameEvent, GameEvents};
-use crate::players::{LocalPlayer, Player, PlayerAttack, PlayerCounter, PlayerEconomy, PlayerLife};
+use crate::players::{
+    LocalPlayer, PlayerAttack, PlayerCount, PlayerCounter, PlayerEconomy, PlayerLife,
+};
 use crate::prelude::*;
 use crate::prelude::{
     AlignItems, BackgroundColor, BorderColor, ButtonBundle, Color, Commands, Component,
@@ -23,28 +25,32 @@ pub struct PassTurnButton {
     pub hovered: bool,
 }
 
+#[derive(Component, Debug, Reflect)]
+pub struct ConcedeButton {
+    pub hovered: bool,
+}
+
 #[derive(Component, Debug, Reflect)]
 pub struct AttackButton {
-    // pub player: u8,
+    pub player: Option<u8>, //target picked with the TargetPlayerButtons, None -> first valid target
+    pub hovered: bool,
+}
+
+//header of the counters of an opponent, clicking it picks the target of the attack button
+#[derive(Component, Debug, Reflect)]
+pub struct TargetPlayerButton {
+    pub player: u8,
     pub hovered: bool,
 }
 
+//containers of the counters texts, filled by spawn_counters_ui once the players are known
+#[derive(Component, Debug, Reflect)]
+pub struct OpponentsCountersUI;
+
+#[derive(Component, Debug, Reflect)]
+pub struct LocalCountersUI;
+
 pub fn spawn_ui(mut commands: Commands, ui_root: Query<Entity, With<UIRoot>>) {
-    let life_style = TextStyle {
-        font_size: 25.,
-        color: Color::GREEN,
-        ..Default::default()
-    };
-    let eco_style = TextStyle {
-        font_size: 25.,
-        color: Color::YELLOW,
-        ..Default::default()
-    };
-    let atk_style = TextStyle {
-        font_size: 25.,
-        color: Color::RED,
-        ..Default::default()
-    };
     let enemy_style = Style {
         left: Val::Percent(89.5),
         top: Val::Percent(25.5),
@@ -60,7 +66,8 @@ pub fn spawn_ui(mut commands: Commands, ui_root: Query<Entity, With<UIRoot>>) {
         .with_children(|root| {
             root.spawn((
                 AttackButton {
-                    /*player: 1*/ hovered: false,
+                    player: None,
+                    hovered: false,
                 },
                 ButtonBundle {
                     style: Style {
@@ -86,54 +93,16 @@ pub fn spawn_ui(mut commands: Commands, ui_root: Query<Entity, With<UIRoot>>) {
                     },
                 ));
             });
-            CardOwners::Player(1).insert(
-                &mut root.spawn((
-                    LifeCounterUI,
-                    TextBundle::from_sections(vec![
-                        TextSection {
-                            value: "Life: ".to_string(),
-                            style: life_style.clone(),
-                        },
-                        TextSection {
-                            value: "0".to_string(),
-                            style: life_style.clone(),
-                        },
-                    ])
-                    .with_style(enemy_style.clone()),
-                )),
-            );
-            CardOwners::Player(1).insert(
-                &mut root.spawn((
-                    EconomyCounterUI,
-                    TextBundle::from_sections(vec![
-                        TextSection {
-                            value: "Economy: ".to_string(),
-                            style: eco_style.clone(),
-                        },
-                        TextSection {
-                            value: "0".to_string(),
-                            style: eco_style.clone(),
-                        },
-                    ])
-                    .with_style(enemy_style.clone()),
-                )),
-            );
-            CardOwners::Player(1).insert(
-                &mut root.spawn((
-                    AttackCounterUI,
-                    TextBundle::from_sections(vec![
-                        TextSection {
-                            value: "Attack: ".to_string(),
-                            style: atk_style.clone(),
-                        },
-                        TextSection {
-                            value: "0".to_string(),
-                            style: atk_style.clone(),
-                        },
-                    ])
-                    .with_style(enemy_style),
-                )),
-            );
+            root.spawn((
+                OpponentsCountersUI,
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        ..enemy_style
+                    },
+                    ..Default::default()
+                },
+            ));
             root.spawn((
                 PassTurnButton { hovered: false },
                 ButtonBundle {
@@ -160,55 +129,122 @@ pub fn spawn_ui(mut commands: Commands, ui_root: Query<Entity, With<UIRoot>>) {
                     },
                 ));
             });
-            CardOwners::Player(0).insert(
-                &mut root.spawn((
-                    LifeCounterUI,
-                    TextBundle::from_sections(vec![
-                        TextSection {
-                            value: "Life: ".to_string(),
-                            style: life_style.clone(),
-                        },
-                        TextSection {
-                            value: "0".to_string(),
-                            style: life_style,
-                        },
-                    ])
-                    .with_style(ally_style.clone()),
-                )),
-            );
-            CardOwners::Player(0).insert(
-                &mut root.spawn((
-                    EconomyCounterUI,
-                    TextBundle::from_sections(vec![
-                        TextSection {
-                            value: "Economy: ".to_string(),
-                            style: eco_style.clone(),
-                        },
-                        TextSection {
-                            value: "0".to_string(),
-                            style: eco_style,
-                        },
-                    ])
-                    .with_style(ally_style.clone()),
-                )),
-            );
-            CardOwners::Player(0).insert(
-                &mut root.spawn((
-                    AttackCounterUI,
-                    TextBundle::from_sections(vec![
-                        TextSection {
-                            value: "Attack: ".to_string(),
-                            style: atk_style.clone(),
+            root.spawn((
+                LocalCountersUI,
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        ..ally_style.clone()
+                    },
+                    ..Default::default()
+                },
+            ));
+            root.spawn((
+                ConcedeButton { hovered: false },
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Px(160.),
+                        height: Val::Px(45.),
+                        border: UiRect::all(Val::Px(5.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..ally_style
+                    },
+                    border_color: BorderColor(Color::DARK_GRAY),
+                    background_color: BackgroundColor(Color::rgba(0.7, 0.7, 0.7, 1.)),
+                    ..Default::default()
+                },
+            ))
+            .with_children(|button| {
+                button.spawn(TextBundle::from_section(
+                    "Concede",
+                    TextStyle {
+                        font_size: 30.,
+                        color: Color::BLACK,
+                        ..Default::default()
+                    },
+                ));
+            });
+        });
+}
+
+//one set of counters per player, the local player's under the pass turn button, the others under the attack button
+pub fn spawn_counters_ui(
+    mut commands: Commands,
+    opponents: Query<Entity, With<OpponentsCountersUI>>,
+    local: Query<Entity, With<LocalCountersUI>>,
+    player_count: Res<PlayerCount>,
+    local_player: Res<LocalPlayer>,
+) {
+    let (Ok(opponents), Ok(local)) = (opponents.get_single(), local.get_single()) else {
+        return;
+    };
+    let life_style = TextStyle {
+        font_size: 25.,
+        color: Color::GREEN,
+        ..Default::default()
+    };
+    let eco_style = TextStyle {
+        font_size: 25.,
+        color: Color::YELLOW,
+        ..Default::default()
+    };
+    let atk_style = TextStyle {
+        font_size: 25.,
+        color: Color::RED,
+        ..Default::default()
+    };
+    let counter = |label: &str, style: &TextStyle| {
+        TextBundle::from_sections(vec![
+            TextSection {
+                value: label.to_string(),
+                style: style.clone(),
+            },
+            TextSection {
+                value: "0".to_string(),
+                style: style.clone(),
+            },
+        ])
+    };
+    commands.entity(opponents).despawn_descendants();
+    commands.entity(local).despawn_descendants();
+    for player in 0..player_count.0 {
+        let is_local = player == local_player.0;
+        let container = if is_local { local } else { opponents };
+        let owner = CardOwners::Player(player);
+        commands.entity(container).with_children(|root| {
+            if !is_local && player_count.0 > 2 {
+                root.spawn((
+                    TargetPlayerButton {
+                        player,
+                        hovered: false,
+                    },
+                    ButtonBundle {
+                        style: Style {
+                            border: UiRect::all(Val::Px(2.0)),
+                            justify_content: JustifyContent::Center,
+                            ..Default::default()
                         },
-                        TextSection {
-                            value: "0".to_string(),
-                            style: atk_style,
+                        border_color: BorderColor(Color::DARK_GRAY),
+                        background_color: BackgroundColor(Color::BLACK.with_a(0.5)),
+                        ..Default::default()
+                    },
+                ))
+                .with_children(|button| {
+                    button.spawn(TextBundle::from_section(
+                        format!("Player {player}"),
+                        TextStyle {
+                            font_size: 25.,
+                            ..Default::default()
                         },
-                    ])
-                    .with_style(ally_style.clone()),
-                )),
-            );
+                    ));
+                });
+            }
+            owner.insert(&mut root.spawn((LifeCounterUI, counter("Life: ", &life_style))));
+            owner.insert(&mut root.spawn((EconomyCounterUI, counter("Economy: ", &eco_style))));
+            owner.insert(&mut root.spawn((AttackCounterUI, counter("Attack: ", &atk_style))));
         });
+    }
 }
 
 pub fn update_attack_button(
@@ -314,13 +350,65 @@ pub fn update_pass_turn_button(
     }
 }
 
-pub fn update_counter_ui<const PLAYER: u8, C: Component + PlayerCounter, T: Component>(
-    mut ui: Query<&mut Text, (With<Player<PLAYER>>, With<T>)>,
-    counter: Query<&C, (Changed<C>, With<Player<PLAYER>>)>,
+pub fn update_concede_button(
+    mut button: Query<
+        (
+            &Interaction,
+            &mut BackgroundColor,
+            &mut BorderColor,
+            &mut ConcedeButton,
+        ),
+        Changed<Interaction>,
+    >,
 ) {
-    if let Ok(mut text) = ui.get_single_mut() {
-        if let Ok(value) = counter.get_single() {
-            text.sections[1].value = value.get_value().to_string();
+    for (interaction, mut background, mut border, mut target) in button.iter_mut() {
+        match interaction {
+            Interaction::Hovered | Interaction::Pressed => {
+                *background = Color::rgba(0.5, 0.5, 0.5, 1.).into();
+                *border = Color::BLACK.into();
+                target.hovered = true;
+            }
+            Interaction::None => {
+                *background = Color::rgba(0.7, 0.7, 0.7, 1.).into();
+                *border = Color::DARK_GRAY.into();
+                target.hovered = false;
+            }
+        }
+    }
+}
+
+//the current target of the attack button is outlined in red
+pub fn update_target_player_buttons(
+    mut buttons: Query<(&Interaction, &mut BorderColor, &mut TargetPlayerButton)>,
+    attack: Query<&AttackButton>,
+) {
+    let target = attack.get_single().ok().and_then(|a| a.player);
+    for (interaction, mut border, mut button) in buttons.iter_mut() {
+        button.hovered = *interaction != Interaction::None;
+        let color = if target == Some(button.player) {
+            Color::RED
+        } else if button.hovered {
+            Color::WHITE
+        } else {
+            Color::DARK_GRAY
+        };
+        if border.0 != color {
+            border.0 = color;
+        }
+    }
+}
+
+pub fn update_counter_ui<C: Component + PlayerCounter, T: Component>(
+    mut ui: Query<(&mut Text, &CardOwners), With<T>>,
+    counters: Query<(&C, &CardOwners)>,
+) {
+    for (mut text, owner) in ui.iter_mut() {
+        if let Some((counter, _)) = counters.iter().find(|(_, o)| *o == owner) {
+            let value = counter.get_value().to_string();
+            //only touch the text when needed, to avoid a relayout every frame
+            if text.sections[1].value != value {
+                text.sections[1].value = value;
+            }
         }
     }
 }
@@ -333,19 +421,32 @@ impl Plugin for CountersUIPlugin {
             .register_type::<EconomyCounterUI>()
             .register_type::<AttackCounterUI>()
             .register_type::<AttackButton>()
+            .register_type::<TargetPlayerButton>()
             .register_type::<PassTurnButton>()
+            .register_type::<ConcedeButton>()
+            .register_type::<OpponentsCountersUI>()
+            .register_type::<LocalCountersUI>()
             .add_systems(Startup, spawn_ui)
-            .add_systems(Update, (update_pass_turn_button, update_attack_button))
             .add_systems(
-                PostUpdate,
+                Update,
                 (
-                    update_counter_ui::<0, PlayerLife, LifeCounterUI>,
-                    update_counter_ui::<0, PlayerAttack, AttackCounterUI>,
-                    update_counter_ui::<0, PlayerEconomy, EconomyCounterUI>,
-                    update_counter_ui::<1, PlayerLife, LifeCounterUI>,
-                    update_counter_ui::<1, PlayerAttack, AttackCounterUI>,
-                    update_counter_ui::<1, PlayerEconomy, EconomyCounterUI>,
+                    update_pass_turn_button,
+                    update_attack_button,
+                    update_concede_button,
+                    update_target_player_buttons,
                 ),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    spawn_counters_ui.run_if(
+                        resource_changed::<PlayerCount>.or_else(resource_changed::<LocalPlayer>),
+                    ),
+                    update_counter_ui::<PlayerLife, LifeCounterUI>,
+                    update_counter_ui::<PlayerAttack, AttackCounterUI>,
+                    update_counter_ui::<PlayerEconomy, EconomyCounterUI>,
+                )
+                    .chain(),
             );
     }
 }
@@ -0,0 +1,260 @@
+use crate::cards::transition::PlayBackSpeed;
+use crate::engine::replay::ReplayInput;
+use crate::game::events::GameEvents;
+use crate::game::replay::ReplayViewer;
+use crate::prelude::*;
+use crate::states::turn::TurnStates;
+use bevy::window::PrimaryWindow;
+
+//playback speed bounds (PlayBackSpeed multiplies the length of transitions, lower is faster)
+const FASTEST: f32 = 0.0625;
+const SLOWEST: f32 = 2.;
+
+#[derive(Component, Debug, Reflect)]
+pub struct ReplayUI;
+
+#[derive(Component, Debug, Reflect, Copy, Clone, Eq, PartialEq)]
+pub enum ReplayButton {
+    Start,
+    Back,
+    PlayPause,
+    Forward,
+    End,
+    Slower,
+    Faster,
+    Exit,
+}
+
+//clicking the timeline jumps to the matching input
+#[derive(Component, Debug, Reflect)]
+pub struct ReplayTimeline;
+
+#[derive(Component, Debug, Reflect)]
+pub struct ReplayTimelineFill;
+
+#[derive(Component, Debug, Reflect)]
+pub struct ReplayLabel;
+
+pub fn spawn_replay_ui(mut commands: Commands) {
+    let text = |value: &str, font_size: f32| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font_size,
+                ..Default::default()
+            },
+        )
+    };
+    let button = || ButtonBundle {
+        background_color: BackgroundColor(Color::BLACK.with_a(0.9)),
+        style: Style {
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            min_width: Val::Px(40.),
+            padding: UiRect::all(Val::Px(5.)),
+            margin: UiRect::all(Val::Px(3.)),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    commands
+        .spawn((
+            ReplayUI,
+            NodeBundle {
+                background_color: BackgroundColor(Color::BLACK.with_a(0.6)),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(0.),
+                    width: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(10.)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        ))
+        .with_children(|root| {
+            root.spawn((
+                ReplayTimeline,
+                ButtonBundle {
+                    background_color: BackgroundColor(Color::DARK_GRAY),
+                    style: Style {
+                        width: Val::Percent(100.),
+                        height: Val::Px(12.),
+                        margin: UiRect::bottom(Val::Px(8.)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            ))
+            .with_children(|timeline| {
+                timeline.spawn((
+                    ReplayTimelineFill,
+                    NodeBundle {
+                        background_color: BackgroundColor(Color::WHITE),
+                        style: Style {
+                            width: Val::Percent(0.),
+                            height: Val::Percent(100.),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                ));
+            });
+            root.spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .with_children(|row| {
+                for (kind, label) in [
+                    (ReplayButton::Start, "|<"),
+                    (ReplayButton::Back, "<"),
+                    (ReplayButton::PlayPause, "Play"),
+                    (ReplayButton::Forward, ">"),
+                    (ReplayButton::End, ">|"),
+                    (ReplayButton::Slower, "-"),
+                    (ReplayButton::Faster, "+"),
+                    (ReplayButton::Exit, "Exit"),
+                ] {
+                    row.spawn((kind, button())).with_children(|button| {
+                        button.spawn(text(label, 20.));
+                    });
+                }
+                row.spawn((ReplayLabel, text("", 20.)));
+            });
+        });
+}
+
+pub fn despawn_replay_ui(mut commands: Commands, ui: Query<Entity, With<ReplayUI>>) {
+    for entity in ui.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub fn handle_replay_buttons(
+    mut buttons: Query<(&Interaction, &ReplayButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut viewer: ResMut<ReplayViewer>,
+    mut speed: ResMut<PlayBackSpeed>,
+    mut app: ResMut<NextState<AppStates>>,
+) {
+    for (interaction, button, mut background) in buttons.iter_mut() {
+        match interaction {
+            Interaction::Pressed => match button {
+                ReplayButton::Start => viewer.seek(0),
+                ReplayButton::Back => viewer.back(),
+                ReplayButton::PlayPause => viewer.toggle(),
+                ReplayButton::Forward => viewer.forward(),
+                ReplayButton::End => {
+                    let last = viewer.len();
+                    viewer.seek(last);
+                }
+                ReplayButton::Slower => speed.0 = (speed.0 * 2.).min(SLOWEST),
+                ReplayButton::Faster => speed.0 = (speed.0 / 2.).max(FASTEST),
+                ReplayButton::Exit => app.set(AppStates::MainMenu),
+            },
+            Interaction::Hovered => *background = BackgroundColor(Color::GRAY),
+            Interaction::None => *background = BackgroundColor(Color::BLACK.with_a(0.9)),
+        }
+    }
+}
+
+pub fn handle_timeline_click(
+    timeline: Query<(&Interaction, &Node, &GlobalTransform), With<ReplayTimeline>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    mut viewer: ResMut<ReplayViewer>,
+) {
+    let Some(cursor) = window.get_single().ok().and_then(|w| w.cursor_position()) else {
+        return;
+    };
+    for (interaction, node, transform) in timeline.iter() {
+        if *interaction != Interaction::Pressed || viewer.is_empty() {
+            continue;
+        }
+        //ui transforms are centered on the node
+        let width = node.size().x.max(1.);
+        let left = transform.translation().x - width / 2.;
+        let ratio = ((cursor.x - left) / width).clamp(0., 1.);
+        let step = (ratio * (viewer.len() - 1) as f32).round() as usize;
+        if step != viewer.step {
+            viewer.seek(step);
+        }
+    }
+}
+
+pub fn update_replay_ui(
+    viewer: Res<ReplayViewer>,
+    speed: Res<PlayBackSpeed>,
+    mut fill: Query<&mut Style, With<ReplayTimelineFill>>,
+    mut label: Query<&mut Text, With<ReplayLabel>>,
+    buttons: Query<(&ReplayButton, &Children)>,
+    mut texts: Query<&mut Text, Without<ReplayLabel>>,
+) {
+    let last = viewer.len().saturating_sub(1).max(1);
+    for mut style in fill.iter_mut() {
+        style.width = Val::Percent(100. * viewer.step as f32 / last as f32);
+    }
+    let turn = match viewer.turn {
+        TurnStates::PlayerTurn(player) => format!("player {player}'s turn"),
+        TurnStates::GameOver { winner, .. } => match viewer.replay.inputs.first() {
+            //in team modes, the whole team of the winner won
+            Some(ReplayInput::Event(GameEvents::Setup { players, mode, .. })) => {
+                format!("players {:?} won", mode.winners(winner, *players))
+            }
+            _ => format!("player {winner} won"),
+        },
+        _ => String::new(),
+    };
+    let value = format!(
+        "{}/{} - {turn} - {} - speed x{}",
+        viewer.step + 1,
+        viewer.len(),
+        viewer.label(viewer.step),
+        0.5 / speed.0,
+    );
+    for mut text in label.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+    let play = if viewer.playing { "Pause" } else { "Play" };
+    for (button, children) in buttons.iter() {
+        if *button != ReplayButton::PlayPause {
+            continue;
+        }
+        for &child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(child) {
+                if text.sections[0].value != play {
+                    text.sections[0].value = play.to_string();
+                }
+            }
+        }
+    }
+}
+
+pub struct ReplayUIPlugin;
+
+impl Plugin for ReplayUIPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ReplayUI>()
+            .register_type::<ReplayButton>()
+            .register_type::<ReplayTimeline>()
+            .register_type::<ReplayTimelineFill>()
+            .register_type::<ReplayLabel>()
+            .add_systems(OnEnter(AppStates::Replay), spawn_replay_ui)
+            .add_systems(OnExit(AppStates::Replay), despawn_replay_ui)
+            .add_systems(
+                Update,
+                (
+                    handle_replay_buttons,
+                    handle_timeline_click,
+                    update_replay_ui,
+                )
+                    .chain()
+                    .run_if(in_state(AppStates::Replay)),
+            );
+    }
+}
@@ -1,4 +1,4 @@
-use crate::players::Player;
+use crate::players::LocalPlayer;
 use crate::prelude::{CardIndex, CardOwners, CardVisibility, MarketOwned, Stacks, StartTransition};
 use crate::stacks::Hand;
 use bevy::diagnostic::DiagnosticsStore;
@@ -7,15 +7,15 @@ use bevy::prelude::*;
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 use bevy_rapier3d::prelude::{DebugRenderContext, RapierDebugRenderPlugin};
 
+type PlayersHands = (With<Hand>, Without<MarketOwned>);
+
 pub fn toggle_debug(
     mut commands: Commands,
     keys: Res<ButtonInput<KeyCode>>,
     mut debug: ResMut<DebugRenderContext>,
     mut root: Query<&mut Visibility, With<FpsRoot>>,
-    enemy_hand: Query<
-        (Entity, &CardOwners, &CardIndex),
-        (With<Hand>, Without<MarketOwned>, Without<Player<0>>),
-    >,
+    enemy_hand: Query<(Entity, &CardOwners, &CardIndex), PlayersHands>,
+    local_player: Res<LocalPlayer>,
 ) {
     if keys.just_pressed(KeyCode::ContextMenu) {
         debug.enabled ^= true;
@@ -25,7 +25,10 @@ pub fn toggle_debug(
         } else {
             Visibility::Hidden
         };
-        for (card, &owner, &index) in enemy_hand.iter() {
+        for (card, &owner, &index) in enemy_hand
+            .iter()
+            .filter(|(_, o, _)| **o != CardOwners::Player(local_player.0))
+        {
             commands.entity(card).insert(StartTransition {
                 owner,
                 stack: Stacks::Hand,
@@ -0,0 +1,60 @@
+use card_game_4::cards::actions::{Action, ActionSet};
+
+fn parse(set: &str) -> ActionSet {
+    ron::from_str(set).unwrap()
+}
+
+#[test]
+fn choices_resolve_within_their_bounds() {
+    let set = parse(
+        "All([One(Atk(2)), Choose(min: 1, max: 2, of: [One(Draw(1)), Optional([One(Eco(2)), One(Life(1))]), One(Atk(1))])])",
+    );
+    let ActionSet::All(parts) = &set else {
+        panic!("{set:?}");
+    };
+    let choice = &parts[1];
+    assert_eq!(choice.resolve_choices(&[]), None);
+    assert_eq!(choice.resolve_choices(&[0, 1, 2]), None);
+    //the choices have to be sorted and distinct
+    assert_eq!(choice.resolve_choices(&[2, 0]), None);
+    assert_eq!(choice.resolve_choices(&[1, 1]), None);
+    assert_eq!(choice.resolve_choices(&[3]), None);
+    assert_eq!(
+        choice.resolve_choices(&[0, 2]),
+        Some(vec![
+            ActionSet::One(Action::Draw(1)),
+            ActionSet::One(Action::Atk(1))
+        ])
+    );
+    //an optional group is applied whole or not at all
+    let optional = &choice.options()[1];
+    assert_eq!(optional.resolve_choices(&[]), Some(vec![]));
+    assert_eq!(
+        optional.resolve_choices(&[0]),
+        Some(vec![ActionSet::All(vec![
+            ActionSet::One(Action::Eco(2)),
+            ActionSet::One(Action::Life(1))
+        ])])
+    );
+    //only choice nodes take choices
+    assert_eq!(parts[0].resolve_choices(&[]), None);
+}
+
+#[test]
+fn choice_bounds_are_capped_by_the_options() {
+    let set = parse("Choose(min: 3, max: 5, of: [One(Draw(1)), One(Eco(1))])");
+    assert_eq!(set.choice_bounds(), (2, 2));
+    assert!(set.resolve_choices(&[0, 1]).is_some());
+}
+
+#[test]
+fn labels_follow_the_grammar() {
+    assert_eq!(
+        parse("Choose(min: 1, max: 1, of: [One(Atk(2)), One(Eco(2))])").label(),
+        "gain 2 attack or gain 2 economy"
+    );
+    assert_eq!(
+        parse("Optional([One(Draw(1)), One(Atk(1))])").label(),
+        "you may draw a card, gain 1 attack"
+    );
+}
@@ -0,0 +1,171 @@
+//the bevy app and the rules engine are fed the same inputs and must end up on the same board
+mod common;
+
+use bevy::prelude::*;
+use card_game_4::bots::decide;
+use card_game_4::bots::heuristic::HeuristicBot;
+use card_game_4::engine::hash::CanonicalBoard;
+use card_game_4::engine::replay::ReplayInput;
+use card_game_4::engine::{Decision, GameState};
+use card_game_4::game::events::GameEvents;
+use card_game_4::game::history::BoardSnapshot;
+use card_game_4::game::modes::GameMode;
+use card_game_4::game::routines::move_to_stack::NextBuyOnDeckFlag;
+use card_game_4::prelude::*;
+use card_game_4::states::turn::TurnStates;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+//the app reveals the hand of the local player, the engine snapshot hides every hand
+fn hide_hands(mut board: BoardSnapshot) -> BoardSnapshot {
+    for card in board.cards.iter_mut() {
+        if card.state.stack == Stacks::Hand {
+            card.state.visibility = CardVisibility::Hidden;
+        }
+    }
+    board
+}
+
+fn differences(app: &BoardSnapshot, engine: &BoardSnapshot) -> Vec<String> {
+    let mut out = Vec::new();
+    for (a, e) in app.cards.iter().zip(engine.cards.iter()) {
+        if a != e {
+            out.push(format!("{a:?}\n{e:?}"));
+        }
+    }
+    for (a, e) in app.players.iter().zip(engine.players.iter()) {
+        if a != e {
+            out.push(format!("{a:?}\n{e:?}"));
+        }
+    }
+    if app.cards.len() != engine.cards.len() || app.players.len() != engine.players.len() {
+        out.push("not the same number of cards or players".to_string());
+    }
+    if (app.next_buy_on_deck, &app.triggers, app.rng)
+        != (engine.next_buy_on_deck, &engine.triggers, engine.rng)
+    {
+        out.push(format!(
+            "{:?} {:?} {:?}\n{:?} {:?} {:?}",
+            app.next_buy_on_deck,
+            app.triggers,
+            app.rng,
+            engine.next_buy_on_deck,
+            engine.triggers,
+            engine.rng
+        ));
+    }
+    out
+}
+
+//half of the inputs come from the heuristic bot (to get games that end), the other half are any
+//legal input (to reach the actions the bot never picks). with concessions, the player whose turn it
+//is sometimes gives up instead of answering a selection or a choice
+fn next_input(state: &GameState, rng: &mut StdRng, concessions: bool) -> Option<ReplayInput> {
+    if let (true, Some(_), Some(player)) = (concessions, state.pending(), state.current_player()) {
+        if rng.gen_bool(0.2) {
+            return Some(ReplayInput::Event(GameEvents::Concede(player)));
+        }
+    }
+    let legal: Vec<ReplayInput> = if state.pending().is_some() {
+        state.legal_answers(16)
+    } else {
+        state
+            .legal_events()
+            .into_iter()
+            .filter(|event| !matches!(event, GameEvents::Concede(_)))
+            .map(ReplayInput::Event)
+            .collect()
+    };
+    if !legal.is_empty() && rng.gen_bool(0.5) {
+        Some(legal[rng.gen_range(0..legal.len())].clone())
+    } else {
+        decide(&mut HeuristicBot, state)
+    }
+}
+
+fn lockstep(seed: u64, players: u8, mode: GameMode, concessions: bool) {
+    let library = common::library();
+    let mut app = common::app(&library);
+    let mut state = GameState::new(library.clone());
+    let setup = GameEvents::Setup {
+        seed,
+        set: 0,
+        players,
+        starting_player: (seed % players as u64) as u8,
+        mode,
+    };
+    state.apply(setup).unwrap();
+    common::push(&mut app, setup);
+    common::settle(&mut app);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut step = 0;
+    while let Some(input) = next_input(&state, &mut rng, concessions) {
+        step += 1;
+        state.input(input.clone()).unwrap();
+        common::input(&mut app, &input);
+        common::settle(&mut app);
+        let mut pending = state.pending().cloned();
+        if let Some(Decision::Selection { cards, .. }) = &mut pending {
+            cards.sort();
+        }
+        assert_eq!(
+            common::decision(&mut app),
+            pending,
+            "seed {seed}, step {step} ({input})"
+        );
+        assert_eq!(
+            app.world.resource::<State<TurnStates>>().get(),
+            &state.turn(),
+            "seed {seed}, step {step} ({input})"
+        );
+        let mut engine = hide_hands(state.board_snapshot(false));
+        if state.current_player().is_none() {
+            //the engine keeps the flag by player and only reports the one of the current player
+            engine.next_buy_on_deck = app.world.resource::<NextBuyOnDeckFlag>().0;
+        }
+        let board = BoardSnapshot::capture(&mut app.world);
+        //networked apps hash their board to compare it with the other side
+        assert_eq!(
+            CanonicalBoard::from(&board).hash(),
+            state.hash(),
+            "seed {seed}, step {step} ({input})"
+        );
+        let differences = differences(&hide_hands(board), &engine);
+        assert!(
+            differences.is_empty(),
+            "seed {seed}, step {step} ({input}), app / engine:\n{}",
+            differences.join("\n")
+        );
+        assert!(step < 5000, "seed {seed}: the game does not end");
+    }
+}
+
+#[test]
+fn two_players() {
+    for seed in 0..6 {
+        lockstep(seed, 2, GameMode::default(), false);
+    }
+}
+
+#[test]
+fn modes() {
+    for (seed, players, mode) in [
+        (1, 4, GameMode::FreeForAll),
+        (2, 3, GameMode::Hunter),
+        (3, 4, GameMode::TeamEmperor),
+        (4, 3, GameMode::Raid),
+    ] {
+        lockstep(seed, players, mode, false);
+    }
+}
+
+#[test]
+fn concessions() {
+    for (seed, players, mode) in [
+        (5, 2, GameMode::default()),
+        (6, 4, GameMode::FreeForAll),
+        (7, 4, GameMode::TeamEmperor),
+    ] {
+        lockstep(seed, players, mode, true);
+    }
+}
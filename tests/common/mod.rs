@@ -0,0 +1,192 @@
+//the default set and its rules engine games, and a headless bevy app running the game plugins
+//without a window: the assets that are only drawn are replaced by empty handles and the ui
+//entities the systems expect are spawned bare
+#![allow(dead_code)] //each test file only uses part of the helpers
+
+use bevy::prelude::*;
+use card_game_4::cards::assets::LoadedModels;
+use card_game_4::cards::transition::PlayBackSpeed;
+use card_game_4::engine::replay::ReplayInput;
+use card_game_4::engine::{CardLibrary, Decision, GameState};
+use card_game_4::game::events::{GameEvent, GameEvents};
+use card_game_4::game::history::board_at_rest;
+use card_game_4::game::modes::GameMode;
+use card_game_4::game::routines::card_action::{Selectable, Selection};
+use card_game_4::game::routines::selection::{answer_selection, selection_open};
+use card_game_4::game::GameStates;
+use card_game_4::prelude::*;
+use card_game_4::states::turn::TurnStates;
+use card_game_4::ui::choice_ui::{answer_choice, ChoiceRoot};
+use card_game_4::ui::player_counters::{AttackButton, ConcedeButton, PassTurnButton};
+use card_game_4::ui::SelectionValidationButton;
+use std::sync::{Arc, OnceLock};
+
+//the default set, read once per test binary
+pub fn library() -> Arc<CardLibrary> {
+    static LIBRARY: OnceLock<Arc<CardLibrary>> = OnceLock::new();
+    LIBRARY
+        .get_or_init(|| Arc::new(CardLibrary::load("assets", "default").unwrap()))
+        .clone()
+}
+
+pub fn setup(seed: u64, players: u8, mode: GameMode) -> GameEvents {
+    GameEvents::Setup {
+        seed,
+        set: 0,
+        players,
+        starting_player: 0,
+        mode,
+    }
+}
+
+//rules engine game of the default set, right after its setup
+pub fn game(seed: u64, players: u8, mode: GameMode) -> GameState {
+    let mut state = GameState::new(library());
+    state.apply(setup(seed, players, mode)).unwrap();
+    state
+}
+
+//put cards of the given names in the hand of the player, in place of the first cards of their
+//hand: each one swaps places with a card of that name taken from anywhere else on the board
+pub fn give(state: &mut GameState, player: u8, names: &[&str]) {
+    let hand = state.stack(CardOwners::Player(player), Stacks::Hand);
+    assert!(names.len() <= hand.len(), "the hand is too small");
+    let mut board = state.board_snapshot(false);
+    let mut taken = Vec::new();
+    for (&name, &slot) in names.iter().zip(&hand) {
+        let card = (0..state.cards().len())
+            .find(|c| state.cards()[*c].name == name && !hand.contains(c) && !taken.contains(c))
+            .unwrap_or_else(|| panic!("no {name} left to give"));
+        taken.push(card);
+        let placed = board.cards[card].state;
+        board.cards[card].state = board.cards[slot].state;
+        board.cards[slot].state = placed;
+    }
+    state.restore_board(&board, state.turn());
+}
+
+//index in the hand of the player of the first card with this name, as played by PlayCard
+pub fn in_hand(state: &GameState, player: u8, name: &str) -> u8 {
+    state
+        .stack(CardOwners::Player(player), Stacks::Hand)
+        .into_iter()
+        .map(|card| state.card(card).unwrap())
+        .find(|card| card.name == name)
+        .unwrap_or_else(|| panic!("no {name} in the hand of player {player}"))
+        .index as u8
+}
+
+pub fn app(library: &CardLibrary) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        bevy::input::InputPlugin,
+        card_game_4::states::StatesPlugin,
+        card_game_4::cards::CardsPlugin,
+        card_game_4::players::PlayerPlugin,
+        card_game_4::stacks::StacksPlugin,
+        card_game_4::game::GamePlugin,
+    ));
+    app.init_asset::<bevy::render::render_resource::Shader>()
+        .add_plugins(bevy::gizmos::GizmoPlugin)
+        .init_asset::<bevy::gltf::Gltf>()
+        .init_asset::<Mesh>()
+        .init_asset::<StandardMaterial>()
+        .init_asset::<Image>()
+        .init_asset::<Font>()
+        .init_resource::<card_game_4::ui::hot_seat::HotSeat>()
+        .init_resource::<bevy_rapier3d::render::DebugRenderContext>()
+        .insert_resource(PlayBackSpeed(0.));
+    app.world.spawn((
+        PassTurnButton { hovered: false },
+        ConcedeButton { hovered: false },
+        AttackButton {
+            player: None,
+            hovered: false,
+        },
+    ));
+    app.world
+        .spawn((SelectionValidationButton, Visibility::Hidden));
+    app.update();
+    let mut models = app.world.resource_mut::<LoadedModels>();
+    for name in library.cards.keys() {
+        models
+            .front_materials
+            .insert(name.clone(), Handle::default());
+    }
+    app.world
+        .resource_mut::<NextState<AppStates>>()
+        .set(AppStates::Playing);
+    app.update();
+    app
+}
+
+//update until the board is at rest or waiting for a selection or a choice
+pub fn settle(app: &mut App) {
+    for _ in 0..1000 {
+        app.update();
+        let waiting = match app.world.resource::<State<GameStates>>().get() {
+            GameStates::MainLoop => board_at_rest(&mut app.world),
+            GameStates::SelectionInput => selection_open(&app.world),
+            GameStates::ChoiceInput => app
+                .world
+                .query::<&ChoiceRoot>()
+                .get_single(&app.world)
+                .is_ok_and(|root| !root.finished),
+        };
+        let transition = app.world.resource::<NextState<TurnStates>>().0.is_some()
+            || app.world.resource::<NextState<GameStates>>().0.is_some();
+        if waiting && !transition {
+            return;
+        }
+    }
+    panic!("the app never settled");
+}
+
+//the selection or choice the app waits for, in the terms of the rules engine
+pub fn decision(app: &mut App) -> Option<Decision> {
+    match app.world.resource::<State<GameStates>>().get() {
+        GameStates::MainLoop => None,
+        GameStates::SelectionInput => {
+            let selection = app.world.resource::<Selection>();
+            let (player, min, max) = (selection.player, selection.min, selection.max);
+            let mut cards: Vec<usize> = app
+                .world
+                .query_filtered::<&CardId, With<Selectable>>()
+                .iter(&app.world)
+                .map(|id| id.0)
+                .collect();
+            cards.sort();
+            Some(Decision::Selection {
+                player,
+                min,
+                max,
+                cards,
+            })
+        }
+        GameStates::ChoiceInput => {
+            let root = app
+                .world
+                .query::<&ChoiceRoot>()
+                .get_single(&app.world)
+                .ok()?;
+            Some(Decision::Choice {
+                player: root.player,
+                set: root.set.clone(),
+            })
+        }
+    }
+}
+
+pub fn input(app: &mut App, input: &ReplayInput) {
+    match input {
+        ReplayInput::Event(event) => push(app, *event),
+        ReplayInput::Select(cards) => answer_selection(&mut app.world, cards),
+        ReplayInput::Choose(choices) => answer_choice(&mut app.world, choices),
+    }
+}
+
+pub fn push(app: &mut App, event: GameEvents) {
+    app.world.resource_mut::<GameEvent>().push(event);
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use card_game_4::bots::decide;
+use card_game_4::bots::heuristic::HeuristicBot;
+use card_game_4::engine::GameState;
+use card_game_4::game::events::GameEvents;
+use card_game_4::game::modes::GameMode;
+use card_game_4::states::turn::{GameOverReason, TurnStates};
+
+#[test]
+fn concession_ends_a_duel() {
+    assert!(GameState::new(common::library())
+        .apply(GameEvents::Concede(0))
+        .is_err());
+    let mut state = common::game(1, 2, GameMode::default());
+    state.apply(GameEvents::Concede(0)).unwrap();
+    assert_eq!(
+        state.turn(),
+        TurnStates::GameOver {
+            winner: 1,
+            reason: GameOverReason::Conceded
+        }
+    );
+    assert!(state.apply(GameEvents::PassTurn).is_err());
+}
+
+#[test]
+fn others_keep_playing() {
+    let mut state = common::game(1, 3, GameMode::FreeForAll);
+    //someone else giving up does not change the turn
+    state.apply(GameEvents::Concede(2)).unwrap();
+    assert_eq!(state.turn(), TurnStates::PlayerTurn(0));
+    assert_eq!(state.players()[2].life, 0);
+    //the player whose turn it is gives it to the next one still alive
+    state.apply(GameEvents::Concede(0)).unwrap();
+    assert_eq!(
+        state.turn(),
+        TurnStates::GameOver {
+            winner: 1,
+            reason: GameOverReason::Conceded
+        }
+    );
+}
+
+#[test]
+fn concession_drops_the_pending_decision() {
+    let mut state = common::game(1, 4, GameMode::FreeForAll);
+    while state.pending().is_none() {
+        let input = decide(&mut HeuristicBot, &state).unwrap();
+        state.input(input).unwrap();
+    }
+    let player = state.current_player().unwrap();
+    state.apply(GameEvents::Concede(player)).unwrap();
+    assert_eq!(state.pending(), None);
+    assert_eq!(state.turn(), TurnStates::PlayerTurn((player + 1) % 4));
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use card_game_4::bots::decide;
+use card_game_4::bots::heuristic::HeuristicBot;
+use card_game_4::cards::actions::counters::Counter;
+use card_game_4::engine::GameState;
+use card_game_4::game::events::GameEvents;
+
+fn counter(state: &GameState, player: u8, counter: Counter) -> usize {
+    counter.value(&state.player(player).unwrap().tracker)
+}
+
+#[test]
+fn turns_start_with_no_card_drawn() {
+    let mut state = common::game(1, 2, Default::default());
+    for turn in 0..6 {
+        let player = state.current_player().unwrap();
+        assert_eq!(
+            counter(&state, player, Counter::CardsDrawn),
+            0,
+            "turn {turn}"
+        );
+        state.apply(GameEvents::PassTurn).unwrap();
+        //the end of turn draws are not counted for the turn that ended either
+        assert_eq!(
+            counter(&state, player, Counter::CardsDrawn),
+            0,
+            "turn {turn}"
+        );
+    }
+}
+
+#[test]
+fn life_changes_are_counted() {
+    for seed in 0..20 {
+        let mut state = common::game(seed, 2, Default::default());
+        while let Some(player) = state.current_player() {
+            let target = 1 - player;
+            let attack = GameEvents::Attack {
+                player: target,
+                as_much_as_possible: false,
+                base_index: None,
+            };
+            if state.pending().is_none() && state.legal_events().contains(&attack) {
+                let lost = counter(&state, target, Counter::LifeLost);
+                let life = state.player(target).unwrap().life;
+                state.apply(attack).unwrap();
+                assert_eq!(state.player(target).unwrap().life, life - 1);
+                assert_eq!(counter(&state, target, Counter::LifeLost), lost + 1);
+                assert_eq!(counter(&state, player, Counter::LifeLost), 0);
+                return;
+            }
+            let input = decide(&mut HeuristicBot, &state).unwrap();
+            state.input(input).unwrap();
+        }
+    }
+    panic!("no player was attacked");
+}
+
+#[test]
+fn life_gained_this_turn() {
+    for seed in 0..50 {
+        let mut state = common::game(seed, 2, Default::default());
+        for _ in 0..200 {
+            let Some(player) = state.current_player() else {
+                break;
+            };
+            let life = state.player(player).unwrap().life;
+            let gained = counter(&state, player, Counter::LifeGained);
+            let input = decide(&mut HeuristicBot, &state).unwrap();
+            state.input(input).unwrap();
+            if state.current_player() == Some(player) && state.player(player).unwrap().life > life {
+                let delta = (state.player(player).unwrap().life - life) as usize;
+                assert_eq!(counter(&state, player, Counter::LifeGained), gained + delta);
+                return;
+            }
+        }
+    }
+    panic!("no player gained life");
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use card_game_4::engine::{Decision, GameState};
+use card_game_4::game::events::GameEvents;
+use card_game_4::prelude::{CardOwners, Stacks};
+
+fn answer(state: &mut GameState) {
+    while let Some(decision) = state.pending().cloned() {
+        match decision {
+            Decision::Selection { cards, min, .. } => {
+                state.select(cards.into_iter().take(min).collect()).unwrap();
+            }
+            Decision::Choice { .. } => {
+                state.choose(vec![0]).unwrap();
+            }
+        }
+    }
+}
+
+fn play(state: &mut GameState, name: &str) {
+    let index = common::in_hand(state, 0, name);
+    state.apply(GameEvents::PlayCard(index)).unwrap();
+    answer(state);
+}
+
+fn counters(state: &GameState) -> (i32, i32) {
+    let player = state.player(0).unwrap();
+    (player.attack, player.economy)
+}
+
+#[test]
+fn ships_played_after_fleet_hq_give_attack() {
+    let mut state = common::game(1, 2, Default::default());
+    common::give(&mut state, 0, &["Scout", "Fleet-HQ", "Scout"]);
+    //a ship played before Fleet HQ only gives what it says
+    play(&mut state, "Scout");
+    assert_eq!(counters(&state), (0, 1));
+    play(&mut state, "Fleet-HQ");
+    let index = state
+        .stack(CardOwners::Player(0), Stacks::Bases)
+        .into_iter()
+        .map(|card| state.card(card).unwrap())
+        .find(|card| card.name == "Fleet-HQ")
+        .unwrap()
+        .index;
+    state
+        .apply(GameEvents::ActivateCard {
+            base: true,
+            index: index as u32,
+            action: 0,
+        })
+        .unwrap();
+    answer(&mut state);
+    //a Scout only gives economy, the attack comes from Fleet HQ
+    play(&mut state, "Scout");
+    assert_eq!(counters(&state), (1, 2));
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use card_game_4::engine::Decision;
+use card_game_4::game::events::GameEvents;
+use card_game_4::prelude::{CardOwners, Stacks};
+
+//the discards stored by EnemyDiscard are asked to the target when their turn starts
+#[test]
+fn discards_are_applied_at_the_start_of_the_turn() {
+    let mut state = common::game(1, 2, Default::default());
+    //the Imperial Fighter attacks and makes the only opponent discard a card
+    common::give(&mut state, 0, &["Imperial-Fighter"]);
+    let fighter = common::in_hand(&state, 0, "Imperial-Fighter");
+    state.apply(GameEvents::PlayCard(fighter)).unwrap();
+    assert_eq!(state.pending(), None);
+    assert_eq!(state.player(1).unwrap().tracker.cards_to_discard, 1);
+    //nothing is asked before their turn
+    let owner = CardOwners::Player(1);
+    let hand = state.stack(owner, Stacks::Hand);
+    state.apply(GameEvents::PassTurn).unwrap();
+    assert_eq!(state.stack(owner, Stacks::Hand), hand);
+    let Some(Decision::Selection {
+        player,
+        min,
+        max,
+        cards,
+    }) = state.pending().cloned()
+    else {
+        panic!("no discard asked: {:?}", state.pending());
+    };
+    assert_eq!((player, min, max), (1, 1, 1));
+    assert_eq!(cards, hand);
+    assert!(state.select(vec![]).is_err());
+    state.select(vec![cards[0]]).unwrap();
+    assert_eq!(state.stack(owner, Stacks::Hand).len(), hand.len() - 1);
+    assert!(state.stack(owner, Stacks::DiscardPile).contains(&cards[0]));
+    assert_eq!(state.player(1).unwrap().tracker.cards_to_discard, 0);
+    assert_eq!(state.pending(), None);
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use card_game_4::engine::gym::{Environment, ObservationMode};
+use card_game_4::engine::replay::ReplayInput;
+use card_game_4::game::events::GameEvents;
+use card_game_4::game::modes::GameMode;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+#[test]
+fn random_agents_play_until_the_end() {
+    let library = common::library();
+    for players in [2, 3] {
+        let mut env = Environment::new(library.clone(), players, Default::default());
+        let size = env.observation_size();
+        let mut rng = StdRng::seed_from_u64(players as u64);
+        for seed in 0..3 {
+            let observation = env.reset(seed).unwrap();
+            assert_eq!(observation.features.len(), size);
+            assert_eq!(Some(observation.player), env.acting_player());
+            let mut modes = Vec::new();
+            for _ in 0..20000 {
+                let actions = env.legal_actions();
+                assert!(!actions.is_empty());
+                for action in &actions {
+                    assert!(
+                        env.state().clone().input(action.clone()).is_ok(),
+                        "{action:?}"
+                    );
+                }
+                //passing too often would make the games endless
+                let played: Vec<_> = actions
+                    .iter()
+                    .filter(|a| **a != ReplayInput::Event(GameEvents::PassTurn))
+                    .collect();
+                let action = if !played.is_empty() && rng.gen_bool(0.9) {
+                    played[rng.gen_range(0..played.len())].clone()
+                } else {
+                    actions[rng.gen_range(0..actions.len())].clone()
+                };
+                let player = env.acting_player().unwrap();
+                let (observation, reward, done) = env.step(action).unwrap();
+                assert_eq!(observation.features.len(), size);
+                if !modes.contains(&observation.mode) {
+                    modes.push(observation.mode);
+                }
+                if done {
+                    //the last input wins the game, the others lose it
+                    assert_eq!(reward, 1.);
+                    assert_eq!(observation.mode, ObservationMode::GameOver);
+                    let rewards = env.rewards();
+                    assert_eq!(rewards[player as usize], 1.);
+                    assert_eq!(rewards.iter().sum::<f32>(), 2. - players as f32);
+                    break;
+                }
+                assert_eq!(reward, 0.);
+                assert_eq!(Some(observation.player), env.acting_player());
+            }
+            assert!(env.done(), "{players} players, seed {seed}");
+            assert!(env.legal_actions().is_empty());
+            assert!(modes.contains(&ObservationMode::Selection));
+        }
+    }
+}
+
+#[test]
+fn observations_start_with_the_counters_of_each_seat() {
+    let library = common::library();
+    let mut env = Environment::new(library, 2, Default::default());
+    let observation = env.reset(7).unwrap();
+    let player = observation.player;
+    assert_eq!(observation.mode, ObservationMode::Main);
+    let counters = |p: u8| {
+        let state = env.state().player(p).unwrap();
+        [state.life, state.attack, state.economy].map(|c| c as f32)
+    };
+    assert_eq!(observation.features[0..3], counters(player));
+    assert_eq!(observation.features[6..9], counters(1 - player));
+    //the other player sees the same counters, their own first
+    let other = env.observe(1 - player);
+    assert_eq!(other.features[0..6], observation.features[6..12]);
+    assert_eq!(other.features[6..12], observation.features[0..6]);
+}
+
+//in team modes, the teammates of the winner share the reward
+#[test]
+fn the_winning_team_is_rewarded() {
+    let library = common::library();
+    let mut env = Environment::new(library, 4, GameMode::TeamEmperor);
+    env.reset(0).unwrap();
+    assert_eq!(env.rewards(), vec![0.; 4]);
+    let player = env.acting_player().unwrap();
+    let (_, reward, done) = env
+        .step(ReplayInput::Event(GameEvents::Concede(player)))
+        .unwrap();
+    assert!(done);
+    assert_eq!(reward, -1.);
+    let rewards = env.rewards();
+    for seat in 0..4 {
+        let teammate = GameMode::TeamEmperor.team(seat, 4) == GameMode::TeamEmperor.team(player, 4);
+        assert_eq!(rewards[seat as usize], if teammate { -1. } else { 1. });
+    }
+}
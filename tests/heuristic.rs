@@ -0,0 +1,76 @@
+mod common;
+
+use card_game_4::bots::decide;
+use card_game_4::bots::heuristic::HeuristicBot;
+use card_game_4::engine::GameState;
+use card_game_4::game::events::GameEvents;
+use card_game_4::prelude::*;
+use card_game_4::states::turn::TurnStates;
+
+#[test]
+fn bots_finish_their_games() {
+    for seed in 0..20 {
+        let mut state = common::game(seed, 2, Default::default());
+        let mut steps = 0;
+        while let Some(input) = decide(&mut HeuristicBot, &state) {
+            state.input(input).unwrap();
+            steps += 1;
+            assert!(steps < 5000, "seed {seed}: the game does not end");
+        }
+        assert!(
+            matches!(state.turn(), TurnStates::GameOver { .. }),
+            "seed {seed}"
+        );
+    }
+}
+
+//the opponent can be attacked if no outpost is in the way, or if one of them can be destroyed
+fn attackable(state: &GameState, target: u8, attack: i32) -> bool {
+    let outposts: Vec<i32> = state
+        .stack(CardOwners::Player(target), Stacks::Bases)
+        .into_iter()
+        .map(|c| &state.cards()[c])
+        .filter(|c| matches!(c.kind, CardKinds::Outpost(_)))
+        .map(|c| c.life.0)
+        .collect();
+    outposts.is_empty() || outposts.iter().any(|&life| life <= attack)
+}
+
+#[test]
+fn attacks_go_around_outposts_they_can_not_destroy() {
+    let mut around = 0;
+    for seed in 0..30 {
+        let mut state = common::game(seed, 3, Default::default());
+        for _ in 0..3000 {
+            let Some(player) = state.current_player() else {
+                break;
+            };
+            let attack = state.player(player).unwrap().attack;
+            if state.pending().is_none() && attack > 0 {
+                let targets = state.targets(player);
+                let weakest = *targets
+                    .iter()
+                    .min_by_key(|&&p| state.player(p).unwrap().life)
+                    .unwrap();
+                let attacks: Vec<GameEvents> = HeuristicBot::ranked(&state)
+                    .into_iter()
+                    .filter(|e| matches!(e, GameEvents::Attack { .. }))
+                    .collect();
+                let possible = targets.iter().any(|&t| attackable(&state, t, attack));
+                assert_eq!(attacks.len(), possible as usize, "seed {seed}");
+                for attack in attacks {
+                    assert!(
+                        state.clone().apply(attack).is_ok(),
+                        "seed {seed}: {attack:?}"
+                    );
+                    if let GameEvents::Attack { player: target, .. } = attack {
+                        around += (target != weakest) as usize;
+                    }
+                }
+            }
+            let input = decide(&mut HeuristicBot, &state).unwrap();
+            state.input(input).unwrap();
+        }
+    }
+    assert!(around > 0, "no opponent was attacked around an outpost");
+}
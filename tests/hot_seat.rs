@@ -0,0 +1,132 @@
+mod common;
+
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use card_game_4::bots::heuristic::HeuristicBot;
+use card_game_4::bots::Bots;
+use card_game_4::game::events::GameEvents;
+use card_game_4::players::LocalPlayer;
+use card_game_4::prelude::*;
+use card_game_4::states::turn::TurnStates;
+use card_game_4::ui::hot_seat::{
+    follow_acting_player, handle_handoff_button, HandoffButton, HandoffCurtain, HotSeat,
+};
+
+const SETUP: GameEvents = GameEvents::Setup {
+    seed: 4,
+    set: 0,
+    players: 2,
+    starting_player: 0,
+    mode: card_game_4::game::modes::GameMode::FreeForAll,
+};
+
+fn hot_seat() -> App {
+    let library = common::library();
+    let mut app = common::app(&library);
+    app.world
+        .resource_mut::<bevy_rapier3d::render::DebugRenderContext>()
+        .enabled = false;
+    app.world.resource_mut::<HotSeat>().enabled = true;
+    common::push(&mut app, SETUP);
+    common::settle(&mut app);
+    app
+}
+
+//players whose hand is face up
+fn visible_hands(app: &mut App) -> Vec<CardOwners> {
+    let mut owners = Vec::new();
+    for (owner, visibility) in app
+        .world
+        .query_filtered::<(&CardOwners, &CardVisibility), With<Hand>>()
+        .iter(&app.world)
+    {
+        if *visibility == CardVisibility::Visible && !owners.contains(owner) {
+            owners.push(*owner);
+        }
+    }
+    owners
+}
+
+fn curtains(app: &mut App) -> usize {
+    app.world
+        .query_filtered::<(), With<HandoffCurtain>>()
+        .iter(&app.world)
+        .count()
+}
+
+fn pass_turn(app: &mut App) {
+    common::push(app, GameEvents::PassTurn);
+    common::settle(app);
+    app.world.run_system_once(follow_acting_player);
+    common::settle(app);
+}
+
+fn take_device(app: &mut App) {
+    let button = app
+        .world
+        .query_filtered::<Entity, With<HandoffButton>>()
+        .single(&app.world);
+    app.world.entity_mut(button).insert(Interaction::Pressed);
+    app.world.run_system_once(handle_handoff_button);
+    common::settle(app);
+}
+
+#[test]
+fn the_device_goes_to_the_next_player_behind_a_curtain() {
+    let mut app = hot_seat();
+    app.world.run_system_once(follow_acting_player);
+    assert_eq!(curtains(&mut app), 0);
+    assert_eq!(visible_hands(&mut app), [CardOwners::Player(0)]);
+
+    pass_turn(&mut app);
+    assert_eq!(
+        app.world.resource::<State<TurnStates>>().get(),
+        &TurnStates::PlayerTurn(1)
+    );
+    assert_eq!(app.world.resource::<LocalPlayer>().0, 1);
+    assert_eq!(app.world.resource::<HotSeat>().handoff, Some(1));
+    assert_eq!(curtains(&mut app), 1);
+    //nobody sees a hand until the next player takes the device
+    assert!(visible_hands(&mut app).is_empty());
+    take_device(&mut app);
+    assert_eq!(app.world.resource::<HotSeat>().handoff, None);
+    assert_eq!(curtains(&mut app), 0);
+    assert_eq!(visible_hands(&mut app), [CardOwners::Player(1)]);
+
+    pass_turn(&mut app);
+    assert_eq!(app.world.resource::<LocalPlayer>().0, 0);
+    take_device(&mut app);
+    assert_eq!(visible_hands(&mut app), [CardOwners::Player(0)]);
+}
+
+#[test]
+fn the_device_stays_with_the_humans() {
+    let mut app = hot_seat();
+    app.world.insert_resource(Bots::new(vec![1], HeuristicBot));
+    pass_turn(&mut app);
+    assert_eq!(app.world.resource::<LocalPlayer>().0, 0);
+    assert_eq!(app.world.resource::<HotSeat>().handoff, None);
+    assert_eq!(curtains(&mut app), 0);
+}
+
+//where the deck of a player lies on the table, seen from above
+fn deck_place(app: &mut App, player: u8) -> Vec2 {
+    let (_, transform) = app
+        .world
+        .query_filtered::<(&CardOwners, &Transform), With<PlayerDeck>>()
+        .iter(&app.world)
+        .find(|(owner, _)| **owner == CardOwners::Player(player))
+        .unwrap();
+    transform.translation.xz()
+}
+
+//the table turns with the device: the stacks of the player taking it move to the bottom seat
+#[test]
+fn every_stack_follows_the_table() {
+    let mut app = hot_seat();
+    let (bottom, top) = (deck_place(&mut app, 0), deck_place(&mut app, 1));
+    assert_ne!(bottom, top);
+    pass_turn(&mut app);
+    assert_eq!(deck_place(&mut app, 1), bottom);
+    assert_eq!(deck_place(&mut app, 0), top);
+}
@@ -0,0 +1,175 @@
+mod common;
+
+use bevy::prelude::*;
+use card_game_4::bots::decide;
+use card_game_4::bots::heuristic::HeuristicBot;
+use card_game_4::cards::assets::LoadedLibrary;
+use card_game_4::engine::GameState;
+use card_game_4::game::event_handlers::EngineBoard;
+use card_game_4::game::events::{BuyFrom, GameEvent, GameEvents};
+use card_game_4::game::modes::GameMode;
+use card_game_4::states::turn::{GameOverReason, TurnStates};
+
+//every event that could be sent with these many players, legal or not
+fn candidates(players: u8) -> Vec<GameEvents> {
+    let mut all = vec![GameEvents::PassTurn, GameEvents::BuyCard(BuyFrom::Joker)];
+    all.extend((0..12).map(GameEvents::PlayCard));
+    all.extend((0..6).map(|s| GameEvents::BuyCard(BuyFrom::Market(s))));
+    all.extend((0..players + 1).map(GameEvents::Concede));
+    for base in [false, true] {
+        for index in 0..8 {
+            for action in 0..4 {
+                all.push(GameEvents::ActivateCard {
+                    base,
+                    index,
+                    action,
+                });
+            }
+        }
+    }
+    for player in 0..players + 1 {
+        for as_much_as_possible in [true, false] {
+            for base_index in [None, Some(0), Some(1), Some(2), Some(3)] {
+                all.push(GameEvents::Attack {
+                    player,
+                    as_much_as_possible,
+                    base_index,
+                });
+            }
+        }
+    }
+    all
+}
+
+#[test]
+fn listed_events_are_exactly_the_accepted_ones() {
+    let library = common::library();
+    for (seed, players, mode) in [
+        (1, 2, GameMode::FreeForAll),
+        (2, 4, GameMode::Hunter),
+        (3, 4, GameMode::TeamEmperor),
+        (4, 3, GameMode::FreeForAll),
+    ] {
+        let mut state = GameState::new(library.clone());
+        assert!(state.legal_events().is_empty());
+        state
+            .apply(GameEvents::Setup {
+                seed,
+                set: 0,
+                players,
+                starting_player: 0,
+                mode,
+            })
+            .unwrap();
+        let all = candidates(players);
+        let mut steps = 0;
+        while let Some(input) = decide(&mut HeuristicBot, &state) {
+            let acting = state.input_player(&input).unwrap();
+            assert_eq!(state.legal_events(), state.legal_events_for(acting));
+            for player in 0..players + 1 {
+                let legal = state.legal_events_for(player);
+                for event in &all {
+                    //a concession comes from the conceding player, the rest from the current one
+                    let sender = match event {
+                        GameEvents::Concede(p) => *p,
+                        _ => state.current_player().unwrap_or(acting),
+                    };
+                    if sender != player {
+                        assert!(!legal.contains(event), "{event:?} listed for {player}");
+                        continue;
+                    }
+                    assert_eq!(
+                        state.clone().apply(*event).is_ok(),
+                        legal.contains(event),
+                        "{event:?} in {:?}",
+                        state.turn()
+                    );
+                }
+                assert!(legal.iter().all(|e| all.contains(e)));
+            }
+            //only the seat expected to act may concede through legal_events
+            let concessions: Vec<_> = state
+                .legal_events()
+                .into_iter()
+                .filter(|e| matches!(e, GameEvents::Concede(_)))
+                .collect();
+            assert_eq!(concessions, [GameEvents::Concede(acting)]);
+            state.input(input).unwrap();
+            steps += 1;
+            assert!(steps < 5000, "seed {seed}: the game does not end");
+        }
+        assert!(state.legal_events().is_empty());
+    }
+}
+
+const SETUP: GameEvents = GameEvents::Setup {
+    seed: 3,
+    set: 0,
+    players: 3,
+    starting_player: 0,
+    mode: GameMode::FreeForAll,
+};
+
+fn turn(app: &App) -> TurnStates {
+    *app.world.resource::<State<TurnStates>>().get()
+}
+
+#[test]
+fn the_app_refuses_what_the_engine_does_not_list() {
+    let library = common::library();
+    let mut app = common::app(&library);
+    common::push(&mut app, SETUP);
+    common::settle(&mut app);
+    assert_eq!(turn(&app), TurnStates::PlayerTurn(0));
+    //unknown players, cards that are not in the hand, attacks without attack points
+    for event in [
+        GameEvents::Concede(3),
+        GameEvents::PlayCard(11),
+        GameEvents::Attack {
+            player: 1,
+            as_much_as_possible: true,
+            base_index: None,
+        },
+    ] {
+        common::push(&mut app, event);
+        common::settle(&mut app);
+        assert_eq!(app.world.resource::<GameEvent>().log(), &[SETUP]);
+    }
+    //another seat concedes out of turn, and can not concede twice
+    common::push(&mut app, GameEvents::Concede(1));
+    common::settle(&mut app);
+    assert_eq!(turn(&app), TurnStates::PlayerTurn(0));
+    common::push(&mut app, GameEvents::Concede(1));
+    common::settle(&mut app);
+    assert_eq!(
+        app.world.resource::<GameEvent>().log(),
+        &[SETUP, GameEvents::Concede(1)]
+    );
+    common::push(&mut app, GameEvents::Concede(0));
+    common::settle(&mut app);
+    assert_eq!(
+        turn(&app),
+        TurnStates::GameOver {
+            winner: 2,
+            reason: GameOverReason::Conceded
+        }
+    );
+}
+
+//the board is built from the cards loaded by the set plugin, without them nothing is accepted
+#[test]
+fn events_are_refused_without_the_library() {
+    let library = common::library();
+    let mut app = common::app(&library);
+    common::push(&mut app, SETUP);
+    common::settle(&mut app);
+    let loaded = app.world.resource::<LoadedLibrary>().0.clone().unwrap();
+    assert_eq!(loaded.id, 0);
+    assert_eq!(loaded.cards.len(), library.cards.len());
+    app.world.resource_mut::<LoadedLibrary>().0 = None;
+    *app.world.resource_mut::<EngineBoard>() = EngineBoard::default();
+    common::push(&mut app, GameEvents::PassTurn);
+    common::settle(&mut app);
+    assert_eq!(app.world.resource::<GameEvent>().log(), &[SETUP]);
+    assert_eq!(turn(&app), TurnStates::PlayerTurn(0));
+}
@@ -0,0 +1,141 @@
+use bevy::prelude::*;
+use card_game_4::bots::{BotKind, Bots};
+use card_game_4::game::modes::GameMode;
+use card_game_4::players::{LocalPlayer, MAXIMUM_PLAYERS};
+use card_game_4::ui::hot_seat::HotSeat;
+use card_game_4::ui::main_menu::{GameConfig, MenuOption, PlayerKind};
+
+fn config() -> GameConfig {
+    GameConfig::new(vec![PlayerKind::Human, PlayerKind::Bot], BotKind::Heuristic)
+}
+
+//values taken by a setting when it is changed a few times in a row
+fn cycle(config: &mut GameConfig, option: MenuOption, forward: bool) -> Vec<String> {
+    (0..4)
+        .map(|_| {
+            config.change(option, forward);
+            config.value(option)
+        })
+        .collect()
+}
+
+#[test]
+fn the_sets_found_in_the_assets_are_offered() {
+    let mut config = config();
+    assert_eq!(config.set, 0);
+    assert!(config.sets.contains(&(0, "Default".to_string())));
+    assert!(config.sets.contains(&(1, "Debug bases".to_string())));
+    let mut ids = vec![config.set];
+    for _ in 1..config.sets.len() {
+        config.change(MenuOption::Set, true);
+        ids.push(config.set);
+    }
+    ids.sort();
+    assert_eq!(ids, [0, 1]);
+    config.change(MenuOption::Set, true);
+    assert_eq!(config.set, 0);
+}
+
+#[test]
+fn only_the_second_seat_of_two_can_be_remote() {
+    let mut config = config();
+    config.change(MenuOption::Player(1), false);
+    assert_eq!(
+        cycle(&mut config, MenuOption::Player(1), true),
+        ["Bot", "Remote", "Human", "Bot"]
+    );
+    assert_eq!(
+        cycle(&mut config, MenuOption::Player(1), false),
+        ["Human", "Remote", "Bot", "Human"]
+    );
+    assert_eq!(
+        cycle(&mut config, MenuOption::Player(0), true),
+        ["Bot", "Human", "Bot", "Human"]
+    );
+    //a third player makes it a local game
+    config.change(MenuOption::Players, true);
+    assert_eq!(
+        config.players,
+        [PlayerKind::Human, PlayerKind::Human, PlayerKind::Human]
+    );
+}
+
+#[test]
+fn settings_stay_compatible_with_the_mode() {
+    let mut config = config();
+    for _ in 0..8 {
+        config.change(MenuOption::Mode, true);
+        for _ in 0..MAXIMUM_PLAYERS + 1 {
+            assert!(config.mode.valid_player_count(config.players.len() as u8));
+            config.change(MenuOption::Players, true);
+        }
+    }
+    while config.mode != GameMode::TeamEmperor {
+        config.change(MenuOption::Mode, true);
+    }
+    assert_eq!(config.players.len() % 2, 0);
+    //the starting player is random, then each seat in turn
+    config.change(MenuOption::Players, false);
+    let players = config.players.len();
+    let mut starting = Vec::new();
+    for _ in 0..=players {
+        starting.push(config.starting_player);
+        config.change(MenuOption::StartingPlayer, true);
+    }
+    assert_eq!(starting[0], None);
+    assert_eq!(
+        starting[1..],
+        (0..players as u8).map(Some).collect::<Vec<_>>()
+    );
+    //removing seats forgets a starting player who is no longer there
+    config.change(MenuOption::StartingPlayer, false);
+    config.change(MenuOption::Mode, true);
+    while config.players.len() > 2 {
+        config.change(MenuOption::Players, false);
+    }
+    assert!(config.starting_player.is_none_or(|p| p < 2));
+}
+
+#[test]
+fn the_setup_uses_the_fixed_settings() {
+    let mut config = config();
+    for _ in 0..4 {
+        let setup = config.setup();
+        assert_eq!(setup.players, 2);
+        assert!(setup.starting_player < 2);
+    }
+    config.change(MenuOption::Seed, true);
+    config.change(MenuOption::Seed, true);
+    config.change(MenuOption::StartingPlayer, false);
+    assert_eq!(config.value(MenuOption::Seed), "1");
+    let setup = config.setup();
+    assert_eq!((setup.seed, setup.starting_player), (1, 1));
+    config.change(MenuOption::Seed, false);
+    config.change(MenuOption::Seed, false);
+    assert_eq!(config.value(MenuOption::Seed), "Random");
+}
+
+#[test]
+fn local_games_get_their_bots_and_hot_seat() {
+    let mut world = World::new();
+    world.init_resource::<Bots>();
+    world.init_resource::<HotSeat>();
+    world.init_resource::<LocalPlayer>();
+    let mut config = config();
+    config.change(MenuOption::Players, true);
+    config.change(MenuOption::Player(0), true);
+    assert_eq!(
+        config.players,
+        [PlayerKind::Bot, PlayerKind::Bot, PlayerKind::Human]
+    );
+    assert!(config.apply(&mut world));
+    assert_eq!(world.resource::<Bots>().seats, [0, 1]);
+    assert!(!world.resource::<HotSeat>().enabled);
+    assert_eq!(world.resource::<LocalPlayer>().0, 2);
+
+    config.change(MenuOption::Player(1), false);
+    assert!(config.apply(&mut world));
+    assert_eq!(world.resource::<Bots>().seats, [0]);
+    assert!(world.resource::<HotSeat>().enabled);
+    assert_eq!(world.resource::<LocalPlayer>().0, 1);
+}
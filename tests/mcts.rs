@@ -0,0 +1,108 @@
+mod common;
+
+use card_game_4::bots::heuristic::HeuristicBot;
+use card_game_4::bots::mcts::{Budget, MctsBot};
+use card_game_4::bots::{decide, Bot, BotPlugin, Bots};
+use card_game_4::engine::view::PlayerView;
+use card_game_4::engine::GameState;
+use card_game_4::game::events::GameEvent;
+use std::time::{Duration, Instant};
+
+//the player expected to give the next input
+fn deciding(state: &GameState) -> Option<u8> {
+    state
+        .pending()
+        .map(|decision| decision.player())
+        .or(state.current_player())
+}
+
+//the hidden stacks are listed in the order their cards were found
+fn sorted(mut view: PlayerView) -> PlayerView {
+    view.hidden
+        .sort_by_key(|h| (format!("{:?}", h.owner), format!("{:?}", h.stack)));
+    view
+}
+
+#[test]
+fn determinized_boards_look_the_same_to_the_player() {
+    let mut state = common::game(2, 3, Default::default());
+    let mut changed = 0;
+    for step in 0..600 {
+        let Some(player) = deciding(&state) else {
+            break;
+        };
+        let mut sample = state.clone();
+        sample.determinize(player, step);
+        assert_eq!(sorted(sample.view(player)), sorted(state.view(player)));
+        assert_eq!(sample.legal_events(), state.legal_events());
+        changed += (sample.canonical() != state.canonical()) as usize;
+        let input = decide(&mut HeuristicBot, &state).unwrap();
+        state.input(input).unwrap();
+    }
+    assert!(changed > 0, "the hidden cards never moved");
+}
+
+#[test]
+fn searches_give_legal_inputs() {
+    let mut state = common::game(4, 2, Default::default());
+    let mut mcts = MctsBot::new(Budget::Iterations(20), 4);
+    for _ in 0..3000 {
+        let Some(player) = deciding(&state) else {
+            return;
+        };
+        let bot: &mut dyn Bot = if player == 0 {
+            &mut mcts
+        } else {
+            &mut HeuristicBot
+        };
+        let input = decide(bot, &state).unwrap();
+        state
+            .input(input.clone())
+            .unwrap_or_else(|err| panic!("{input}: {err}"));
+    }
+    panic!("the game does not end");
+}
+
+#[test]
+fn searches_are_reproducible() {
+    let mut state = common::game(6, 2, Default::default());
+    for _ in 0..40 {
+        let searched: Vec<_> = (0..2)
+            .map(|_| decide(&mut MctsBot::new(Budget::Iterations(10), 6), &state))
+            .collect();
+        assert_eq!(searched[0], searched[1]);
+        let Some(input) = decide(&mut HeuristicBot, &state) else {
+            break;
+        };
+        state.input(input).unwrap();
+    }
+}
+
+//the search runs beside the frames: the app keeps updating until the bot has its input
+#[test]
+fn searches_do_not_block_the_app() {
+    let library = common::library();
+    let mut app = common::app(&library);
+    app.add_plugins(BotPlugin);
+    app.world.resource_mut::<Bots>().seats.clear();
+    common::push(&mut app, common::setup(3, 2, Default::default()));
+    common::settle(&mut app);
+    let budget = Duration::from_millis(500);
+    let mut bots = Bots::new(vec![0], MctsBot::new(Budget::Time(budget), 2));
+    bots.delay = Duration::ZERO;
+    app.world.insert_resource(bots);
+    let events = app.world.resource::<GameEvent>().len();
+    let start = Instant::now();
+    app.update();
+    assert!(start.elapsed() < budget);
+    assert!(app.world.resource::<Bots>().thinking());
+    let mut frames = 0;
+    while app.world.resource::<Bots>().thinking() {
+        assert!(start.elapsed() < Duration::from_secs(10));
+        app.update();
+        frames += 1;
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(frames > 1);
+    assert_eq!(app.world.resource::<GameEvent>().len(), events + 1);
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use card_game_4::cards::actions::{Action, ActionSet};
+use card_game_4::engine::{Decision, GameState};
+use card_game_4::game::events::GameEvents;
+use card_game_4::game::modes::GameMode;
+use card_game_4::states::turn::TurnStates;
+
+#[test]
+fn team_emperor() {
+    assert!(GameState::new(common::library())
+        .apply(common::setup(1, 3, GameMode::TeamEmperor))
+        .is_err());
+    //0 and 1 against 2 and 3, the teams alternate and share their life
+    let mut state = common::game(1, 4, GameMode::TeamEmperor);
+    assert_eq!(state.player(0).unwrap().life, 75);
+    state.apply(GameEvents::PassTurn).unwrap();
+    assert_eq!(state.turn(), TurnStates::PlayerTurn(2));
+    state.apply(GameEvents::PassTurn).unwrap();
+    assert_eq!(state.turn(), TurnStates::PlayerTurn(1));
+    assert_eq!(state.targets(1), vec![2, 3]);
+    //a concession knocks the whole team out
+    state.apply(GameEvents::Concede(3)).unwrap();
+    assert!(matches!(
+        state.turn(),
+        TurnStates::GameOver { winner: 0, .. }
+    ));
+    //the teammate of the winner wins too
+    assert!(GameMode::TeamEmperor.won(1, 0, 4));
+    assert!(!GameMode::TeamEmperor.won(2, 0, 4));
+    assert_eq!(GameMode::TeamEmperor.winners(0, 4), vec![0, 1]);
+}
+
+#[test]
+fn hunter_targets_the_next_player() {
+    let mut state = common::game(1, 4, GameMode::Hunter);
+    assert_eq!(state.targets(0), vec![1]);
+    assert_eq!(state.targets(3), vec![0]);
+    //the hunter of the player who gave up wins
+    state.apply(GameEvents::Concede(2)).unwrap();
+    assert!(matches!(
+        state.turn(),
+        TurnStates::GameOver { winner: 1, .. }
+    ));
+}
+
+#[test]
+fn free_for_all_and_raid_targets() {
+    let mut state = common::game(1, 3, GameMode::FreeForAll);
+    assert_eq!(state.targets(0), vec![1, 2]);
+    state.apply(GameEvents::Concede(0)).unwrap();
+    assert_eq!(state.turn(), TurnStates::PlayerTurn(1));
+    assert_eq!(state.targets(1), vec![2]);
+    let state = common::game(1, 4, GameMode::Raid);
+    assert_eq!(state.player(0).unwrap().life, 150);
+    assert_eq!(state.targets(0), vec![1, 2, 3]);
+    assert_eq!(state.targets(2), vec![0]);
+    //the raiders win together, the boss alone
+    assert_eq!(GameMode::Raid.winners(2, 4), vec![1, 2, 3]);
+    assert_eq!(GameMode::Raid.winners(0, 4), vec![0]);
+    assert_eq!(GameMode::FreeForAll.winners(2, 4), vec![2]);
+}
+
+//with several opponents, EnemyDiscard asks which player discards, never for a card of their hand
+#[test]
+fn enemy_discard_chooses_a_player() {
+    let mut state = common::game(1, 3, GameMode::FreeForAll);
+    common::give(&mut state, 0, &["Imperial-Fighter"]);
+    let fighter = common::in_hand(&state, 0, "Imperial-Fighter");
+    state.apply(GameEvents::PlayCard(fighter)).unwrap();
+    //with several opponents, the one discarding is chosen
+    let Some(Decision::Choice { player, set }) = state.pending().cloned() else {
+        panic!("no target asked: {:?}", state.pending());
+    };
+    let ActionSet::Choose { of, .. } = &set else {
+        panic!("{set:?}");
+    };
+    assert_eq!(player, 0);
+    assert_eq!(of.len(), state.targets(player).len());
+    let ActionSet::One(Action::PlayerDiscard(target)) = of[of.len() - 1] else {
+        panic!("{set:?}");
+    };
+    assert_ne!(target, player);
+    let before = state.player(target).unwrap().tracker.cards_to_discard;
+    state.choose(vec![of.len() as u8 - 1]).unwrap();
+    assert_eq!(
+        state.player(target).unwrap().tracker.cards_to_discard,
+        before + 1
+    );
+}
@@ -0,0 +1,367 @@
+mod common;
+
+use bevy::prelude::*;
+use card_game_4::bots::decide;
+use card_game_4::bots::heuristic::HeuristicBot;
+use card_game_4::engine::hash::{CanonicalBoard, CanonicalCard, CanonicalPlayer};
+use card_game_4::engine::replay::{Replay, ReplayInput};
+use card_game_4::engine::{CardLibrary, GameState};
+use card_game_4::game::events::{Answers, GameEvent, GameEvents};
+use card_game_4::network::lockstep::LockstepClient;
+use card_game_4::network::protocol::{Connection, NetMessage, PROTOCOL_VERSION};
+use card_game_4::network::{NetworkPlugin, NetworkSession};
+use card_game_4::players::PlayerLife;
+use card_game_4::prelude::*;
+use card_game_4::states::turn::{GameOverReason, TurnStates};
+use card_game_4::ui::desync::{DesyncUI, DesyncUIPlugin};
+use std::net::TcpListener;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+const SETUP: GameEvents = GameEvents::Setup {
+    seed: 11,
+    set: 0,
+    players: 2,
+    starting_player: 0,
+    mode: card_game_4::game::modes::GameMode::FreeForAll,
+};
+
+//both sides play with the heuristic bot until the game ends or enough inputs were played
+fn play(mut client: LockstepClient, inputs: usize) -> LockstepClient {
+    while client.replay.inputs.len() < inputs && client.state.current_player().is_some() {
+        if !client.our_turn() {
+            client.wait(TIMEOUT).unwrap();
+            continue;
+        }
+        let input = decide(&mut HeuristicBot, &client.state).unwrap();
+        client.play(input).unwrap();
+    }
+    client
+}
+
+#[test]
+fn lockstep_clients_play_the_same_game() {
+    let library = common::library();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let host_library = library.clone();
+    let host = std::thread::spawn(move || {
+        let client = LockstepClient::host(&listener, host_library, SETUP, TIMEOUT).unwrap();
+        play(client, 400)
+    });
+    let guest = LockstepClient::join(addr, library.clone(), TIMEOUT).unwrap();
+    assert_eq!(guest.player, 1);
+    let guest = play(guest, 400);
+    let host = host.join().unwrap();
+    assert_eq!(host.replay, guest.replay);
+    assert_eq!(host.state.canonical(), guest.state.canonical());
+    host.replay.verify(library).unwrap();
+}
+
+#[test]
+fn sends_do_not_wait_for_the_other_side() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut sender = Connection::connect(listener.local_addr().unwrap()).unwrap();
+    let (stream, _) = listener.accept().unwrap();
+    let mut receiver = Connection::new(stream).unwrap();
+    //far more than the socket buffers hold, while nothing is read
+    let message = NetMessage::Refused {
+        input: ReplayInput::Event(GameEvents::PassTurn),
+        reason: "x".repeat(32 * 1024),
+    };
+    for _ in 0..1000 {
+        sender.send(&message).unwrap();
+    }
+    assert!(!sender.flush().unwrap());
+    let reader = std::thread::spawn(move || {
+        for _ in 0..1000 {
+            assert_eq!(receiver.wait(TIMEOUT).unwrap(), message);
+        }
+    });
+    sender.close(TIMEOUT).unwrap();
+    reader.join().unwrap();
+}
+
+//app hosting a game, with a bare connection as the guest
+fn hosted() -> (App, Connection) {
+    let library = common::library();
+    let mut app = common::app(&library);
+    let session = NetworkSession::host("127.0.0.1:0").unwrap();
+    let mut guest = Connection::connect(session.local_addr().unwrap()).unwrap();
+    app.add_plugins(NetworkPlugin).insert_resource(session);
+    guest
+        .send(&NetMessage::Hello {
+            version: PROTOCOL_VERSION,
+            player: None,
+        })
+        .unwrap();
+    for _ in 0..100 {
+        app.update();
+        if app.world.resource::<NetworkSession>().connected() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    assert!(app.world.resource::<NetworkSession>().connected());
+    assert!(matches!(
+        guest.wait(TIMEOUT).unwrap(),
+        NetMessage::Hello {
+            player: Some(1),
+            ..
+        }
+    ));
+    common::push(&mut app, SETUP);
+    common::settle(&mut app);
+    assert_eq!(
+        guest.wait(TIMEOUT).unwrap(),
+        NetMessage::Input(ReplayInput::Event(SETUP))
+    );
+    (app, guest)
+}
+
+//give the app the time to receive what the guest sent
+fn receive(app: &mut App) {
+    for _ in 0..20 {
+        app.update();
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    common::settle(app);
+}
+
+#[test]
+fn guests_only_play_for_their_seat() {
+    let (mut app, mut guest) = hosted();
+    assert_eq!(
+        app.world.resource::<State<TurnStates>>().get(),
+        &TurnStates::PlayerTurn(0)
+    );
+    //passing the turn of the host
+    guest
+        .send(&NetMessage::Input(ReplayInput::Event(GameEvents::PassTurn)))
+        .unwrap();
+    receive(&mut app);
+    assert!(!app.world.resource::<NetworkSession>().connected());
+    assert_eq!(app.world.resource::<GameEvent>().log(), &[SETUP]);
+    assert_eq!(
+        app.world.resource::<State<TurnStates>>().get(),
+        &TurnStates::PlayerTurn(0)
+    );
+
+    //conceding for the host
+    let (mut app, mut guest) = hosted();
+    guest
+        .send(&NetMessage::Input(ReplayInput::Event(GameEvents::Concede(
+            0,
+        ))))
+        .unwrap();
+    receive(&mut app);
+    assert!(!app.world.resource::<NetworkSession>().connected());
+    assert_eq!(
+        app.world.resource::<State<TurnStates>>().get(),
+        &TurnStates::PlayerTurn(0)
+    );
+}
+
+#[test]
+fn guests_can_concede_at_any_time() {
+    let (mut app, mut guest) = hosted();
+    guest
+        .send(&NetMessage::Input(ReplayInput::Event(GameEvents::Concede(
+            1,
+        ))))
+        .unwrap();
+    receive(&mut app);
+    assert!(app.world.resource::<NetworkSession>().connected());
+    assert_eq!(
+        app.world.resource::<State<TurnStates>>().get(),
+        &TurnStates::GameOver {
+            winner: 0,
+            reason: GameOverReason::Conceded
+        }
+    );
+}
+
+//the events refused by the dispatcher of the host never reach the guest
+#[test]
+fn only_accepted_events_are_sent() {
+    let (mut app, mut guest) = hosted();
+    common::push(&mut app, GameEvents::PlayCard(11));
+    common::settle(&mut app);
+    common::push(&mut app, GameEvents::PassTurn);
+    common::settle(&mut app);
+    assert_eq!(
+        app.world.resource::<GameEvent>().log(),
+        &[SETUP, GameEvents::PassTurn]
+    );
+    let input = loop {
+        match guest.wait(TIMEOUT).unwrap() {
+            NetMessage::Hash { .. } => continue,
+            message => break message,
+        }
+    };
+    assert_eq!(
+        input,
+        NetMessage::Input(ReplayInput::Event(GameEvents::PassTurn))
+    );
+}
+
+//two apps playing together, the host being player 0
+fn joined(library: &CardLibrary) -> [App; 2] {
+    let mut host = common::app(library);
+    let session = NetworkSession::host("127.0.0.1:0").unwrap();
+    let mut guest = common::app(library);
+    guest
+        .add_plugins(NetworkPlugin)
+        .insert_resource(NetworkSession::join(session.local_addr().unwrap()).unwrap());
+    host.add_plugins(NetworkPlugin).insert_resource(session);
+    let mut apps = [host, guest];
+    for _ in 0..100 {
+        if apps
+            .iter()
+            .all(|app| app.world.resource::<NetworkSession>().connected())
+        {
+            return apps;
+        }
+        for app in apps.iter_mut() {
+            app.update();
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    panic!("the apps did not connect");
+}
+
+//inputs played by an app, counted as in a replay
+fn played(app: &App) -> usize {
+    app.world.resource::<GameEvent>().len() + app.world.resource::<Answers>().0.len()
+}
+
+//update both apps until they played every input and had the time to compare their boards
+fn exchange(apps: &mut [App; 2], inputs: usize) {
+    for _ in 0..1000 {
+        if apps.iter().all(|app| played(app) == inputs) {
+            break;
+        }
+        for app in apps.iter_mut() {
+            app.update();
+        }
+    }
+    for _ in 0..5 {
+        std::thread::sleep(Duration::from_millis(1));
+        for app in apps.iter_mut() {
+            common::settle(app);
+        }
+    }
+}
+
+//the heuristic bot plays both seats, each input being given to the app of the player it belongs to
+fn play_inputs(apps: &mut [App; 2], state: &mut GameState, replay: &mut Replay, inputs: usize) {
+    for _ in 0..inputs {
+        let Some(input) = decide(&mut HeuristicBot, state) else {
+            return;
+        };
+        let player = state.input_player(&input).unwrap();
+        replay.record(state, input.clone()).unwrap();
+        common::input(&mut apps[player as usize], &input);
+        exchange(apps, replay.inputs.len());
+    }
+}
+
+//a game started by the host, with the rules engine to pick the inputs
+fn started(apps: &mut [App; 2]) -> (GameState, Replay) {
+    let mut state = GameState::new(common::library());
+    let mut replay = Replay::default();
+    replay
+        .record(&mut state, ReplayInput::Event(SETUP))
+        .unwrap();
+    common::push(&mut apps[0], SETUP);
+    exchange(apps, 1);
+    (state, replay)
+}
+
+#[test]
+fn apps_agree_on_every_board() {
+    let library = common::library();
+    let mut apps = joined(&library);
+    let (mut state, mut replay) = started(&mut apps);
+    play_inputs(&mut apps, &mut state, &mut replay, 150);
+    for app in apps.iter_mut() {
+        let session = app.world.resource::<NetworkSession>();
+        assert!(session.connected());
+        assert_eq!(session.desync, None);
+        assert_eq!(played(app), replay.inputs.len());
+    }
+}
+
+#[test]
+fn different_boards_are_reported() {
+    let library = common::library();
+    let mut apps = joined(&library);
+    for app in apps.iter_mut() {
+        app.add_plugins(DesyncUIPlugin);
+    }
+    let (mut state, mut replay) = started(&mut apps);
+    play_inputs(&mut apps, &mut state, &mut replay, 20);
+    //the guest gives a life point to the host behind its back
+    for (owner, mut life) in apps[1]
+        .world
+        .query::<(&CardOwners, &mut PlayerLife)>()
+        .iter_mut(&mut apps[1].world)
+    {
+        if *owner == CardOwners::Player(0) {
+            life.0 += 1;
+        }
+    }
+    let step = replay.inputs.len();
+    play_inputs(&mut apps, &mut state, &mut replay, 1);
+    for app in apps.iter_mut() {
+        common::settle(app);
+        let session = app.world.resource::<NetworkSession>();
+        assert!(!session.connected());
+        assert_eq!(session.desync, Some(step));
+        //both sides got the board of the other one
+        let diff = session.diff.clone().unwrap();
+        assert!(diff.cards.is_empty());
+        assert_eq!(diff.players.len(), 1);
+        assert_eq!(diff.players[0].0, 0);
+        let text = app
+            .world
+            .query_filtered::<&Text, With<DesyncUI>>()
+            .single(&app.world)
+            .sections[0]
+            .value
+            .clone();
+        assert!(text.contains(&format!("after input {step}")), "{text}");
+        assert!(text.contains("player 0:"), "{text}");
+    }
+}
+
+//the hash is written field by field, it is the same on every build and platform
+#[test]
+fn board_hashes_are_stable() {
+    let board = CanonicalBoard {
+        cards: vec![
+            CanonicalCard {
+                owner: CardOwners::Market,
+                stack: Stacks::MarketRow,
+                index: 2,
+                life: None,
+            },
+            CanonicalCard {
+                owner: CardOwners::Player(1),
+                stack: Stacks::Bases,
+                index: 0,
+                life: Some(-3),
+            },
+        ],
+        players: vec![CanonicalPlayer {
+            life: 50,
+            attack: 4,
+            economy: -1,
+        }],
+    };
+    assert_eq!(board.hash(), 15339890481476616459);
+    let mut moved = board.clone();
+    moved.cards[0].index = 3;
+    assert_ne!(moved.hash(), board.hash());
+}
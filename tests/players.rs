@@ -0,0 +1,74 @@
+mod common;
+
+use card_game_4::engine::GameState;
+use card_game_4::game::events::GameEvents;
+use card_game_4::game::modes::GameMode;
+use card_game_4::prelude::{CardOwners, Stacks};
+use card_game_4::states::turn::TurnStates;
+
+//the starting player is what these tests are about, unlike common::setup
+fn setup(players: u8, starting_player: u8) -> GameEvents {
+    GameEvents::Setup {
+        seed: 1,
+        set: 0,
+        players,
+        starting_player,
+        mode: Default::default(),
+    }
+}
+
+#[test]
+fn four_players_take_turns_in_order() {
+    let mut state = GameState::new(common::library());
+    state.apply(setup(4, 2)).unwrap();
+    assert_eq!(state.turn(), TurnStates::PlayerTurn(2));
+    //the starting player draws a smaller hand
+    for player in 0..4 {
+        let hand = state.stack(CardOwners::Player(player), Stacks::Hand);
+        assert_eq!(hand.len(), if player == 2 { 3 } else { 5 });
+    }
+    for next in [3, 0, 1, 2] {
+        state.apply(GameEvents::PassTurn).unwrap();
+        assert_eq!(state.turn(), TurnStates::PlayerTurn(next));
+    }
+}
+
+#[test]
+fn player_count_is_bounded() {
+    let library = common::library();
+    for (players, starting_player) in [(7, 2), (1, 0), (3, 3)] {
+        assert!(GameState::new(library.clone())
+            .apply(setup(players, starting_player))
+            .is_err());
+    }
+}
+
+#[test]
+fn bases_heal_at_the_end_of_every_turn() {
+    let mut state = common::game(1, 3, GameMode::FreeForAll);
+    state.apply(GameEvents::PassTurn).unwrap();
+    state.apply(GameEvents::PassTurn).unwrap();
+    common::give(&mut state, 2, &["Blob-Wheel"]);
+    let wheel = common::in_hand(&state, 2, "Blob-Wheel");
+    state.apply(GameEvents::PlayCard(wheel)).unwrap();
+    state.apply(GameEvents::PassTurn).unwrap();
+    let bases = state.stack(CardOwners::Player(2), Stacks::Bases);
+    let wheel = bases[0];
+    let life = |state: &GameState| state.card(wheel).unwrap().life.0;
+    //player 0 damages the base of player 2
+    common::give(&mut state, 0, &["Blob-Fighter"]);
+    let fighter = common::in_hand(&state, 0, "Blob-Fighter");
+    state.apply(GameEvents::PlayCard(fighter)).unwrap();
+    state
+        .apply(GameEvents::Attack {
+            player: 2,
+            as_much_as_possible: false,
+            base_index: Some(state.card(wheel).unwrap().index as u32),
+        })
+        .unwrap();
+    assert_eq!(life(&state), 4);
+    //player 1 finds it at full life
+    state.apply(GameEvents::PassTurn).unwrap();
+    assert_eq!(state.turn(), TurnStates::PlayerTurn(1));
+    assert_eq!(life(&state), 5);
+}
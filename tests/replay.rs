@@ -0,0 +1,137 @@
+mod common;
+
+use card_game_4::bots::decide;
+use card_game_4::bots::heuristic::HeuristicBot;
+use card_game_4::engine::replay::{Replay, ReplayError, ReplayInput};
+use card_game_4::engine::{CardLibrary, GameState};
+use card_game_4::game::events::GameEvents;
+use card_game_4::game::history::BoardSnapshot;
+use card_game_4::game::replay::ReplayViewer;
+use card_game_4::prelude::Stacks;
+use std::sync::Arc;
+
+//a game of heuristic bots, with the board after each input
+fn recorded(library: &Arc<CardLibrary>, inputs: usize) -> (Replay, Vec<BoardSnapshot>) {
+    let mut state = GameState::new(library.clone());
+    let mut replay = Replay::default();
+    let setup = GameEvents::Setup {
+        seed: 3,
+        set: 0,
+        players: 2,
+        starting_player: 0,
+        mode: Default::default(),
+    };
+    replay
+        .record(&mut state, ReplayInput::Event(setup))
+        .unwrap();
+    let mut boards = vec![state.board_snapshot(true)];
+    while boards.len() < inputs {
+        let Some(input) = decide(&mut HeuristicBot, &state) else {
+            break;
+        };
+        replay.record(&mut state, input).unwrap();
+        boards.push(state.board_snapshot(true));
+    }
+    (replay, boards)
+}
+
+#[test]
+fn replays_round_trip_through_ron() {
+    let library = common::library();
+    let (replay, _) = recorded(&library, 150);
+    let path = std::env::temp_dir().join("card_game_4_tests/roundtrip.replay.ron");
+    replay.write(&path).unwrap();
+    assert_eq!(Replay::read(&path).unwrap(), replay);
+}
+
+#[test]
+fn replays_play_the_same_game() {
+    let library = common::library();
+    let (replay, boards) = recorded(&library, 150);
+    let mut played = Vec::new();
+    replay
+        .play(&mut GameState::new(library.clone()), |_, state| {
+            played.push(state.board_snapshot(true))
+        })
+        .unwrap();
+    assert_eq!(played, boards);
+}
+
+#[test]
+fn the_viewer_shows_the_board_of_any_step() {
+    let library = common::library();
+    let (replay, boards) = recorded(&library, 150);
+    let mut viewer = ReplayViewer::open(replay, library).unwrap();
+    assert_eq!(viewer.len(), boards.len());
+    //forward, backward and jumps across turns
+    let last = boards.len() - 1;
+    for step in [5, 6, 7, 120, 2, last, 0, last - 1] {
+        assert_eq!(viewer.board(step).unwrap(), boards[step], "step {step}");
+    }
+    viewer.seek(last + 10);
+    assert_eq!(viewer.step, last);
+    viewer.forward();
+    assert_eq!(viewer.step, last);
+    viewer.back();
+    assert_eq!(viewer.step, last - 1);
+    //playing from the end starts again from the beginning
+    viewer.seek(last);
+    viewer.toggle();
+    assert_eq!((viewer.step, viewer.playing), (0, true));
+}
+
+#[test]
+fn keyframes_are_rebuilt_identically() {
+    let library = common::library();
+    let (replay, _) = recorded(&library, 300);
+    assert!(replay.keyframes.len() > 5);
+    assert_eq!(
+        replay.build_keyframes(library.clone()).unwrap(),
+        replay.keyframes
+    );
+    replay.verify(library).unwrap();
+}
+
+#[test]
+fn seeking_from_a_keyframe_gives_the_same_board() {
+    let library = common::library();
+    let (replay, boards) = recorded(&library, 300);
+    for step in (0..boards.len()).rev().step_by(7) {
+        let state = replay.seek(library.clone(), step).unwrap();
+        assert_eq!(state.board_snapshot(true), boards[step], "step {step}");
+        assert_eq!(state.hash(), replay.hashes[step], "step {step}");
+    }
+}
+
+#[test]
+fn verify_finds_the_first_divergence() {
+    let library = common::library();
+    let (replay, _) = recorded(&library, 300);
+    let mut tampered = replay.clone();
+    tampered.keyframes[2].players[0].life += 1;
+    assert!(matches!(
+        tampered.verify(library.clone()),
+        Err(ReplayError::Diverged(step)) if step == replay.keyframes[2].step
+    ));
+    let mut tampered = replay.clone();
+    tampered.hashes[40] ^= 1;
+    assert!(matches!(
+        tampered.verify(library),
+        Err(ReplayError::Diverged(40))
+    ));
+}
+
+#[test]
+fn scrapped_explorers_return_unused() {
+    let library = common::library();
+    let (_, boards) = recorded(&library, 300);
+    for (step, board) in boards.iter().enumerate() {
+        for card in board.cards.iter() {
+            if card.state.stack == Stacks::JokerDeck {
+                let mut unused = card.actions.clone();
+                unused.reset();
+                assert_eq!(card.actions, unused, "step {step}");
+            }
+        }
+    }
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use bevy::prelude::*;
+use card_game_4::bots::decide;
+use card_game_4::bots::heuristic::HeuristicBot;
+use card_game_4::engine::GameState;
+use card_game_4::game::events::GameEvents;
+use card_game_4::game::history::BoardSnapshot;
+use card_game_4::game::save::{load_game, PendingLoad, SaveError, SavedGame};
+use card_game_4::states::turn::TurnStates;
+
+//a few turns played by the heuristic bot, stopped between two events
+fn played(app: &mut App, seed: u64, inputs: usize) {
+    let library = common::library();
+    let mut state = GameState::new(library.clone());
+    let setup = GameEvents::Setup {
+        seed,
+        set: 0,
+        players: 2,
+        starting_player: 0,
+        mode: Default::default(),
+    };
+    state.apply(setup).unwrap();
+    common::push(app, setup);
+    common::settle(app);
+    for _ in 0..inputs {
+        let input = decide(&mut HeuristicBot, &state).unwrap();
+        state.input(input.clone()).unwrap();
+        common::input(app, &input);
+        common::settle(app);
+    }
+    while state.pending().is_some() {
+        let input = decide(&mut HeuristicBot, &state).unwrap();
+        state.input(input.clone()).unwrap();
+        common::input(app, &input);
+        common::settle(app);
+    }
+}
+
+#[test]
+fn saved_games_load_the_same_board() {
+    let library = common::library();
+    let mut app = common::app(&library);
+    played(&mut app, 4, 40);
+    let saved = SavedGame::capture(&mut app.world).unwrap();
+    let path = std::env::temp_dir().join("card_game_4_tests/roundtrip.game.ron");
+    saved.write(&path).unwrap();
+    let read = SavedGame::read(&path).unwrap();
+    assert_eq!(read, saved);
+
+    let mut loaded = common::app(&library);
+    load_game(&mut loaded.world, read).unwrap();
+    //the saved board replaces the fresh one once the setup is done
+    for _ in 0..100 {
+        if loaded.world.resource::<PendingLoad>().0.is_none() {
+            break;
+        }
+        common::settle(&mut loaded);
+    }
+    assert!(loaded.world.resource::<PendingLoad>().0.is_none());
+    assert_eq!(
+        loaded.world.resource::<State<TurnStates>>().get(),
+        &saved.turn
+    );
+    assert_eq!(BoardSnapshot::capture(&mut loaded.world), saved.board);
+}
+
+#[test]
+fn saves_need_a_game_at_rest() {
+    let library = common::library();
+    let mut app = common::app(&library);
+    assert!(matches!(
+        SavedGame::capture(&mut app.world),
+        Err(SaveError::NoGame)
+    ));
+    let path = std::env::temp_dir().join("card_game_4_tests/no_setup.game.ron");
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, "(log: [PassTurn], turn: Setup, board: (cards: [], players: [], next_buy_on_deck: None, triggers: [], rng: (seed: 0, shuffles: 0)))").unwrap();
+    assert!(matches!(
+        SavedGame::read(&path),
+        Err(SaveError::MissingSetup)
+    ));
+}
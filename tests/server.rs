@@ -0,0 +1,334 @@
+mod common;
+
+use card_game_4::bots::decide;
+use card_game_4::bots::heuristic::HeuristicBot;
+use card_game_4::engine::replay::ReplayInput;
+use card_game_4::engine::view::PlayerView;
+use card_game_4::engine::Decision;
+use card_game_4::game::events::{BuyFrom, GameEvents};
+use card_game_4::network::client::ServerGame;
+use card_game_4::network::protocol::{NetMessage, NetworkError, MAX_LINE_LENGTH, PROTOCOL_VERSION};
+use card_game_4::network::server::{Server, ServerClient};
+use card_game_4::network::NetworkPlugin;
+use card_game_4::players::LocalPlayer;
+use card_game_4::prelude::*;
+use card_game_4::states::turn::{GameOverReason, TurnStates};
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[test]
+fn views_only_show_what_a_player_can_see() {
+    let mut state = common::game(5, 2, Default::default());
+    let mut selections = 0;
+    for _ in 0..400 {
+        for player in 0..2 {
+            let view = state.view(player);
+            for card in &view.cards {
+                let own_hand =
+                    card.owner == CardOwners::Player(player) && card.stack == Stacks::Hand;
+                assert!(
+                    own_hand || state.card(card.id).unwrap().visibility == CardVisibility::Visible,
+                    "{card:?}"
+                );
+            }
+            let opponent = CardOwners::Player(1 - player);
+            assert!(view.stack(opponent, Stacks::Hand).is_empty());
+            assert!(view
+                .stack(CardOwners::Player(player), Stacks::PlayerDeck)
+                .is_empty());
+            assert!(view
+                .stack(CardOwners::Market, Stacks::MarketDeck)
+                .is_empty());
+            let total = view.cards.len() + view.hidden.iter().map(|h| h.count).sum::<usize>();
+            assert_eq!(total, state.cards().len());
+            //the cards to select from were already seen, only the player deciding knows them
+            match &view.pending {
+                Some(Decision::Selection { cards, .. }) => {
+                    selections += 1;
+                    assert!(cards.iter().all(|&c| view.card(c).is_some()));
+                }
+                Some(decision) => assert_eq!(decision.player(), player),
+                None => {}
+            }
+            if let Some(decision) = state.pending() {
+                assert_eq!(view.pending.is_some(), decision.player() == player);
+            }
+        }
+        let Some(input) = decide(&mut HeuristicBot, &state) else {
+            break;
+        };
+        state.input(input).unwrap();
+    }
+    assert!(selections > 0);
+}
+
+//plays the first card of the hand, then tries to buy and attack before passing the turn
+fn play(addr: std::net::SocketAddr) -> ServerClient {
+    let mut client = ServerClient::connect(addr, TIMEOUT).unwrap();
+    let me = client.player;
+    let mut refused = 0;
+    let mut turns = 0;
+    loop {
+        if !client.our_turn() {
+            match client.wait(TIMEOUT) {
+                Ok(()) => continue,
+                Err(NetworkError::Closed) => return client,
+                Err(err) => panic!("{err}"),
+            }
+        }
+        let event = match client.view.pending.clone() {
+            Some(Decision::Selection { min, cards, .. }) => {
+                ReplayInput::Select(cards.into_iter().take(min.max(1)).collect())
+            }
+            Some(Decision::Choice { .. }) => ReplayInput::Choose(vec![0]),
+            None => ReplayInput::Event(
+                match client
+                    .view
+                    .stack(CardOwners::Player(me), Stacks::Hand)
+                    .first()
+                {
+                    Some(card) => GameEvents::PlayCard(card.index as u8),
+                    None if refused < 5 => GameEvents::BuyCard(BuyFrom::Market(refused as u8)),
+                    None if refused == 5 => GameEvents::Attack {
+                        player: 1 - me,
+                        as_much_as_possible: true,
+                        base_index: None,
+                    },
+                    None if turns > 40 => GameEvents::Concede(me),
+                    None => {
+                        refused = 0;
+                        turns += 1;
+                        GameEvents::PassTurn
+                    }
+                },
+            ),
+        };
+        client.submit(event).unwrap();
+        match client.wait(TIMEOUT) {
+            Ok(()) => {}
+            Err(NetworkError::Refused { .. }) => refused += 1,
+            Err(NetworkError::Closed) => return client,
+            Err(err) => panic!("{err}"),
+        }
+    }
+}
+
+#[test]
+fn players_play_through_the_server() {
+    let library = common::library();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server_library = library.clone();
+    let server = std::thread::spawn(move || {
+        let mut server = Server::start(
+            &listener,
+            server_library,
+            common::setup(5, 2, Default::default()),
+            TIMEOUT,
+        )
+        .unwrap();
+        server.run();
+        server
+    });
+    let clients = [
+        std::thread::spawn(move || play(addr)),
+        std::thread::spawn(move || play(addr)),
+    ];
+    let clients = clients.map(|c| c.join().unwrap());
+    let server = server.join().unwrap();
+    assert!(server.state.current_player().is_none());
+    for client in &clients {
+        assert_eq!(client.view, server.state.view(client.player));
+    }
+    server.replay.verify(library).unwrap();
+}
+
+#[test]
+fn endless_lines_drop_the_connection() {
+    let library = common::library();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || {
+        let mut server = Server::start(
+            &listener,
+            library,
+            common::setup(5, 2, Default::default()),
+            TIMEOUT,
+        )
+        .unwrap();
+        server.run();
+        server
+    });
+    let client = std::thread::spawn(move || {
+        let mut client = ServerClient::connect(addr, TIMEOUT).unwrap();
+        while client.wait(TIMEOUT).is_ok() {}
+        client
+    });
+    //the second seat says hello, then sends a line that never ends
+    let mut stream = TcpStream::connect(addr).unwrap();
+    let hello = ron::to_string(&NetMessage::Hello {
+        version: PROTOCOL_VERSION,
+        player: None,
+    })
+    .unwrap();
+    stream.write_all(format!("{hello}\n").as_bytes()).unwrap();
+    //the server may close the connection before everything was written
+    let _ = stream.write_all(&vec![b' '; MAX_LINE_LENGTH + 4096]);
+    let server = server.join().unwrap();
+    let client = client.join().unwrap();
+    //leaving the game concedes it
+    assert_eq!(
+        server.state.turn(),
+        TurnStates::GameOver {
+            winner: client.player,
+            reason: GameOverReason::Conceded
+        }
+    );
+}
+
+//a connection that does not say hello properly loses its seat, the next one takes it
+#[test]
+fn bad_hellos_do_not_stop_the_server() {
+    let library = common::library();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || {
+        Server::start(
+            &listener,
+            library,
+            common::setup(5, 2, Default::default()),
+            TIMEOUT,
+        )
+        .map(|server| server.state.turn())
+    });
+    let mut stream = TcpStream::connect(addr).unwrap();
+    let hello = ron::to_string(&NetMessage::Hello {
+        version: PROTOCOL_VERSION + 1,
+        player: None,
+    })
+    .unwrap();
+    stream.write_all(format!("{hello}\n").as_bytes()).unwrap();
+    let clients = [
+        std::thread::spawn(move || ServerClient::connect(addr, TIMEOUT).unwrap()),
+        std::thread::spawn(move || ServerClient::connect(addr, TIMEOUT).unwrap()),
+    ];
+    let mut seats = clients.map(|c| c.join().unwrap().player);
+    seats.sort();
+    assert_eq!(seats, [0, 1]);
+    assert_eq!(server.join().unwrap().unwrap(), TurnStates::PlayerTurn(0));
+}
+
+#[test]
+fn the_server_gives_up_after_its_deadline() {
+    let library = common::library();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    //one player out of two shows up
+    let client = std::thread::spawn(move || ServerClient::connect(addr, TIMEOUT));
+    let started = Server::start(
+        &listener,
+        library,
+        common::setup(5, 2, Default::default()),
+        Duration::from_millis(200),
+    );
+    assert!(matches!(started, Err(NetworkError::Timeout)));
+    assert!(client.join().unwrap().is_err());
+}
+
+//updates the app until its board shows a view of the server matching the condition
+fn sync(app: &mut App, done: impl Fn(&PlayerView) -> bool) -> PlayerView {
+    let end = Instant::now() + TIMEOUT;
+    while Instant::now() < end {
+        common::settle(app);
+        let game = app.world.resource::<ServerGame>();
+        if let Some(view) = game.view().filter(|view| done(view)).cloned() {
+            //one more round for the board to move to it
+            common::settle(app);
+            return view;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    panic!("the server never sent the view");
+}
+
+#[test]
+fn the_app_plays_a_turn_against_the_server() {
+    let library = common::library();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let mut app = common::app(&library);
+    app.add_plugins(NetworkPlugin)
+        .insert_resource(ServerGame::join(addr).unwrap());
+    let server_library = library.clone();
+    let server = std::thread::spawn(move || {
+        let mut server = Server::start(
+            &listener,
+            server_library,
+            common::setup(5, 2, Default::default()),
+            TIMEOUT,
+        )
+        .unwrap();
+        server.run();
+        server
+    });
+    //the second seat concedes once the app showed its turn
+    let (shown, concede) = std::sync::mpsc::channel();
+    let opponent = std::thread::spawn(move || {
+        let mut client = ServerClient::connect(addr, TIMEOUT).unwrap();
+        while !client.our_turn() {
+            client.wait(TIMEOUT).unwrap();
+        }
+        concede.recv_timeout(TIMEOUT).unwrap();
+        client
+            .submit(ReplayInput::Event(GameEvents::Concede(1)))
+            .unwrap();
+        while client.wait(TIMEOUT).is_ok() {}
+    });
+    //indexes of the cards in the hand of the app
+    let hand = |view: &PlayerView| -> Vec<usize> {
+        let cards = view.stack(CardOwners::Player(0), Stacks::Hand);
+        cards.iter().map(|card| card.index).collect()
+    };
+    let mut view = sync(&mut app, |view| !hand(view).is_empty());
+    assert_eq!(app.world.resource::<LocalPlayer>().0, 0);
+    //every event goes through the server, the app only shows the views it sends back
+    while let Some(&index) = hand(&view).first() {
+        let cards = hand(&view).len();
+        common::push(&mut app, GameEvents::PlayCard(index as u8));
+        view = sync(&mut app, |view| hand(view).len() < cards);
+    }
+    assert!(view.players[0].economy > 0);
+    common::push(&mut app, GameEvents::PassTurn);
+    view = sync(&mut app, |view| view.turn == TurnStates::PlayerTurn(1));
+    assert_eq!(
+        *app.world.resource::<State<TurnStates>>().get(),
+        TurnStates::PlayerTurn(1)
+    );
+    for (id, owner, stack, index) in app
+        .world
+        .query::<(&CardId, &CardOwners, &Stacks, &CardIndex)>()
+        .iter(&app.world)
+    {
+        if let Some(card) = view.card(id.0) {
+            assert_eq!(
+                (card.owner, card.stack, card.index),
+                (*owner, *stack, index.0)
+            );
+        }
+    }
+    assert!(app.world.resource::<ServerGame>().connected());
+    shown.send(()).unwrap();
+    let server = server.join().unwrap();
+    opponent.join().unwrap();
+    let over = TurnStates::GameOver {
+        winner: 0,
+        reason: GameOverReason::Conceded,
+    };
+    //the app keeps the last board once the server closed the game
+    sync(&mut app, |view| view.turn == over);
+    assert_eq!(server.state.turn(), over);
+    server.replay.verify(library).unwrap();
+}
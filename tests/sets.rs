@@ -0,0 +1,165 @@
+use card_game_4::cards::assets::manifest::{DeckRole, SetCatalog};
+use card_game_4::cards::assets::serializer::AssetLoadderError;
+use card_game_4::engine::{CardLibrary, GameState};
+use card_game_4::game::events::GameEvents;
+use card_game_4::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+fn setup(set: u8) -> GameEvents {
+    GameEvents::Setup {
+        seed: 1,
+        set,
+        players: 2,
+        starting_player: 0,
+        mode: Default::default(),
+    }
+}
+
+#[test]
+fn sets_are_found_by_id_folder_or_name() {
+    let catalog = SetCatalog::discover("assets");
+    let ids: Vec<u8> = catalog.0.iter().map(|set| set.manifest.id).collect();
+    assert_eq!(ids, [0, 1]);
+    for key in ["0", "default", "Default", "DEFAULT"] {
+        assert_eq!(catalog.find(key).unwrap().folder, "default", "{key}");
+    }
+    for key in ["1", "debug_bases", "debug bases"] {
+        assert_eq!(catalog.find(key).unwrap().manifest.id, 1, "{key}");
+    }
+    assert!(catalog.find("2").is_none());
+    assert!(matches!(
+        CardLibrary::load("assets", "nope"),
+        Err(AssetLoadderError::UnknownSet(_))
+    ));
+}
+
+#[test]
+fn setups_play_the_set_of_the_library() {
+    let library = Arc::new(CardLibrary::load("assets", "debug_bases").unwrap());
+    assert_eq!(library.id, 1);
+    assert!(GameState::new(library.clone()).apply(setup(0)).is_err());
+    let mut state = GameState::new(library);
+    state.apply(setup(1)).unwrap();
+    //every card of the market comes from the deck of the set
+    let market: Vec<String> = fs::read_to_string("assets/sets/debug_bases/market.deck.ron")
+        .map(|deck| ron::from_str::<Vec<(u32, String)>>(&deck).unwrap())
+        .unwrap()
+        .into_iter()
+        .map(|(_, name)| name)
+        .collect();
+    for card in state.cards() {
+        if card.owner == CardOwners::Market && card.stack != Stacks::JokerDeck {
+            assert!(market.contains(&card.name), "{}", card.name);
+        }
+    }
+}
+
+//assets folder of its own, with the cards copied from the game
+struct Assets(PathBuf);
+
+impl Assets {
+    fn new(name: &str) -> Self {
+        let root = std::env::temp_dir().join(format!("sets-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for (folder, cards) in [
+            ("cards", &["Scout", "Viper"][..]),
+            ("more_cards", &["Explorer", "Battlecruiser", "Cutter"][..]),
+        ] {
+            fs::create_dir_all(root.join(folder)).unwrap();
+            for card in cards {
+                let file = format!("{card}.card.ron");
+                fs::copy(
+                    Path::new("assets/cards").join(&file),
+                    root.join(folder).join(&file),
+                )
+                .unwrap();
+            }
+        }
+        Self(root)
+    }
+
+    fn set(&self, folder: &str, manifest: &str) {
+        let path = self.0.join("sets").join(folder);
+        fs::create_dir_all(&path).unwrap();
+        fs::write(path.join("set.ron"), manifest).unwrap();
+        fs::write(
+            path.join("market.ron"),
+            r#"[(6, "Battlecruiser"), (6, "Cutter")]"#,
+        )
+        .unwrap();
+        fs::write(path.join("player.ron"), r#"[(7, "Scout"), (3, "Viper")]"#).unwrap();
+        fs::write(path.join("joker.ron"), r#"[(10, "Explorer")]"#).unwrap();
+    }
+}
+
+impl Drop for Assets {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+const DECKS: &str = r#"decks: {
+        Market: "market.ron",
+        Player: "player.ron",
+        Joker: "joker.ron",
+    }"#;
+
+#[test]
+fn manifests_declare_decks_paths_and_rules() {
+    let assets = Assets::new("rules");
+    assets.set(
+        "small",
+        &format!(
+            r#"(
+    id: 7,
+    name: "Small",
+    {DECKS},
+    card_paths: ["cards", "more_cards"],
+    rules: (starting_life: 20, market_size: 3),
+)"#
+        ),
+    );
+    let library = Arc::new(CardLibrary::load(&assets.0, "small").unwrap());
+    assert_eq!(library.id, 7);
+    //the rules left out keep their default
+    assert_eq!(library.rules.hand_size, 5);
+    let mut state = GameState::new(library);
+    state.apply(setup(7)).unwrap();
+    assert!(state.players().iter().all(|p| p.life == 20));
+    assert_eq!(state.stack(CardOwners::Market, Stacks::MarketRow).len(), 3);
+}
+
+#[test]
+fn broken_sets_are_skipped() {
+    let assets = Assets::new("broken");
+    let manifest = |id: u8, name: &str| {
+        format!(r#"(id: {id}, name: "{name}", {DECKS}, card_paths: ["cards", "more_cards"])"#)
+    };
+    assets.set("first", &manifest(3, "First"));
+    //same id as the first one, in a folder sorted after it
+    assets.set("second", &manifest(3, "Second"));
+    assets.set("unreadable", "(id: 4, name: ");
+    assets.set(
+        "no_joker",
+        r#"(id: 5, name: "No joker", decks: { Market: "market.ron", Player: "player.ron" })"#,
+    );
+    //no search path holds the explorer
+    assets.set(
+        "no_cards",
+        &format!(r#"(id: 6, name: "No cards", {DECKS})"#),
+    );
+    let catalog = SetCatalog::discover(&assets.0);
+    let folders: Vec<&str> = catalog.0.iter().map(|s| s.folder.as_str()).collect();
+    assert_eq!(folders, ["first", "no_joker", "no_cards"]);
+    assert!(matches!(
+        CardLibrary::load(&assets.0, "no_joker"),
+        Err(AssetLoadderError::MissingDeck(_, DeckRole::Joker))
+    ));
+    assert!(matches!(
+        CardLibrary::load(&assets.0, "no_cards"),
+        Err(AssetLoadderError::Io(_))
+    ));
+    assert!(CardLibrary::load(&assets.0, "3").is_ok());
+}
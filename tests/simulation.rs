@@ -0,0 +1,150 @@
+mod common;
+
+use card_game_4::bots::heuristic::HeuristicBot;
+use card_game_4::bots::simulation::{play_game, GameRecord, SimulationStats, MAX_INPUTS};
+use card_game_4::bots::Bot;
+use card_game_4::cards::components::factions::CardFaction;
+use card_game_4::engine::replay::ReplayInput;
+use card_game_4::engine::{Decision, GameState};
+use card_game_4::game::events::GameEvents;
+use card_game_4::game::modes::GameMode;
+
+fn games(count: u64) -> Vec<GameRecord> {
+    let library = common::library();
+    (0..count)
+        .map(|seed| {
+            let mut bots: Vec<Box<dyn Bot>> = vec![Box::new(HeuristicBot), Box::new(HeuristicBot)];
+            let setup = GameEvents::Setup {
+                seed,
+                set: 0,
+                players: 2,
+                starting_player: 0,
+                mode: Default::default(),
+            };
+            play_game(library.clone(), setup, &mut bots).unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn games_are_recorded() {
+    for record in games(5) {
+        assert!(record.winner.is_some_and(|w| w < 2));
+        assert_eq!(record.winners, Vec::from_iter(record.winner));
+        assert!(record.turns > 1);
+        //the market row is dealt before the first turn
+        assert!(record.offered.len() >= 5);
+        //explorers are bought from the joker deck, not the market row
+        assert!(record.acquired.iter().all(|(player, name, turn)| {
+            *player < 2 && *turn >= 1 && (record.offered.contains(name) || name == "Explorer")
+        }));
+        assert_eq!(record.factions.len(), 2);
+    }
+}
+
+fn stats(records: &[GameRecord]) -> SimulationStats {
+    let mut stats = SimulationStats::default();
+    for record in records {
+        stats.add(record);
+    }
+    stats
+}
+
+fn ron(stats: &SimulationStats) -> String {
+    ron::to_string(stats).unwrap()
+}
+
+#[test]
+fn statistics_add_up() {
+    let records = games(6);
+    let mut all = stats(&records);
+    let mut merged = stats(&records[..2]);
+    merged.merge(stats(&records[2..]));
+    all.finish();
+    merged.finish();
+    assert_eq!(ron(&all), ron(&merged));
+    assert_eq!(all.games, 6);
+    assert_eq!(all.wins_by_seat.iter().sum::<u32>() + all.unfinished, 6);
+    assert!(all.average_turns > 1.);
+    for (name, card) in &all.cards {
+        assert!(card.owned_wins <= card.owned_games, "{name}");
+        assert!((0. ..=1.).contains(&card.win_rate_when_owned), "{name}");
+        if card.bought > 0 {
+            assert!(card.average_turn_bought >= 1., "{name}");
+        }
+    }
+    assert!(all.factions.values().all(|f| f.wins <= f.players));
+
+    let path = std::env::temp_dir().join(format!("simulation-{}/stats.ron", std::process::id()));
+    all.write(&path).unwrap();
+    let read: SimulationStats = ron::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(read.games, all.games);
+    assert_eq!(read.cards.len(), all.cards.len());
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+//in team modes, the statistics credit the win to the whole winning team
+#[test]
+fn team_wins_are_shared() {
+    let library = common::library();
+    let mut bots: Vec<Box<dyn Bot>> = (0..4).map(|_| Box::new(HeuristicBot) as _).collect();
+    let setup = GameEvents::Setup {
+        seed: 3,
+        set: 0,
+        players: 4,
+        starting_player: 0,
+        mode: GameMode::TeamEmperor,
+    };
+    let record = play_game(library, setup, &mut bots).unwrap();
+    let winner = record.winner.expect("the game did not finish");
+    assert_eq!(record.winners, GameMode::TeamEmperor.winners(winner, 4));
+    assert_eq!(record.winners.len(), 2);
+    let stats = stats(std::slice::from_ref(&record));
+    assert_eq!(stats.wins_by_seat.iter().sum::<u32>(), 2);
+    for (player, faction) in record.factions.iter().enumerate() {
+        let Some(faction) = faction else {
+            continue;
+        };
+        if record.winners.contains(&(player as u8)) {
+            assert!(stats.factions[&format!("{faction:?}")].wins > 0);
+        }
+    }
+}
+
+//never ends its turn otherwise, so the game runs until MAX_INPUTS
+struct Passer;
+
+impl Bot for Passer {
+    fn play(&mut self, _: &GameState) -> GameEvents {
+        GameEvents::PassTurn
+    }
+
+    fn answer(&mut self, state: &GameState, decision: &Decision) -> ReplayInput {
+        HeuristicBot.answer(state, decision)
+    }
+}
+
+#[test]
+fn unfinished_games_count_every_turn_but_no_win() {
+    let library = common::library();
+    let mut bots: Vec<Box<dyn Bot>> = vec![Box::new(Passer), Box::new(Passer)];
+    let setup = GameEvents::Setup {
+        seed: 1,
+        set: 0,
+        players: 2,
+        starting_player: 0,
+        mode: Default::default(),
+    };
+    let mut record = play_game(library, setup, &mut bots).unwrap();
+    assert_eq!(record.winner, None);
+    //every input passed a turn, the turn started by the last one included
+    assert_eq!(record.turns as usize, MAX_INPUTS + 1);
+
+    record.acquired = vec![(0, "Explorer".to_string(), 1)];
+    record.factions = vec![Some(CardFaction::Blob), None];
+    let stats = stats(std::slice::from_ref(&record));
+    assert_eq!(stats.unfinished, 1);
+    assert_eq!(stats.cards["Explorer"].bought, 1);
+    assert_eq!(stats.cards["Explorer"].owned_games, 0);
+    assert!(stats.factions.is_empty());
+}
@@ -0,0 +1,31 @@
+mod common;
+
+use card_game_4::engine::Decision;
+use card_game_4::game::events::GameEvents;
+use card_game_4::prelude::{CardOwners, Stacks};
+
+const NEEDLE: &str = "Stealth-Needle";
+
+#[test]
+fn needle_copies_another_ship() {
+    let mut state = common::game(1, 2, Default::default());
+    common::give(&mut state, 0, &["Imperial-Fighter", NEEDLE]);
+    let fighter = common::in_hand(&state, 0, "Imperial-Fighter");
+    state.apply(GameEvents::PlayCard(fighter)).unwrap();
+    let needle = common::in_hand(&state, 0, NEEDLE);
+    state.apply(GameEvents::PlayCard(needle)).unwrap();
+    let used = state.stack(CardOwners::Player(0), Stacks::UsedCards);
+    let name = |card| state.card(card).unwrap().name.as_str();
+    let needle = *used.iter().find(|&&c| name(c) == NEEDLE).unwrap();
+    let fighter = *used.iter().find(|&&c| name(c) != NEEDLE).unwrap();
+    //the needle can copy the other ship played this turn, never itself
+    let Some(Decision::Selection { min, cards, .. }) = state.pending().cloned() else {
+        panic!("no ship to copy asked: {:?}", state.pending());
+    };
+    assert_eq!(min, 0);
+    assert_eq!(cards, vec![fighter]);
+    state.select(vec![fighter]).unwrap();
+    let gained = &state.card(needle).unwrap().factions;
+    let copied = &state.card(fighter).unwrap().factions;
+    assert!(copied.0.iter().all(|f| gained.0.contains(f)));
+}
@@ -0,0 +1,132 @@
+mod common;
+
+use bevy::prelude::*;
+use card_game_4::engine::GameState;
+use card_game_4::game::events::{BuyFrom, GameEvents};
+use card_game_4::game::history::{redo, undo, BoardSnapshot, History};
+use card_game_4::prelude::{CardOwners, CardVisibility, Stacks};
+
+//a game started in the app, with the rules engine to find the cards of the hand
+fn started() -> (App, GameState) {
+    let library = common::library();
+    let mut app = common::app(&library);
+    let mut state = GameState::new(library.clone());
+    let setup = GameEvents::Setup {
+        seed: 3,
+        set: 0,
+        players: 2,
+        starting_player: 0,
+        mode: Default::default(),
+    };
+    state.apply(setup).unwrap();
+    common::push(&mut app, setup);
+    common::settle(&mut app);
+    (app, state)
+}
+
+//slots of the hand of the current player holding cards that don't draw
+fn quiet_cards(state: &GameState) -> Vec<u8> {
+    state
+        .stack(CardOwners::Player(0), Stacks::Hand)
+        .into_iter()
+        .map(|c| state.card(c).unwrap())
+        .filter(|c| c.name == "Scout" || c.name == "Viper")
+        .map(|c| c.index as u8)
+        .collect()
+}
+
+#[test]
+fn undo_restores_the_board_and_redo_plays_again() {
+    let (mut app, state) = started();
+    let slot = quiet_cards(&state)[0];
+    let before = BoardSnapshot::capture(&mut app.world);
+    common::push(&mut app, GameEvents::PlayCard(slot));
+    common::settle(&mut app);
+    let played = BoardSnapshot::capture(&mut app.world);
+    assert_ne!(before, played);
+    assert!(app.world.resource::<History>().can_undo());
+
+    assert!(undo(&mut app.world));
+    common::settle(&mut app);
+    assert_eq!(BoardSnapshot::capture(&mut app.world), before);
+    assert!(app.world.resource::<History>().can_redo());
+
+    assert!(redo(&mut app.world));
+    common::settle(&mut app);
+    assert_eq!(BoardSnapshot::capture(&mut app.world), played);
+    assert!(!app.world.resource::<History>().can_redo());
+}
+
+#[test]
+fn passing_the_turn_can_not_be_undone() {
+    let (mut app, state) = started();
+    common::push(&mut app, GameEvents::PlayCard(quiet_cards(&state)[0]));
+    common::settle(&mut app);
+    common::push(&mut app, GameEvents::PassTurn);
+    common::settle(&mut app);
+    assert!(!app.world.resource::<History>().can_undo());
+    assert!(!undo(&mut app.world));
+}
+
+//plays the cards of the hand, in the app and in the rules engine
+fn play_hand(app: &mut App, state: &mut GameState) {
+    while let Some(&slot) = quiet_cards(state).first() {
+        common::push(app, GameEvents::PlayCard(slot));
+        common::settle(app);
+        state.apply(GameEvents::PlayCard(slot)).unwrap();
+    }
+}
+
+//the board goes back to what it was before the buy, and buying again gives the same board
+fn undo_buy(app: &mut App, buy: GameEvents) {
+    let before = BoardSnapshot::capture(&mut app.world);
+    common::push(app, buy);
+    common::settle(app);
+    let bought = BoardSnapshot::capture(&mut app.world);
+    assert_ne!(before, bought);
+
+    assert!(undo(&mut app.world));
+    common::settle(app);
+    assert_eq!(BoardSnapshot::capture(&mut app.world), before);
+
+    assert!(redo(&mut app.world));
+    common::settle(app);
+    assert_eq!(BoardSnapshot::capture(&mut app.world), bought);
+}
+
+#[test]
+fn buying_an_explorer_can_be_undone() {
+    let (mut app, mut state) = started();
+    play_hand(&mut app, &mut state);
+    let buy = GameEvents::BuyCard(BuyFrom::Joker);
+    assert!(state.legal_events_for(0).contains(&buy));
+    undo_buy(&mut app, buy);
+}
+
+//the card refilling the market row goes back face down in the market deck
+#[test]
+fn buying_from_the_market_can_be_undone() {
+    let (mut app, mut state) = started();
+    play_hand(&mut app, &mut state);
+    let buy = state
+        .legal_events_for(0)
+        .into_iter()
+        .find(|event| matches!(event, GameEvents::BuyCard(BuyFrom::Market(_))))
+        .expect("a card of the market row is affordable");
+    let deck = BoardSnapshot::capture(&mut app.world)
+        .cards
+        .into_iter()
+        .filter(|card| card.state.stack == Stacks::MarketDeck)
+        .count();
+    undo_buy(&mut app, buy);
+    assert!(undo(&mut app.world));
+    common::settle(&mut app);
+    let board = BoardSnapshot::capture(&mut app.world);
+    let hidden = board
+        .cards
+        .iter()
+        .filter(|card| card.state.stack == Stacks::MarketDeck)
+        .filter(|card| card.state.visibility == CardVisibility::Hidden)
+        .count();
+    assert_eq!(hidden, deck);
+}